@@ -1,6 +1,21 @@
+use starstruck::helpers::rgba;
+use starstruck::renderer::Mesh;
+use starstruck::shapes::Rectangle;
 use starstruck::StarstruckEngine;
 
 #[tokio::main]
 async fn main() {
-    StarstruckEngine::new("Simple Test").run().await;
+    let (rectangle, _) = Rectangle::new(
+        (0.25, 0.25, 0.5),
+        (0.5, 0.5, -0.5),
+        1.0,
+        rgba(255, 180, 180, 255),
+        rgba(180, 180, 255, 255),
+    )
+    .into_raw();
+
+    StarstruckEngine::new("Simple Test")
+        .add_component(Mesh::new(rectangle).with_cast_shadows(false))
+        .run()
+        .await;
 }
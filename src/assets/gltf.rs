@@ -0,0 +1,546 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use base64::Engine;
+use glam::{Mat4, Vec3};
+use gltf::khr_lights_punctual::Kind;
+use gltf::mesh::Mode;
+use starry_ecs::component::Component;
+use starry_ecs::World;
+use thiserror::Error;
+use wgpu::Color;
+
+use crate::commands::Commands;
+use crate::renderer::{
+    Camera, DirectionalLight, Image, ImageError, ImageId, Images, Mesh, Projection, SpotLight,
+};
+use crate::shapes::Vertex;
+
+/// The extensions the importer understands
+const SUPPORTED_EXTENSIONS: &[&str] = &["KHR_lights_punctual", "KHR_materials_emissive_strength"];
+
+#[derive(Error, Debug)]
+pub enum GltfError {
+    #[error("couldn't read the scene: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid glTF: {0}")]
+    Gltf(#[from] gltf::Error),
+    #[error("buffer {0} is missing, a binary glTF has no BIN chunk")]
+    MissingBuffer(usize),
+    #[error("buffer view {view} goes past the end of buffer {buffer}")]
+    BufferView { view: usize, buffer: usize },
+    #[error("invalid data URI: {0}")]
+    DataUri(#[from] base64::DecodeError),
+    #[error("external file {0} can't be read without the scene's directory")]
+    ExternalFile(String),
+    #[error("couldn't decode image {index}: {source}")]
+    Image { index: usize, source: ImageError },
+}
+
+/// Something in the file the engine ignores, the scene may look different than intended
+#[derive(Debug, Clone, PartialEq)]
+pub enum GltfWarning {
+    /// A `required` extension is one the file says it can't be displayed without
+    UnsupportedExtension { name: String, required: bool },
+    /// Points and lines aren't drawn
+    UnsupportedPrimitive { mesh: String, mode: String },
+    /// Point lights have no equivalent in the engine
+    UnsupportedLight { node: String, kind: String },
+    /// Only PNG images are decoded, the textures using others are left out
+    UnsupportedImage { index: usize, mime_type: String },
+    /// Skins, morph targets and animations aren't imported
+    Ignored { what: String, count: usize },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    #[default]
+    Opaque,
+    /// Transparent below the material's `alpha_cutoff`
+    Mask,
+    Blend,
+}
+
+/// A glTF metallic-roughness material, its textures were added to `Images`.
+///
+/// The engine only draws the base color, the other parameters are kept for custom passes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PbrMaterial {
+    pub name: String,
+    pub base_color: [f32; 4],
+    pub base_color_texture: Option<ImageId>,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Metalness in the blue channel and roughness in the green one
+    pub metallic_roughness_texture: Option<ImageId>,
+    pub normal_texture: Option<ImageId>,
+    pub occlusion_texture: Option<ImageId>,
+    /// Multiplied by the emissive strength
+    pub emissive: [f32; 3],
+    pub emissive_texture: Option<ImageId>,
+    pub alpha_mode: AlphaMode,
+    pub alpha_cutoff: f32,
+    pub double_sided: bool,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            base_color: [1.0; 4],
+            base_color_texture: None,
+            metallic: 1.0,
+            roughness: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive: [0.0; 3],
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfPrimitive {
+    /// Colored by `COLOR_0` only, with zero normals where the file has none
+    pub vertices: Vec<Vertex>,
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfMesh {
+    pub name: String,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SceneLight {
+    Directional(DirectionalLight),
+    Spot(SpotLight),
+}
+
+/// A node of an imported scene, with its camera and light placed in the world
+#[derive(Debug, Clone)]
+pub struct SceneNode {
+    pub name: String,
+    /// Indices into the scene's nodes
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    /// Relative to the parent
+    pub transform: Mat4,
+    pub global_transform: Mat4,
+    pub mesh: Option<usize>,
+    pub camera: Option<Camera>,
+    pub light: Option<SceneLight>,
+}
+
+impl Component for SceneNode {}
+
+/// The default scene of a glTF or GLB file
+#[derive(Debug, Clone, Default)]
+pub struct GltfScene {
+    /// Parents come before their children
+    pub nodes: Vec<SceneNode>,
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<PbrMaterial>,
+    pub warnings: Vec<GltfWarning>,
+}
+
+fn color([r, g, b]: [f32; 3]) -> Color {
+    Color {
+        r: r as f64,
+        g: g as f64,
+        b: b as f64,
+        a: 1.0,
+    }
+}
+
+/// The bytes of an external file or a base64 data URI
+fn read_uri(uri: &str, directory: Option<&Path>) -> Result<Vec<u8>, GltfError> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (_, encoded) = data.split_once(";base64,").unwrap_or(("", data));
+        return Ok(base64::engine::general_purpose::STANDARD.decode(encoded)?);
+    }
+    let directory = directory.ok_or_else(|| GltfError::ExternalFile(String::from(uri)))?;
+    Ok(std::fs::read(directory.join(uri))?)
+}
+
+impl GltfScene {
+    /// Loads a `.gltf` with its external files, or a `.glb`
+    pub fn load(path: impl AsRef<Path>, images: &mut Images) -> Result<Self, GltfError> {
+        let path = path.as_ref();
+        Self::from_slice(&std::fs::read(path)?, path.parent(), images)
+    }
+
+    /// Imports a glTF or GLB file, external buffers and images are read from `directory`
+    pub fn from_slice(
+        bytes: &[u8],
+        directory: Option<&Path>,
+        images: &mut Images,
+    ) -> Result<Self, GltfError> {
+        let gltf::Gltf { document, mut blob } = gltf::Gltf::from_slice(bytes)?;
+        let mut scene = Self::default();
+
+        let required = document.extensions_required().collect::<Vec<_>>();
+        for name in document.extensions_used() {
+            if !SUPPORTED_EXTENSIONS.contains(&name) {
+                scene.warnings.push(GltfWarning::UnsupportedExtension {
+                    name: String::from(name),
+                    required: required.contains(&name),
+                });
+            }
+        }
+        for (what, count) in [
+            ("skins", document.skins().len()),
+            ("animations", document.animations().len()),
+        ] {
+            if count > 0 {
+                scene.warnings.push(GltfWarning::Ignored {
+                    what: String::from(what),
+                    count,
+                });
+            }
+        }
+
+        let buffers = document
+            .buffers()
+            .map(|buffer| match buffer.source() {
+                gltf::buffer::Source::Bin => {
+                    blob.take().ok_or(GltfError::MissingBuffer(buffer.index()))
+                }
+                gltf::buffer::Source::Uri(uri) => read_uri(uri, directory),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut textures = HashMap::new();
+        let mut texture = |info: gltf::texture::Texture<'_>,
+                           scene: &mut Self|
+         -> Result<Option<ImageId>, GltfError> {
+            let image = info.source();
+            if let Some(id) = textures.get(&image.index()) {
+                return Ok(*id);
+            }
+            let (bytes, mime_type) = match image.source() {
+                gltf::image::Source::View { view, mime_type } => {
+                    // Buffers can be shorter than the file says
+                    let bytes = buffers
+                        .get(view.buffer().index())
+                        .and_then(|buffer| buffer.get(view.offset()..view.offset() + view.length()))
+                        .ok_or(GltfError::BufferView {
+                            view: view.index(),
+                            buffer: view.buffer().index(),
+                        })?
+                        .to_vec();
+                    (bytes, String::from(mime_type))
+                }
+                gltf::image::Source::Uri { uri, mime_type } => {
+                    let mime_type = mime_type.map(String::from).unwrap_or_else(|| {
+                        let png = uri.to_lowercase().ends_with(".png")
+                            || uri.starts_with("data:image/png");
+                        String::from(if png { "image/png" } else { "unknown" })
+                    });
+                    (read_uri(uri, directory)?, mime_type)
+                }
+            };
+            let id = if mime_type == "image/png" {
+                let decoded = Image::from_png(&bytes).map_err(|source| GltfError::Image {
+                    index: image.index(),
+                    source,
+                })?;
+                Some(images.add(decoded))
+            } else {
+                scene.warnings.push(GltfWarning::UnsupportedImage {
+                    index: image.index(),
+                    mime_type,
+                });
+                None
+            };
+            textures.insert(image.index(), id);
+            Ok(id)
+        };
+
+        for material in document.materials() {
+            let pbr = material.pbr_metallic_roughness();
+            let strength = material.emissive_strength().unwrap_or(1.0);
+            let imported = PbrMaterial {
+                name: String::from(material.name().unwrap_or_default()),
+                base_color: pbr.base_color_factor(),
+                base_color_texture: match pbr.base_color_texture() {
+                    Some(info) => texture(info.texture(), &mut scene)?,
+                    None => None,
+                },
+                metallic: pbr.metallic_factor(),
+                roughness: pbr.roughness_factor(),
+                metallic_roughness_texture: match pbr.metallic_roughness_texture() {
+                    Some(info) => texture(info.texture(), &mut scene)?,
+                    None => None,
+                },
+                normal_texture: match material.normal_texture() {
+                    Some(info) => texture(info.texture(), &mut scene)?,
+                    None => None,
+                },
+                occlusion_texture: match material.occlusion_texture() {
+                    Some(info) => texture(info.texture(), &mut scene)?,
+                    None => None,
+                },
+                emissive: material.emissive_factor().map(|value| value * strength),
+                emissive_texture: match material.emissive_texture() {
+                    Some(info) => texture(info.texture(), &mut scene)?,
+                    None => None,
+                },
+                alpha_mode: match material.alpha_mode() {
+                    gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+                    gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+                    gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+                },
+                alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+                double_sided: material.double_sided(),
+            };
+            scene.materials.push(imported);
+        }
+
+        for mesh in document.meshes() {
+            let name = String::from(mesh.name().unwrap_or_default());
+            let mut primitives = vec![];
+            for primitive in mesh.primitives() {
+                let reader =
+                    primitive.reader(|buffer| buffers.get(buffer.index()).map(Vec::as_slice));
+                let Some(positions) = reader.read_positions() else {
+                    continue;
+                };
+                let positions = positions.collect::<Vec<_>>();
+                let normals = reader
+                    .read_normals()
+                    .map(|normals| normals.collect::<Vec<_>>());
+                let uvs = reader
+                    .read_tex_coords(0)
+                    .map(|uvs| uvs.into_f32().collect::<Vec<_>>());
+                let colors = reader
+                    .read_colors(0)
+                    .map(|colors| colors.into_rgba_f32().collect::<Vec<_>>());
+                let vertex = |index: u32| {
+                    let index = index as usize;
+                    let [x, y, z] = positions.get(index).copied().unwrap_or_default();
+                    Vertex {
+                        position: [x, y, z, 1.0],
+                        color: colors
+                            .as_ref()
+                            .and_then(|colors| colors.get(index).copied())
+                            .unwrap_or([1.0; 4]),
+                        normal: normals
+                            .as_ref()
+                            .and_then(|normals| normals.get(index).copied())
+                            .unwrap_or_default(),
+                        uv: uvs
+                            .as_ref()
+                            .and_then(|uvs| uvs.get(index).copied())
+                            .unwrap_or_default(),
+                    }
+                };
+                let indices = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+                    None => (0..positions.len() as u32).collect(),
+                };
+
+                let triangles = match primitive.mode() {
+                    Mode::Triangles => indices,
+                    Mode::TriangleStrip => (2..indices.len())
+                        .flat_map(|i| {
+                            // Every other triangle of a strip is wound the other way
+                            if i % 2 == 0 {
+                                [indices[i - 2], indices[i - 1], indices[i]]
+                            } else {
+                                [indices[i - 1], indices[i - 2], indices[i]]
+                            }
+                        })
+                        .collect(),
+                    Mode::TriangleFan => (2..indices.len())
+                        .flat_map(|i| [indices[0], indices[i - 1], indices[i]])
+                        .collect(),
+                    mode => {
+                        scene.warnings.push(GltfWarning::UnsupportedPrimitive {
+                            mesh: name.clone(),
+                            mode: format!("{:?}", mode),
+                        });
+                        continue;
+                    }
+                };
+                if reader.read_morph_targets().next().is_some() {
+                    scene.warnings.push(GltfWarning::Ignored {
+                        what: format!("morph targets of mesh {:?}", name),
+                        count: primitive.morph_targets().len(),
+                    });
+                }
+                primitives.push(GltfPrimitive {
+                    vertices: triangles.into_iter().map(vertex).collect(),
+                    material: primitive.material().index(),
+                });
+            }
+            scene.meshes.push(GltfMesh { name, primitives });
+        }
+
+        let Some(root) = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+        else {
+            return Ok(scene);
+        };
+        // Depth first, in the order of the file
+        let mut stack = root
+            .nodes()
+            .map(|node| (node, None::<usize>))
+            .collect::<Vec<_>>();
+        stack.reverse();
+        while let Some((node, parent)) = stack.pop() {
+            let index = scene.nodes.len();
+            let transform = Mat4::from_cols_array_2d(&node.transform().matrix());
+            let global_transform = match parent {
+                Some(parent) => scene.nodes[parent].global_transform * transform,
+                None => transform,
+            };
+            let name = String::from(node.name().unwrap_or_default());
+            let light = node
+                .light()
+                .and_then(|light| scene.light(&name, light, global_transform));
+            scene.nodes.push(SceneNode {
+                camera: node
+                    .camera()
+                    .map(|camera| Self::camera(camera, global_transform)),
+                light,
+                name,
+                parent,
+                children: vec![],
+                transform,
+                global_transform,
+                mesh: node.mesh().map(|mesh| mesh.index()),
+            });
+            if let Some(parent) = parent {
+                scene.nodes[parent].children.push(index);
+            }
+            let children = node.children().collect::<Vec<_>>();
+            stack.extend(children.into_iter().rev().map(|child| (child, Some(index))));
+        }
+        Ok(scene)
+    }
+
+    /// glTF cameras look down their -Z axis
+    fn camera(camera: gltf::Camera<'_>, transform: Mat4) -> Camera {
+        let position = transform.transform_point3(Vec3::ZERO);
+        let projection = match camera.projection() {
+            gltf::camera::Projection::Perspective(perspective) => Projection::Perspective {
+                fov_y_degrees: perspective.yfov().to_degrees(),
+                near: perspective.znear(),
+                far: perspective.zfar().unwrap_or(1000.0),
+            },
+            gltf::camera::Projection::Orthographic(orthographic) => Projection::Orthographic {
+                height: orthographic.ymag() * 2.0,
+                near: orthographic.znear(),
+                far: orthographic.zfar(),
+            },
+        };
+        Camera {
+            position,
+            look_at: position + transform.transform_vector3(Vec3::NEG_Z),
+            up: transform.transform_vector3(Vec3::Y).normalize_or_zero(),
+            projection,
+            ..Default::default()
+        }
+    }
+
+    /// Intensities are kept as they are, the engine's lights have no physical units
+    fn light(
+        &mut self,
+        node: &str,
+        light: gltf::khr_lights_punctual::Light<'_>,
+        transform: Mat4,
+    ) -> Option<SceneLight> {
+        let direction = transform.transform_vector3(Vec3::NEG_Z).normalize_or_zero();
+        match light.kind() {
+            Kind::Directional => Some(SceneLight::Directional(DirectionalLight {
+                direction,
+                color: color(light.color()),
+                illuminance: light.intensity(),
+                ..Default::default()
+            })),
+            Kind::Spot {
+                inner_cone_angle,
+                outer_cone_angle,
+            } => Some(SceneLight::Spot(SpotLight {
+                position: transform.transform_point3(Vec3::ZERO),
+                direction,
+                color: color(light.color()),
+                intensity: light.intensity(),
+                range: light.range().unwrap_or(SpotLight::default().range),
+                inner_angle_degrees: inner_cone_angle.to_degrees(),
+                outer_angle_degrees: outer_cone_angle.to_degrees(),
+                ..Default::default()
+            })),
+            Kind::Point => {
+                self.warnings.push(GltfWarning::UnsupportedLight {
+                    node: String::from(node),
+                    kind: String::from("point"),
+                });
+                None
+            }
+        }
+    }
+
+    /// A mesh for every primitive of every node, colored and textured by its material
+    pub fn world_meshes(&self) -> Vec<Mesh> {
+        let mut meshes = vec![];
+        for node in self.nodes.iter() {
+            let Some(mesh) = node.mesh.and_then(|mesh| self.meshes.get(mesh)) else {
+                continue;
+            };
+            for primitive in mesh.primitives.iter() {
+                let material = primitive
+                    .material
+                    .and_then(|material| self.materials.get(material));
+                let base_color = material.map_or([1.0; 4], |material| material.base_color);
+                let vertices = primitive
+                    .vertices
+                    .iter()
+                    .map(|vertex| Vertex {
+                        color: [0, 1, 2, 3].map(|i| vertex.color[i] * base_color[i]),
+                        ..*vertex
+                    })
+                    .collect();
+                let mut result = Mesh::new(vertices).with_transform(node.global_transform);
+                if let Some(texture) = material.and_then(|material| material.base_color_texture) {
+                    result = result.with_texture(texture);
+                }
+                meshes.push(result);
+            }
+        }
+        meshes
+    }
+
+    /// Adds the nodes, meshes, cameras and lights to the world
+    pub fn spawn(&self, world: World) -> World {
+        let mut world = world;
+        for mesh in self.world_meshes() {
+            world = world.add_component(mesh);
+        }
+        for node in self.nodes.iter() {
+            if let Some(camera) = node.camera.clone() {
+                world = world.add_component(camera);
+            }
+            world = match node.light.clone() {
+                Some(SceneLight::Directional(light)) => world.add_component(light),
+                Some(SceneLight::Spot(light)) => world.add_component(light),
+                None => world,
+            };
+            world = world.add_component(node.clone());
+        }
+        world
+    }
+
+    /// Spawns the scene once the commands are applied, for systems
+    pub fn spawn_with(&self, commands: &mut Commands) {
+        let scene = self.clone();
+        commands.add(move |world| scene.spawn(world));
+    }
+}
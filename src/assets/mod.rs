@@ -0,0 +1,20 @@
+pub mod gltf;
+pub mod obj;
+pub mod pak;
+pub mod ply;
+pub mod reload;
+pub mod server;
+pub mod stl;
+
+pub use crate::assets::gltf::{
+    AlphaMode, GltfError, GltfMesh, GltfPrimitive, GltfScene, GltfWarning, PbrMaterial, SceneLight,
+    SceneNode,
+};
+pub use crate::assets::obj::{ObjError, ObjMaterial, ObjMesh, ObjModel};
+pub use crate::assets::pak::{Compression, Packer, Pak, PakError};
+pub use crate::assets::ply::{PlyError, PlyFormat};
+pub use crate::assets::reload::sync_assets_system;
+pub use crate::assets::server::{
+    AssetError, AssetPlugin, AssetServer, AssetSource, Handle, LoadContext, LoadState, Shader,
+};
+pub use crate::assets::stl::{StlError, StlFormat};
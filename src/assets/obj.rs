@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use glam::{Mat3, Vec3, Vec4};
+use thiserror::Error;
+
+use crate::assets::AssetSource;
+use crate::renderer::{Image, ImageError, Images, Mesh};
+use crate::shapes::Vertex;
+
+#[derive(Error, Debug)]
+pub enum ObjError {
+    #[error("couldn't read the model: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("couldn't load a texture: {0}")]
+    Texture(#[from] ImageError),
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> ObjError {
+    ObjError::Parse {
+        line,
+        message: message.into(),
+    }
+}
+
+fn numbers<'a>(line: usize, words: impl Iterator<Item = &'a str>) -> Result<Vec<f32>, ObjError> {
+    words
+        .map(|word| {
+            word.parse::<f32>()
+                .map_err(|_| parse_error(line, format!("invalid number {:?}", word)))
+        })
+        .collect()
+}
+
+/// The parts of an MTL material the engine can draw
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    /// `Kd` with the opacity from `d` or `Tr`
+    pub diffuse: [f32; 4],
+    /// `map_Kd`, relative to the MTL file when loaded from disk
+    pub texture: Option<PathBuf>,
+}
+
+impl ObjMaterial {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            diffuse: [1.0; 4],
+            texture: None,
+        }
+    }
+
+    pub fn with_diffuse(mut self, diffuse: [f32; 4]) -> Self {
+        self.diffuse = diffuse;
+        self
+    }
+
+    pub fn with_texture(mut self, texture: impl Into<PathBuf>) -> Self {
+        self.texture = Some(texture.into());
+        self
+    }
+
+    /// Parses an MTL file, texture paths are joined to `directory`
+    pub fn parse_mtl(source: &str, directory: &Path) -> Result<Vec<Self>, ObjError> {
+        let mut materials: Vec<Self> = vec![];
+        for (index, line) in source.lines().enumerate() {
+            let number = index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            if keyword == "newmtl" {
+                materials.push(Self::new(line[keyword.len()..].trim()));
+                continue;
+            }
+            let Some(material) = materials.last_mut() else {
+                return Err(parse_error(number, format!("{} before newmtl", keyword)));
+            };
+            match keyword {
+                "Kd" => {
+                    let values = numbers(number, words)?;
+                    let [r, g, b] = values[..] else {
+                        return Err(parse_error(number, "Kd needs 3 numbers"));
+                    };
+                    material.diffuse = [r, g, b, material.diffuse[3]];
+                }
+                "d" | "Tr" => {
+                    let values = numbers(number, words)?;
+                    let [value] = values[..] else {
+                        return Err(parse_error(number, format!("{} needs a number", keyword)));
+                    };
+                    material.diffuse[3] = if keyword == "d" { value } else { 1.0 - value };
+                }
+                // Options like `-s 2 2 1` come before the file
+                "map_Kd" => match words.last() {
+                    Some(file) => material.texture = Some(directory.join(file)),
+                    None => return Err(parse_error(number, "map_Kd needs a file")),
+                },
+                _ => {}
+            }
+        }
+        Ok(materials)
+    }
+}
+
+/// The faces of a group of an OBJ file that use the same material
+#[derive(Debug, Clone)]
+pub struct ObjMesh {
+    pub name: String,
+    pub material: Option<usize>,
+    /// Colored by the file's vertex colors only, with zero normals where it has none
+    pub vertices: Vec<Vertex>,
+}
+
+/// A Wavefront OBJ model with its MTL materials
+#[derive(Debug, Clone, Default)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+}
+
+impl ObjModel {
+    /// A model with a single mesh, like the vertices of a built-in shape
+    pub fn from_vertices(name: &str, vertices: Vec<Vertex>) -> Self {
+        Self {
+            meshes: vec![ObjMesh {
+                name: String::from(name),
+                material: None,
+                vertices,
+            }],
+            materials: vec![],
+        }
+    }
+
+    /// A model with the vertices of `mesh` moved by its transform
+    pub fn from_mesh(name: &str, mesh: &Mesh) -> Self {
+        let normal_matrix = Mat3::from_mat4(mesh.transform).inverse().transpose();
+        let vertices = mesh
+            .vertices
+            .iter()
+            .map(|vertex| Vertex {
+                position: (mesh.transform * Vec4::from(vertex.position)).to_array(),
+                normal: (normal_matrix * Vec3::from(vertex.normal))
+                    .normalize_or_zero()
+                    .to_array(),
+                ..*vertex
+            })
+            .collect();
+        Self::from_vertices(name, vertices)
+    }
+
+    /// Loads an OBJ file and the MTL files it refers to, which are skipped when missing
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        Self::parse_with(
+            &std::fs::read_to_string(path)?,
+            |file| match std::fs::read_to_string(directory.join(file)) {
+                Ok(source) => ObjMaterial::parse_mtl(&source, &directory),
+                Err(error) => {
+                    tracing::warn!("Couldn't read the material library {}: {}", file, error);
+                    Ok(vec![])
+                }
+            },
+        )
+    }
+
+    /// Parses an OBJ file without reading its material libraries,
+    /// the materials it uses are white
+    pub fn parse(source: &str) -> Result<Self, ObjError> {
+        Self::parse_with(source, |_| Ok(vec![]))
+    }
+
+    pub(crate) fn parse_with(
+        source: &str,
+        mut material_library: impl FnMut(&str) -> Result<Vec<ObjMaterial>, ObjError>,
+    ) -> Result<Self, ObjError> {
+        let mut model = Self::default();
+        let mut positions: Vec<([f32; 4], [f32; 4])> = vec![];
+        let mut uvs: Vec<[f32; 2]> = vec![];
+        let mut normals: Vec<[f32; 3]> = vec![];
+        let mut name = String::new();
+        let mut material = None;
+        let mut current = None;
+
+        for (index, line) in source.lines().enumerate() {
+            let number = index + 1;
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut words = line.split_whitespace();
+            let Some(keyword) = words.next() else {
+                continue;
+            };
+            match keyword {
+                "v" => {
+                    let values = numbers(number, words)?;
+                    let vertex = match values[..] {
+                        [x, y, z] => ([x, y, z, 1.0], [1.0; 4]),
+                        [x, y, z, w] => ([x, y, z, w], [1.0; 4]),
+                        // Vertex colors, as written by Blender and most scanners
+                        [x, y, z, r, g, b] => ([x, y, z, 1.0], [r, g, b, 1.0]),
+                        _ => return Err(parse_error(number, "v needs 3, 4 or 6 numbers")),
+                    };
+                    positions.push(vertex);
+                }
+                "vt" => {
+                    let values = numbers(number, words)?;
+                    let (u, v) = match values[..] {
+                        [u] => (u, 0.0),
+                        [u, v, ..] => (u, v),
+                        _ => return Err(parse_error(number, "vt needs a number")),
+                    };
+                    // OBJ textures start at the bottom
+                    uvs.push([u, 1.0 - v]);
+                }
+                "vn" => {
+                    let values = numbers(number, words)?;
+                    let [x, y, z] = values[..] else {
+                        return Err(parse_error(number, "vn needs 3 numbers"));
+                    };
+                    normals.push([x, y, z]);
+                }
+                "f" => {
+                    let corners = words
+                        .map(|corner| {
+                            let mut indices = corner.split('/');
+                            let mut next = |count: usize| {
+                                Self::index(number, indices.next().unwrap_or_default(), count)
+                            };
+                            let position = next(positions.len())?.ok_or_else(|| {
+                                parse_error(number, format!("{:?} has no position", corner))
+                            })?;
+                            let (position, color) = positions[position];
+                            Ok(Vertex {
+                                position,
+                                color,
+                                uv: next(uvs.len())?.map_or([0.0; 2], |uv| uvs[uv]),
+                                normal: next(normals.len())?.map_or([0.0; 3], |n| normals[n]),
+                            })
+                        })
+                        .collect::<Result<Vec<_>, ObjError>>()?;
+                    if corners.len() < 3 {
+                        return Err(parse_error(number, "a face needs 3 corners"));
+                    }
+
+                    let mesh = *current.get_or_insert_with(|| {
+                        let existing = model
+                            .meshes
+                            .iter()
+                            .position(|mesh| mesh.name == name && mesh.material == material);
+                        existing.unwrap_or_else(|| {
+                            model.meshes.push(ObjMesh {
+                                name: name.clone(),
+                                material,
+                                vertices: vec![],
+                            });
+                            model.meshes.len() - 1
+                        })
+                    });
+                    // Polygons are split into a fan
+                    for i in 1..corners.len() - 1 {
+                        let vertices = &mut model.meshes[mesh].vertices;
+                        vertices.extend([corners[0], corners[i], corners[i + 1]]);
+                    }
+                }
+                "o" | "g" => {
+                    name = String::from(line[keyword.len()..].trim());
+                    current = None;
+                }
+                "usemtl" => {
+                    let used = line[keyword.len()..].trim();
+                    let existing = model.materials.iter().position(|m| m.name == used);
+                    material = Some(existing.unwrap_or_else(|| {
+                        model.materials.push(ObjMaterial::new(used));
+                        model.materials.len() - 1
+                    }));
+                    current = None;
+                }
+                "mtllib" => {
+                    for file in words {
+                        model.materials.extend(material_library(file)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(model)
+    }
+
+    /// An index of a face corner, 1-based or negative from the end
+    fn index(line: usize, index: &str, count: usize) -> Result<Option<usize>, ObjError> {
+        if index.is_empty() {
+            return Ok(None);
+        }
+        let value = index
+            .parse::<i64>()
+            .map_err(|_| parse_error(line, format!("invalid index {:?}", index)))?;
+        let resolved = if value < 0 {
+            count as i64 + value
+        } else {
+            value - 1
+        };
+        if resolved < 0 || resolved >= count as i64 {
+            return Err(parse_error(
+                line,
+                format!("index {} is out of range", value),
+            ));
+        }
+        Ok(Some(resolved as usize))
+    }
+
+    /// Engine meshes colored by their material, material textures are added to `images`
+    pub fn meshes(&self, images: &mut Images) -> Result<Vec<Mesh>, ObjError> {
+        self.meshes_with(images, |path| std::fs::read(path))
+    }
+
+    /// Like `meshes`, with textures read from `source`, for models loaded by an `AssetServer`
+    pub fn meshes_from(
+        &self,
+        images: &mut Images,
+        source: &AssetSource,
+    ) -> Result<Vec<Mesh>, ObjError> {
+        self.meshes_with(images, |path| source.read(path))
+    }
+
+    fn meshes_with(
+        &self,
+        images: &mut Images,
+        read: impl Fn(&Path) -> std::io::Result<Vec<u8>>,
+    ) -> Result<Vec<Mesh>, ObjError> {
+        let mut textures = HashMap::new();
+        let mut meshes = vec![];
+        for mesh in self.meshes.iter() {
+            let material = mesh.material.and_then(|index| self.materials.get(index));
+            let diffuse = material.map_or([1.0; 4], |material| material.diffuse);
+            let vertices = mesh
+                .vertices
+                .iter()
+                .map(|vertex| Vertex {
+                    color: [0, 1, 2, 3].map(|i| vertex.color[i] * diffuse[i]),
+                    ..*vertex
+                })
+                .collect();
+            let mut result = Mesh::new(vertices);
+            if let Some(path) = material.and_then(|material| material.texture.as_ref()) {
+                let texture = match textures.get(path) {
+                    Some(texture) => *texture,
+                    None => {
+                        let image = read(path).map_err(ImageError::from)?;
+                        let texture = images.add(Image::from_png(&image)?);
+                        textures.insert(path.clone(), texture);
+                        texture
+                    }
+                };
+                result = result.with_texture(texture);
+            }
+            meshes.push(result);
+        }
+        Ok(meshes)
+    }
+
+    /// The 1-based index of a value, written to `obj` as `line` the first time it's seen
+    fn shared<const N: usize>(
+        seen: &mut HashMap<[u32; N], usize>,
+        obj: &mut String,
+        value: [f32; N],
+        line: String,
+    ) -> usize {
+        let next = seen.len() + 1;
+        *seen.entry(value.map(f32::to_bits)).or_insert_with(|| {
+            obj.push_str(&line);
+            obj.push('\n');
+            next
+        })
+    }
+
+    /// The model as an OBJ file, vertex colors are written unless they're all white
+    pub fn to_obj(&self, material_library: Option<&str>) -> String {
+        let vertices = || self.meshes.iter().flat_map(|mesh| mesh.vertices.iter());
+        let colored = vertices().any(|vertex| vertex.color != [1.0; 4]);
+
+        let mut obj = String::new();
+        if let Some(library) = material_library {
+            let _ = writeln!(obj, "mtllib {}", library);
+        }
+        // Shared positions, texture coordinates and normals are written once
+        let mut positions = HashMap::new();
+        let mut uvs = HashMap::new();
+        let mut normals = HashMap::new();
+        let mut faces = String::new();
+        for mesh in self.meshes.iter() {
+            if !mesh.name.is_empty() {
+                let _ = writeln!(faces, "o {}", mesh.name);
+            }
+            if let Some(material) = mesh.material.and_then(|index| self.materials.get(index)) {
+                let _ = writeln!(faces, "usemtl {}", material.name);
+            }
+            for triangle in mesh.vertices.chunks_exact(3) {
+                // Faces with a missing normal stay flat
+                let smooth = triangle.iter().all(|vertex| vertex.normal != [0.0; 3]);
+                faces.push('f');
+                for vertex in triangle {
+                    let [x, y, z, w] = vertex.position;
+                    let (x, y, z) = (x / w, y / w, z / w);
+                    let [r, g, b, _] = vertex.color;
+                    let line = if colored {
+                        format!("v {} {} {} {} {} {}", x, y, z, r, g, b)
+                    } else {
+                        format!("v {} {} {}", x, y, z)
+                    };
+                    let position = Self::shared(&mut positions, &mut obj, [x, y, z, r, g, b], line);
+                    let [u, v] = vertex.uv;
+                    let line = format!("vt {} {}", u, 1.0 - v);
+                    let uv = Self::shared(&mut uvs, &mut obj, [u, v], line);
+                    let _ = write!(faces, " {}/{}", position, uv);
+                    if smooth {
+                        let [x, y, z] = vertex.normal;
+                        let line = format!("vn {} {} {}", x, y, z);
+                        let normal = Self::shared(&mut normals, &mut obj, [x, y, z], line);
+                        let _ = write!(faces, "/{}", normal);
+                    }
+                }
+                faces.push('\n');
+            }
+        }
+        obj.push_str(&faces);
+        obj
+    }
+
+    pub fn to_mtl(&self) -> String {
+        self.to_mtl_in(Path::new(""))
+    }
+
+    /// Textures under `directory` are written relative to it, like `parse_mtl` reads them
+    fn to_mtl_in(&self, directory: &Path) -> String {
+        let mut mtl = String::new();
+        for material in self.materials.iter() {
+            let [r, g, b, a] = material.diffuse;
+            let _ = writeln!(
+                mtl,
+                "newmtl {}\nKd {} {} {}\nd {}",
+                material.name, r, g, b, a
+            );
+            if let Some(texture) = material.texture.as_ref() {
+                let texture = texture.strip_prefix(directory).unwrap_or(texture);
+                let _ = writeln!(mtl, "map_Kd {}", texture.display());
+            }
+            mtl.push('\n');
+        }
+        mtl
+    }
+
+    /// Writes the model, and its materials next to it with the `mtl` extension
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ObjError> {
+        let path = path.as_ref();
+        let mut library = None;
+        if !self.materials.is_empty() {
+            let mtl = path.with_extension("mtl");
+            let directory = path.parent().unwrap_or(Path::new(""));
+            std::fs::write(&mtl, self.to_mtl_in(directory))?;
+            library = mtl
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned());
+        }
+        std::fs::write(path, self.to_obj(library.as_deref()))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path};
+use std::sync::Mutex;
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use thiserror::Error;
+
+const MAGIC: &[u8; 4] = b"SPAK";
+const VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum PakError {
+    #[error("couldn't read or write the archive: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the file isn't an asset archive")]
+    NotAPak,
+    #[error("archives of version {0} aren't supported")]
+    Version(u32),
+    #[error("the archive index is damaged")]
+    Index,
+    #[error("{0} isn't in the archive")]
+    NotFound(String),
+    #[error("{0} can't be stored, archive paths must be relative, UTF-8 and shorter than 64 KiB")]
+    InvalidPath(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    None,
+    /// Files that don't get smaller are stored as they are
+    #[default]
+    Deflate,
+}
+
+/// The archive path of `path`, with `/` separators and `.` and `..` resolved
+pub(crate) fn archive_path(path: &Path) -> Option<String> {
+    let mut parts = vec![];
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(parts.join("/"))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PakEntry {
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+    compressed: bool,
+}
+
+/// Bundles files into an archive with an index, see `Pak` for reading it back.
+///
+/// ```no_run
+/// use starstruck::assets::Packer;
+///
+/// Packer::new().with_directory("assets").unwrap().save("assets.pak").unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct Packer {
+    compression: Compression,
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl Packer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Adds a file under `path`, replacing one added at the same path
+    pub fn add_file(&mut self, path: impl AsRef<Path>, bytes: Vec<u8>) -> Result<(), PakError> {
+        let path = path.as_ref();
+        let name = archive_path(path)
+            .filter(|name| !name.is_empty() && name.len() <= u16::MAX as usize)
+            .ok_or_else(|| PakError::InvalidPath(path.display().to_string()))?;
+        self.files.insert(name, bytes);
+        Ok(())
+    }
+
+    /// Adds every file under `directory`, at paths relative to it
+    pub fn add_directory(&mut self, directory: impl AsRef<Path>) -> Result<(), PakError> {
+        let directory = directory.as_ref();
+        let mut pending = vec![directory.to_path_buf()];
+        while let Some(current) = pending.pop() {
+            for entry in std::fs::read_dir(&current)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else {
+                    let relative = path.strip_prefix(directory).unwrap_or(&path);
+                    self.add_file(relative, std::fs::read(&path)?)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn with_directory(mut self, directory: impl AsRef<Path>) -> Result<Self, PakError> {
+        self.add_directory(directory)?;
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Writes the header and index, then the data of every file
+    pub fn write(&self, mut writer: impl Write) -> Result<(), PakError> {
+        let mut stored = vec![];
+        for (name, bytes) in &self.files {
+            let compressed = match self.compression {
+                Compression::None => None,
+                Compression::Deflate => {
+                    let mut encoder = DeflateEncoder::new(vec![], flate2::Compression::default());
+                    encoder.write_all(bytes)?;
+                    Some(encoder.finish()?).filter(|compressed| compressed.len() < bytes.len())
+                }
+            };
+            stored.push((name, bytes.len() as u64, compressed));
+        }
+
+        let index_size = stored
+            .iter()
+            .map(|(name, ..)| 2 + name.len() + 8 * 3 + 1)
+            .sum::<usize>();
+        let mut offset = (MAGIC.len() + 4 + 4 + index_size) as u64;
+        let mut header = MAGIC.to_vec();
+        header.extend_from_slice(&VERSION.to_le_bytes());
+        header.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+        for (name, size, compressed) in &stored {
+            let stored_size = compressed.as_ref().map_or(*size, |data| data.len() as u64);
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(name.as_bytes());
+            header.extend_from_slice(&offset.to_le_bytes());
+            header.extend_from_slice(&stored_size.to_le_bytes());
+            header.extend_from_slice(&size.to_le_bytes());
+            header.push(compressed.is_some() as u8);
+            offset += stored_size;
+        }
+        writer.write_all(&header)?;
+        for (name, _, compressed) in &stored {
+            writer.write_all(compressed.as_deref().unwrap_or(&self.files[name.as_str()]))?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PakError> {
+        let mut file = std::io::BufWriter::new(File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// An archive made by `Packer`, read a file at a time
+#[derive(Debug)]
+pub struct Pak {
+    file: Mutex<File>,
+    entries: BTreeMap<String, PakEntry>,
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], PakError> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes).map_err(|_| PakError::Index)?;
+    Ok(bytes)
+}
+
+impl Pak {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, PakError> {
+        let mut file = File::open(path)?;
+        let length = file.metadata()?.len();
+        let mut reader = std::io::BufReader::new(&mut file);
+        let magic = read_bytes::<4>(&mut reader).map_err(|_| PakError::NotAPak)?;
+        if &magic != MAGIC {
+            return Err(PakError::NotAPak);
+        }
+        let version = u32::from_le_bytes(read_bytes(&mut reader)?);
+        if version != VERSION {
+            return Err(PakError::Version(version));
+        }
+
+        let count = u32::from_le_bytes(read_bytes(&mut reader)?);
+        let mut entries = BTreeMap::new();
+        for _ in 0..count {
+            let mut name = vec![0; u16::from_le_bytes(read_bytes(&mut reader)?) as usize];
+            reader.read_exact(&mut name).map_err(|_| PakError::Index)?;
+            let name = String::from_utf8(name).map_err(|_| PakError::Index)?;
+            let entry = PakEntry {
+                offset: u64::from_le_bytes(read_bytes(&mut reader)?),
+                stored_size: u64::from_le_bytes(read_bytes(&mut reader)?),
+                size: u64::from_le_bytes(read_bytes(&mut reader)?),
+                compressed: read_bytes::<1>(&mut reader)?[0] != 0,
+            };
+            // Reads trust the index, so it has to stay inside the file
+            let inside = entry
+                .offset
+                .checked_add(entry.stored_size)
+                .is_some_and(|end| end <= length);
+            if !inside || (!entry.compressed && entry.stored_size != entry.size) {
+                return Err(PakError::Index);
+            }
+            entries.insert(name, entry);
+        }
+        Ok(Self {
+            file: Mutex::new(file),
+            entries,
+        })
+    }
+
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        archive_path(path.as_ref()).is_some_and(|name| self.entries.contains_key(&name))
+    }
+
+    /// The paths of every file, sorted
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// The size of a file once it is read, along with the size it takes in the archive
+    pub fn size(&self, path: impl AsRef<Path>) -> Option<(u64, u64)> {
+        let entry = self.entries.get(&archive_path(path.as_ref())?)?;
+        Some((entry.size, entry.stored_size))
+    }
+
+    pub fn read(&self, path: impl AsRef<Path>) -> Result<Vec<u8>, PakError> {
+        let path = path.as_ref();
+        let entry = archive_path(path)
+            .and_then(|name| self.entries.get(&name))
+            .ok_or_else(|| PakError::NotFound(path.display().to_string()))?;
+        let mut stored = vec![0; entry.stored_size as usize];
+        {
+            let mut file = self.file.lock().unwrap();
+            file.seek(SeekFrom::Start(entry.offset))?;
+            file.read_exact(&mut stored)?;
+        }
+        if !entry.compressed {
+            return Ok(stored);
+        }
+        // The size comes from the file, so it only limits the output instead of reserving it
+        let mut bytes = vec![];
+        DeflateDecoder::new(stored.as_slice())
+            .take(entry.size)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() as u64 != entry.size {
+            return Err(PakError::Index);
+        }
+        Ok(bytes)
+    }
+}
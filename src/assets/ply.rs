@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use glam::{Mat3, Vec3, Vec4};
+use thiserror::Error;
+
+use crate::renderer::Mesh;
+use crate::shapes::Vertex;
+
+#[derive(Error, Debug)]
+pub enum PlyError {
+    #[error("couldn't read the PLY file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid PLY header: {0}")]
+    Header(String),
+    #[error("invalid {element} {index}: {message}")]
+    Parse {
+        element: String,
+        index: usize,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlyFormat {
+    Ascii,
+    #[default]
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scalar {
+    Char,
+    UChar,
+    Short,
+    UShort,
+    Int,
+    UInt,
+    Float,
+    Double,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Result<Self, PlyError> {
+        Ok(match name {
+            "char" | "int8" => Self::Char,
+            "uchar" | "uint8" => Self::UChar,
+            "short" | "int16" => Self::Short,
+            "ushort" | "uint16" => Self::UShort,
+            "int" | "int32" => Self::Int,
+            "uint" | "uint32" => Self::UInt,
+            "float" | "float32" => Self::Float,
+            "double" | "float64" => Self::Double,
+            _ => return Err(PlyError::Header(format!("unknown type {:?}", name))),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::Char | Self::UChar => 1,
+            Self::Short | Self::UShort => 2,
+            Self::Int | Self::UInt | Self::Float => 4,
+            Self::Double => 8,
+        }
+    }
+
+    /// The value that maps to a full color channel
+    fn full(self) -> f64 {
+        match self {
+            Self::Char => i8::MAX as f64,
+            Self::UChar => u8::MAX as f64,
+            Self::Short => i16::MAX as f64,
+            Self::UShort => u16::MAX as f64,
+            Self::Int => i32::MAX as f64,
+            Self::UInt => u32::MAX as f64,
+            Self::Float | Self::Double => 1.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Property {
+    Scalar(String, Scalar),
+    List {
+        name: String,
+        count: Scalar,
+        item: Scalar,
+    },
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+enum Body<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary {
+        bytes: &'a [u8],
+        position: usize,
+        big_endian: bool,
+    },
+}
+
+impl Body<'_> {
+    fn read(&mut self, scalar: Scalar) -> Option<f64> {
+        match self {
+            Self::Ascii(words) => words.next()?.parse().ok(),
+            Self::Binary {
+                bytes,
+                position,
+                big_endian,
+            } => {
+                let size = scalar.size();
+                let mut value = [0; 8];
+                value[..size].copy_from_slice(bytes.get(*position..*position + size)?);
+                if *big_endian {
+                    value[..size].reverse();
+                }
+                *position += size;
+                let [a, b, c, d, ..] = value;
+                Some(match scalar {
+                    Scalar::Char => a as i8 as f64,
+                    Scalar::UChar => a as f64,
+                    Scalar::Short => i16::from_le_bytes([a, b]) as f64,
+                    Scalar::UShort => u16::from_le_bytes([a, b]) as f64,
+                    Scalar::Int => i32::from_le_bytes([a, b, c, d]) as f64,
+                    Scalar::UInt => u32::from_le_bytes([a, b, c, d]) as f64,
+                    Scalar::Float => f32::from_le_bytes([a, b, c, d]) as f64,
+                    Scalar::Double => f64::from_le_bytes(value),
+                })
+            }
+        }
+    }
+}
+
+fn parse_header(header: &str) -> Result<(PlyFormat, Vec<Element>), PlyError> {
+    let error = |message: &str| PlyError::Header(message.to_string());
+    let mut lines = header.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err(error("the file doesn't start with \"ply\""));
+    }
+    let mut format = None;
+    let mut elements = Vec::<Element>::new();
+    for line in lines {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words[..] {
+            ["format", name, _] => {
+                format = Some(match name {
+                    "ascii" => PlyFormat::Ascii,
+                    "binary_little_endian" => PlyFormat::BinaryLittleEndian,
+                    "binary_big_endian" => PlyFormat::BinaryBigEndian,
+                    _ => return Err(PlyError::Header(format!("unknown format {:?}", name))),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count
+                    .parse()
+                    .map_err(|_| PlyError::Header(format!("invalid count {:?}", count)))?,
+                properties: vec![],
+            }),
+            ["property", ..] => {
+                let element = elements
+                    .last_mut()
+                    .ok_or_else(|| error("a property comes before any element"))?;
+                element.properties.push(match words[1..] {
+                    ["list", count, item, name] => Property::List {
+                        name: name.to_string(),
+                        count: Scalar::parse(count)?,
+                        item: Scalar::parse(item)?,
+                    },
+                    [scalar, name] => Property::Scalar(name.to_string(), Scalar::parse(scalar)?),
+                    _ => return Err(PlyError::Header(format!("invalid property {:?}", line))),
+                });
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => {}
+            _ => return Err(PlyError::Header(format!("unknown line {:?}", line))),
+        }
+    }
+    Ok((
+        format.ok_or_else(|| error("the format is missing"))?,
+        elements,
+    ))
+}
+
+/// The position of a vertex property, along with the value of a full color channel
+fn column(element: &Element, names: &[&str]) -> Option<(usize, f64)> {
+    element
+        .properties
+        .iter()
+        .enumerate()
+        .find_map(|(index, property)| match property {
+            Property::Scalar(name, scalar) if names.contains(&name.as_str()) => {
+                Some((index, scalar.full()))
+            }
+            _ => None,
+        })
+}
+
+fn vertex(element: &Element, row: &[f64]) -> Option<Vertex> {
+    let value = |names: &[&str]| column(element, names).map(|(index, _)| row[index] as f32);
+    let channel =
+        |names: &[&str]| column(element, names).map(|(index, full)| (row[index] / full) as f32);
+    Some(Vertex {
+        position: [value(&["x"])?, value(&["y"])?, value(&["z"])?, 1.0],
+        color: [
+            channel(&["red", "r"]).unwrap_or(1.0),
+            channel(&["green", "g"]).unwrap_or(1.0),
+            channel(&["blue", "b"]).unwrap_or(1.0),
+            channel(&["alpha", "a"]).unwrap_or(1.0),
+        ],
+        normal: [
+            value(&["nx"]).unwrap_or_default(),
+            value(&["ny"]).unwrap_or_default(),
+            value(&["nz"]).unwrap_or_default(),
+        ],
+        uv: [
+            value(&["s", "u", "texture_u"]).unwrap_or_default(),
+            1.0 - value(&["t", "v", "texture_v"]).unwrap_or(1.0),
+        ],
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Field {
+    Float(f32),
+    Byte(u8),
+    Index(u32),
+}
+
+/// A list count or vertex index, which has to be a whole number that fits a `usize`
+fn whole(value: f64) -> Option<usize> {
+    (value >= 0.0 && value.fract() == 0.0 && value <= usize::MAX as f64).then_some(value as usize)
+}
+
+impl Mesh {
+    /// Reads an ASCII or binary PLY, polygons are split into triangle fans
+    ///
+    /// Vertex colors become [`Vertex::color`], a file without faces gives an empty mesh
+    pub fn from_ply(bytes: &[u8]) -> Result<Self, PlyError> {
+        let end = bytes
+            .windows(10)
+            .position(|window| window == b"end_header")
+            .ok_or_else(|| PlyError::Header(String::from("end_header is missing")))?;
+        let body_start = bytes[end..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(bytes.len(), |newline| end + newline + 1);
+        let header = std::str::from_utf8(&bytes[..end])
+            .map_err(|_| PlyError::Header(String::from("the header isn't text")))?;
+        let (format, elements) = parse_header(header)?;
+
+        let mut body = match format {
+            PlyFormat::Ascii => Body::Ascii(
+                std::str::from_utf8(&bytes[body_start..])
+                    .map_err(|_| PlyError::Header(String::from("an ASCII body isn't text")))?
+                    .split_ascii_whitespace(),
+            ),
+            _ => Body::Binary {
+                bytes: &bytes[body_start..],
+                position: 0,
+                big_endian: format == PlyFormat::BinaryBigEndian,
+            },
+        };
+
+        let mut points = vec![];
+        let mut vertices = vec![];
+        for element in &elements {
+            for index in 0..element.count {
+                let error = |message: &str| PlyError::Parse {
+                    element: element.name.clone(),
+                    index,
+                    message: message.to_string(),
+                };
+                let mut row = vec![];
+                let mut faces = vec![];
+                for property in &element.properties {
+                    match property {
+                        Property::Scalar(_, scalar) => {
+                            row.push(body.read(*scalar).ok_or_else(|| error("missing value"))?)
+                        }
+                        Property::List { name, count, item } => {
+                            let count = body.read(*count).ok_or_else(|| error("missing count"))?;
+                            let count = whole(count)
+                                .ok_or_else(|| error(&format!("invalid list count {}", count)))?;
+                            let items = (0..count)
+                                .map(|_| body.read(*item))
+                                .collect::<Option<Vec<_>>>()
+                                .ok_or_else(|| error("missing list item"))?;
+                            if matches!(name.as_str(), "vertex_indices" | "vertex_index") {
+                                faces = items;
+                            }
+                            row.push(f64::NAN);
+                        }
+                    }
+                }
+                match element.name.as_str() {
+                    "vertex" => points
+                        .push(vertex(element, &row).ok_or_else(|| error("x, y or z is missing"))?),
+                    "face" => {
+                        // NaN, negative and fractional indices don't name a vertex either
+                        let corner = |item: f64| {
+                            whole(item)
+                                .and_then(|item| points.get(item))
+                                .copied()
+                                .ok_or_else(|| error(&format!("no vertex {}", item)))
+                        };
+                        for i in 2..faces.len() {
+                            for item in [faces[0], faces[i - 1], faces[i]] {
+                                vertices.push(corner(item)?);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(Self::new(vertices))
+    }
+
+    pub fn load_ply(path: impl AsRef<Path>) -> Result<Self, PlyError> {
+        Self::from_ply(&std::fs::read(path)?)
+    }
+
+    /// The mesh moved by its transform, with shared vertices merged
+    pub fn to_ply(&self, format: PlyFormat) -> Vec<u8> {
+        let normal_matrix = Mat3::from_mat4(self.transform).inverse().transpose();
+        let mut indices = HashMap::new();
+        let mut rows = vec![];
+        let faces = self
+            .vertices
+            .chunks_exact(3)
+            .map(|triangle| {
+                triangle
+                    .iter()
+                    .map(|vertex| {
+                        let position = self.transform * Vec4::from(vertex.position);
+                        let position = position.truncate() / position.w;
+                        let normal =
+                            (normal_matrix * Vec3::from(vertex.normal)).normalize_or_zero();
+                        let color = vertex
+                            .color
+                            .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+                        let [u, v] = vertex.uv;
+                        let mut row = position.to_array().map(Field::Float).to_vec();
+                        row.extend(normal.to_array().map(Field::Float));
+                        row.extend(color.map(Field::Byte));
+                        row.extend([Field::Float(u), Field::Float(1.0 - v)]);
+                        let key = row
+                            .iter()
+                            .map(|field| match field {
+                                Field::Float(value) => value.to_bits(),
+                                Field::Byte(value) => *value as u32,
+                                Field::Index(value) => *value,
+                            })
+                            .collect::<Vec<_>>();
+                        *indices.entry(key).or_insert_with(|| {
+                            rows.push(row);
+                            rows.len() as u32 - 1
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let name = match format {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+            PlyFormat::BinaryBigEndian => "binary_big_endian",
+        };
+        let mut ply = format!(
+            "ply\nformat {} 1.0\nelement vertex {}\n\
+             property float x\nproperty float y\nproperty float z\n\
+             property float nx\nproperty float ny\nproperty float nz\n\
+             property uchar red\nproperty uchar green\nproperty uchar blue\nproperty uchar alpha\n\
+             property float s\nproperty float t\n\
+             element face {}\nproperty list uchar uint vertex_indices\nend_header\n",
+            name,
+            rows.len(),
+            faces.len()
+        )
+        .into_bytes();
+
+        let faces = faces.into_iter().map(|face| {
+            let mut row = vec![Field::Byte(face.len() as u8)];
+            row.extend(face.into_iter().map(Field::Index));
+            row
+        });
+        for row in rows.into_iter().chain(faces) {
+            if format == PlyFormat::Ascii {
+                let line = row
+                    .iter()
+                    .map(|field| match field {
+                        Field::Float(value) => value.to_string(),
+                        Field::Byte(value) => value.to_string(),
+                        Field::Index(value) => value.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                ply.extend_from_slice(line.as_bytes());
+                ply.push(b'\n');
+                continue;
+            }
+            let big_endian = format == PlyFormat::BinaryBigEndian;
+            for field in row {
+                match (field, big_endian) {
+                    (Field::Byte(value), _) => ply.push(value),
+                    (Field::Float(value), false) => ply.extend_from_slice(&value.to_le_bytes()),
+                    (Field::Float(value), true) => ply.extend_from_slice(&value.to_be_bytes()),
+                    (Field::Index(value), false) => ply.extend_from_slice(&value.to_le_bytes()),
+                    (Field::Index(value), true) => ply.extend_from_slice(&value.to_be_bytes()),
+                }
+            }
+        }
+        ply
+    }
+
+    pub fn save_ply(&self, path: impl AsRef<Path>, format: PlyFormat) -> Result<(), PlyError> {
+        std::fs::write(path, self.to_ply(format))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use starry_ecs::World;
+
+use crate::assets::{AssetError, AssetServer, AssetSource, Handle, Shader};
+use crate::commands::WorldExt;
+use crate::renderer::{Image, Images, Mesh, PostEffect, PostProcessing};
+
+impl AssetServer {
+    /// Watches the asset directory, so files that change are reloaded by `reload_changed`
+    pub fn watch(&mut self) -> Result<(), AssetError> {
+        let AssetSource::Directory(root) = self.source() else {
+            return Err(AssetError::NotWatchable);
+        };
+        let root = root.canonicalize().map_err(|source| AssetError::Io {
+            path: root.clone(),
+            source,
+        })?;
+        let storage = self.storage.clone();
+        let watched = root.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            // Editors often save by writing a new file and renaming it over the old one
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_))
+            ) {
+                return;
+            }
+            let mut storage = storage.lock().unwrap();
+            for path in event.paths {
+                if let Ok(path) = path.strip_prefix(&watched) {
+                    storage.changed.insert(path.to_path_buf());
+                }
+            }
+        })?;
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+        self.watcher = Some(watcher);
+        Ok(())
+    }
+
+    /// Queues a file for `reload_changed` like the watcher does, `path` is relative to the asset directory
+    pub fn mark_changed(&self, path: impl AsRef<Path>) {
+        let mut storage = self.storage.lock().unwrap();
+        storage.changed.insert(path.as_ref().to_path_buf());
+    }
+
+    /// Reloads the files the watcher saw changing since the last call
+    pub fn reload_changed(&self) {
+        let changed = std::mem::take(&mut self.storage.lock().unwrap().changed);
+        for path in changed {
+            self.reload(path);
+        }
+    }
+}
+
+/// Reloads changed files and copies new versions of assets into the world.
+///
+/// Images go into `Images`, which uploads them again, vertices into meshes made with
+/// `Mesh::from_asset` and sources into effects made with `PostEffect::custom_shader`.
+pub fn sync_assets_system(world: &World) {
+    let Ok(server) = world.try_get_resource::<AssetServer>() else {
+        return;
+    };
+    server.reload_changed();
+    let mut storage = server.storage.lock().unwrap();
+    let changed = storage
+        .entries
+        .iter()
+        .filter(|(id, entry)| storage.synced.get(id) != Some(&entry.version))
+        .map(|(id, entry)| (*id, entry.version))
+        .collect::<Vec<_>>();
+    storage.synced.extend(changed.iter().copied());
+    let changed = changed
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect::<HashSet<_>>();
+
+    if let Ok(mut images) = world.try_get_resource_mut::<Images>() {
+        for id in &changed {
+            let handle = Handle::<Image> {
+                id: *id,
+                marker: PhantomData,
+            };
+            let Some(image) = storage.get(handle) else {
+                continue;
+            };
+            match storage.images.get(id) {
+                Some(image_id) => {
+                    if let Some(old) = images.get_mut(*image_id) {
+                        *old = Image::clone(&image);
+                    }
+                }
+                None => {
+                    let image_id = images.add(Image::clone(&image));
+                    storage.images.insert(*id, image_id);
+                }
+            }
+        }
+    }
+
+    if let Ok(meshes) = world.try_get_spawned_mut::<Mesh>() {
+        for mut mesh in meshes {
+            let Some(handle) = mesh.asset else {
+                continue;
+            };
+            // Meshes spawned after their asset loaded are still empty
+            if !changed.contains(&handle.id) && !mesh.vertices.is_empty() {
+                continue;
+            }
+            if let Some(asset) = storage.get(handle) {
+                mesh.vertices = asset.vertices.clone();
+            }
+        }
+    }
+
+    if let Ok(mut post_processing) = world.try_get_resource_mut::<PostProcessing>() {
+        for effect in post_processing.effects.iter_mut() {
+            let PostEffect::Custom {
+                label,
+                source,
+                shader: Some(handle),
+                ..
+            } = effect
+            else {
+                continue;
+            };
+            let Some(shader) = storage.get::<Shader>(*handle) else {
+                continue;
+            };
+            let version = storage
+                .entries
+                .get(&handle.id)
+                .map_or(0, |entry| entry.version);
+            if shader.source == *source || storage.rejected.contains(&(handle.id, version)) {
+                continue;
+            }
+            // wgpu panics on shaders that don't compile, so they are checked first
+            match crate::renderer::post::validate_shader(&shader.source) {
+                Ok(()) => *source = shader.source.clone(),
+                Err(error) => {
+                    tracing::warn!("Kept the last version of the {} effect: {}", label, error);
+                    storage.rejected.insert((handle.id, version));
+                }
+            }
+        }
+    }
+}
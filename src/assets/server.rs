@@ -0,0 +1,560 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use notify::RecommendedWatcher;
+use starry_ecs::resources::Resource;
+use thiserror::Error;
+use tokio::runtime::Runtime;
+
+use crate::assets::{sync_assets_system, ObjMaterial, ObjModel, Pak, PakError};
+use crate::plugin::{EngineOrdering, Plugin};
+use crate::renderer::{Image, ImageId, Mesh};
+use crate::StarstruckEngine;
+
+/// `path` with `.` removed and `..` resolved, so every spelling of a file finds the same asset
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+type BoxError = Box<dyn Error + Send + Sync>;
+type Asset = Arc<dyn Any + Send + Sync>;
+type Loader = Arc<dyn Fn(&[u8], &LoadContext) -> Result<Asset, BoxError> + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum AssetError {
+    #[error("couldn't read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("no loader makes a {type_name} from {path}")]
+    NoLoader {
+        path: PathBuf,
+        type_name: &'static str,
+    },
+    #[error("couldn't load {path}: {source}")]
+    Load { path: PathBuf, source: BoxError },
+    #[error("couldn't watch the asset directory: {0}")]
+    Watch(#[from] notify::Error),
+    #[error("only asset directories can be watched")]
+    NotWatchable,
+}
+
+/// A typed reference to an asset of the `AssetServer`, which may still be loading
+pub struct Handle<T> {
+    pub(crate) id: u64,
+    pub(crate) marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(fmt, "Handle<{}>({})", type_name::<T>(), self.id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LoadState {
+    /// The handle belongs to another server
+    NotLoaded,
+    Loading,
+    Loaded,
+    Failed(Arc<AssetError>),
+}
+
+/// WGSL source, loaded from `.wgsl` files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shader {
+    pub source: String,
+}
+
+/// Where the `AssetServer` reads files from
+#[derive(Debug, Clone)]
+pub enum AssetSource {
+    /// Loose files, the only source that can be watched for changes
+    Directory(PathBuf),
+    /// An archive made by `Packer`
+    Pak(Arc<Pak>),
+}
+
+impl AssetSource {
+    /// Reads the file at `path`, relative to the directory or the root of the archive
+    pub fn read(&self, path: impl AsRef<Path>) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Directory(root) => std::fs::read(root.join(path)),
+            Self::Pak(pak) => pak.read(path).map_err(|error| match error {
+                PakError::Io(error) => error,
+                PakError::NotFound(_) => std::io::Error::new(std::io::ErrorKind::NotFound, error),
+                error => std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+            }),
+        }
+    }
+
+    async fn read_async(&self, path: &Path) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Directory(root) => tokio::fs::read(root.join(path)).await,
+            Self::Pak(_) => {
+                let (source, path) = (self.clone(), path.to_path_buf());
+                tokio::task::spawn_blocking(move || source.read(path))
+                    .await
+                    .map_err(std::io::Error::other)?
+            }
+        }
+    }
+}
+
+/// The file being loaded, for loaders that read other files next to it
+#[derive(Debug)]
+pub struct LoadContext {
+    source: AssetSource,
+    path: PathBuf,
+}
+
+impl LoadContext {
+    /// The path of the asset, relative to the root of its source
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads a file relative to the directory of the asset, from the same source
+    pub fn read(&self, path: impl AsRef<Path>) -> std::io::Result<Vec<u8>> {
+        let directory = self.path.parent().unwrap_or(Path::new(""));
+        self.source.read(directory.join(path))
+    }
+}
+
+enum Slot {
+    Loading,
+    Loaded(Asset),
+    Failed(Arc<AssetError>),
+}
+
+pub(crate) struct Entry {
+    pub(crate) path: PathBuf,
+    pub(crate) type_id: TypeId,
+    slot: Slot,
+    /// How many times the asset was loaded
+    pub(crate) version: u32,
+    /// Numbers the loads, so an older one finishing last doesn't win
+    request: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct Storage {
+    pub(crate) entries: HashMap<u64, Entry>,
+    ids: HashMap<(PathBuf, TypeId), u64>,
+    next: u64,
+    pending: usize,
+    /// Files the watcher saw changing since the last update
+    pub(crate) changed: HashSet<PathBuf>,
+    /// The version of every asset last copied into the world
+    pub(crate) synced: HashMap<u64, u32>,
+    pub(crate) images: HashMap<u64, ImageId>,
+    /// Shader versions that didn't compile
+    pub(crate) rejected: HashSet<(u64, u32)>,
+}
+
+impl Storage {
+    pub(crate) fn get<T: Send + Sync + 'static>(&self, handle: Handle<T>) -> Option<Arc<T>> {
+        match &self.entries.get(&handle.id)?.slot {
+            Slot::Loaded(asset) => asset.clone().downcast().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Loads assets on background tasks, each file once per asset type.
+///
+/// Loaders are picked by file extension and asset type, so `model.obj` can be
+/// loaded both as an `ObjModel` and, with a custom loader, as something else.
+///
+/// ```no_run
+/// use starstruck::assets::AssetServer;
+/// use starstruck::renderer::Image;
+///
+/// let server = AssetServer::new("assets");
+/// let grass = server.load::<Image>("textures/grass.png");
+/// // Later, once it's done
+/// if let Some(image) = server.get(grass) {
+///     println!("{}x{}", image.width, image.height);
+/// }
+/// ```
+pub struct AssetServer {
+    source: AssetSource,
+    loaders: HashMap<(String, TypeId), Loader>,
+    pub(crate) storage: Arc<Mutex<Storage>>,
+    pub(crate) watcher: Option<RecommendedWatcher>,
+    runtime: tokio::runtime::Handle,
+    // Only when the server was made outside of a tokio runtime
+    _runtime: Option<Runtime>,
+}
+
+impl Debug for AssetServer {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("AssetServer")
+            .field("source", &self.source)
+            .field("loaders", &self.loaders.len())
+            .field("assets", &self.storage.lock().unwrap().entries.len())
+            .finish()
+    }
+}
+
+impl AssetServer {
+    /// A server for the loose files under `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self::from_source(AssetSource::Directory(root.into()))
+    }
+
+    /// A server with loaders for PNG images, OBJ models, STL and PLY meshes and WGSL shaders.
+    ///
+    /// Tasks run on the current tokio runtime, or on one owned by the server if there is none.
+    pub fn from_source(source: AssetSource) -> Self {
+        let (runtime, owned) = match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => (runtime, None),
+            Err(_) => {
+                let owned = Runtime::new().expect("Couldn't start the asset runtime");
+                (owned.handle().clone(), Some(owned))
+            }
+        };
+        Self {
+            source,
+            loaders: HashMap::new(),
+            storage: Arc::default(),
+            watcher: None,
+            runtime,
+            _runtime: owned,
+        }
+        .with_loader(&["png"], |bytes, _| Image::from_png(bytes))
+        .with_loader(&["obj"], |bytes, context| {
+            let directory = context.path().parent().unwrap_or(Path::new(""));
+            ObjModel::parse_with(&String::from_utf8_lossy(bytes), |file| {
+                match context.read(file) {
+                    Ok(source) => {
+                        ObjMaterial::parse_mtl(&String::from_utf8_lossy(&source), directory)
+                    }
+                    Err(error) => {
+                        tracing::warn!("Couldn't read the material library {}: {}", file, error);
+                        Ok(vec![])
+                    }
+                }
+            })
+        })
+        .with_loader(&["stl"], |bytes, _| Mesh::from_stl(bytes))
+        .with_loader(&["ply"], |bytes, _| Mesh::from_ply(bytes))
+        .with_loader(&["wgsl"], |bytes, _| {
+            String::from_utf8(bytes.to_vec()).map(|source| Shader { source })
+        })
+    }
+
+    pub fn source(&self) -> &AssetSource {
+        &self.source
+    }
+
+    /// Registers a loader for files with any of the `extensions`, replacing the one for the same
+    /// extension and asset type
+    pub fn add_loader<T, E>(
+        &mut self,
+        extensions: &[&str],
+        loader: impl Fn(&[u8], &LoadContext) -> Result<T, E> + Send + Sync + 'static,
+    ) where
+        T: Send + Sync + 'static,
+        E: Error + Send + Sync + 'static,
+    {
+        let loader: Loader = Arc::new(move |bytes, context| match loader(bytes, context) {
+            Ok(asset) => Ok(Arc::new(asset) as Asset),
+            Err(error) => Err(Box::new(error) as BoxError),
+        });
+        for extension in extensions {
+            self.loaders.insert(
+                (extension.to_lowercase(), TypeId::of::<T>()),
+                loader.clone(),
+            );
+        }
+    }
+
+    pub fn with_loader<T, E>(
+        mut self,
+        extensions: &[&str],
+        loader: impl Fn(&[u8], &LoadContext) -> Result<T, E> + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send + Sync + 'static,
+        E: Error + Send + Sync + 'static,
+    {
+        self.add_loader(extensions, loader);
+        self
+    }
+
+    /// Starts loading the file at `path`, relative to the root of the source.
+    ///
+    /// A file that was already requested as a `T` gives back the same handle without loading it
+    /// again.
+    pub fn load<T: Send + Sync + 'static>(&self, path: impl AsRef<Path>) -> Handle<T> {
+        let path = normalize(path.as_ref());
+        let mut storage = self.storage.lock().unwrap();
+        let key = (path.clone(), TypeId::of::<T>());
+        if let Some(id) = storage.ids.get(&key) {
+            return Handle {
+                id: *id,
+                marker: PhantomData,
+            };
+        }
+        let id = storage.next;
+        storage.next += 1;
+        storage.ids.insert(key, id);
+
+        let loader = self.loader(&path, TypeId::of::<T>());
+        let slot = match loader {
+            Some(_) => Slot::Loading,
+            None => Slot::Failed(Arc::new(AssetError::NoLoader {
+                path: path.clone(),
+                type_name: type_name::<T>(),
+            })),
+        };
+        storage.entries.insert(
+            id,
+            Entry {
+                path: path.clone(),
+                type_id: TypeId::of::<T>(),
+                slot,
+                version: 0,
+                request: 0,
+            },
+        );
+        if let Some(loader) = loader {
+            self.spawn(&mut storage, id, path, loader);
+        }
+        Handle {
+            id,
+            marker: PhantomData,
+        }
+    }
+
+    fn loader(&self, path: &Path, type_id: TypeId) -> Option<Loader> {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        self.loaders.get(&(extension, type_id)).cloned()
+    }
+
+    /// Loads the file again for every asset type it was requested as.
+    ///
+    /// Assets keep their last version until the new one is ready, and keep it if the file
+    /// doesn't load anymore.
+    pub fn reload(&self, path: impl AsRef<Path>) {
+        let path = normalize(path.as_ref());
+        let mut storage = self.storage.lock().unwrap();
+        let assets = storage
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.path == path)
+            .filter_map(|(id, entry)| Some((*id, self.loader(&entry.path, entry.type_id)?)))
+            .collect::<Vec<_>>();
+        for (id, loader) in assets {
+            tracing::debug!("Reloading {}", path.display());
+            self.spawn(&mut storage, id, path.clone(), loader);
+        }
+    }
+
+    fn spawn(&self, storage: &mut Storage, id: u64, path: PathBuf, loader: Loader) {
+        let Some(entry) = storage.entries.get_mut(&id) else {
+            return;
+        };
+        entry.request += 1;
+        let request = entry.request;
+        storage.pending += 1;
+        let storage = self.storage.clone();
+        let context = LoadContext {
+            source: self.source.clone(),
+            path,
+        };
+        self.runtime.spawn(async move {
+            let result = match context.source.read_async(&context.path).await {
+                Ok(bytes) => {
+                    let path = context.path.clone();
+                    // Decoding is CPU bound, so it doesn't run on the async workers
+                    let loaded =
+                        tokio::task::spawn_blocking(move || loader(&bytes, &context)).await;
+                    match loaded {
+                        Ok(Ok(asset)) => Ok(asset),
+                        Ok(Err(source)) => Err(AssetError::Load { path, source }),
+                        Err(error) => Err(AssetError::Load {
+                            path,
+                            source: Box::new(error),
+                        }),
+                    }
+                }
+                Err(source) => Err(AssetError::Io {
+                    path: context.path,
+                    source,
+                }),
+            };
+            let mut storage = storage.lock().unwrap();
+            storage.pending -= 1;
+            let Some(entry) = storage.entries.get_mut(&id) else {
+                return;
+            };
+            if request < entry.request {
+                return;
+            }
+            match result {
+                Ok(asset) => {
+                    entry.slot = Slot::Loaded(asset);
+                    entry.version += 1;
+                }
+                Err(error) if matches!(entry.slot, Slot::Loaded(_)) => {
+                    tracing::warn!("Kept the last version: {}", error);
+                }
+                Err(error) => {
+                    tracing::warn!("{}", error);
+                    entry.slot = Slot::Failed(Arc::new(error));
+                }
+            }
+        });
+    }
+
+    pub fn state<T>(&self, handle: Handle<T>) -> LoadState {
+        match self.storage.lock().unwrap().entries.get(&handle.id) {
+            None => LoadState::NotLoaded,
+            Some(Entry {
+                slot: Slot::Loading,
+                ..
+            }) => LoadState::Loading,
+            Some(Entry {
+                slot: Slot::Loaded(_),
+                ..
+            }) => LoadState::Loaded,
+            Some(Entry {
+                slot: Slot::Failed(error),
+                ..
+            }) => LoadState::Failed(error.clone()),
+        }
+    }
+
+    /// The asset once it is loaded
+    pub fn get<T: Send + Sync + 'static>(&self, handle: Handle<T>) -> Option<Arc<T>> {
+        self.storage.lock().unwrap().get(handle)
+    }
+
+    /// The image in `Images` once the `AssetPlugin` copied it there
+    pub fn image(&self, handle: Handle<Image>) -> Option<ImageId> {
+        self.storage.lock().unwrap().images.get(&handle.id).copied()
+    }
+
+    /// How many times the asset was loaded, which goes up with every reload
+    pub fn version<T>(&self, handle: Handle<T>) -> u32 {
+        let storage = self.storage.lock().unwrap();
+        storage
+            .entries
+            .get(&handle.id)
+            .map_or(0, |entry| entry.version)
+    }
+
+    pub fn path<T>(&self, handle: Handle<T>) -> Option<PathBuf> {
+        let storage = self.storage.lock().unwrap();
+        storage
+            .entries
+            .get(&handle.id)
+            .map(|entry| entry.path.clone())
+    }
+
+    /// Whether every load and reload finished, successfully or not
+    pub fn is_idle(&self) -> bool {
+        self.storage.lock().unwrap().pending == 0
+    }
+}
+
+impl Resource for AssetServer {}
+
+/// Adds an `AssetServer` and copies loaded assets into the world at the start of every frame.
+///
+/// Release builds read `assets.pak` by default and debug builds the loose files under `assets`,
+/// which they also fall back to when the archive can't be opened.
+#[derive(Debug, Clone)]
+pub struct AssetPlugin {
+    pub root: PathBuf,
+    pub pak: Option<PathBuf>,
+    /// Reloads assets when their files change, on by default in debug builds
+    pub hot_reload: bool,
+}
+
+impl Default for AssetPlugin {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::from("assets"),
+            pak: (!cfg!(debug_assertions)).then(|| PathBuf::from("assets.pak")),
+            hot_reload: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl Plugin for AssetPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        let pak = self.pak.as_ref().and_then(|path| match Pak::open(path) {
+            Ok(pak) => Some(pak),
+            Err(error) => {
+                tracing::warn!(
+                    "Loading loose files, {} didn't open: {}",
+                    path.display(),
+                    error
+                );
+                None
+            }
+        });
+        let mut server = match pak {
+            Some(pak) => AssetServer::from_source(AssetSource::Pak(Arc::new(pak))),
+            None => AssetServer::new(self.root.clone()),
+        };
+        if self.hot_reload && matches!(server.source(), AssetSource::Directory(_)) {
+            if let Err(error) = server.watch() {
+                tracing::warn!("Hot reloading is off: {}", error);
+            }
+        }
+        engine
+            .add_resource(server)
+            .add_system(EngineOrdering::First, sync_assets_system)
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use glam::{Vec3, Vec4};
+use thiserror::Error;
+
+use crate::renderer::Mesh;
+use crate::shapes::Vertex;
+
+#[derive(Error, Debug)]
+pub enum StlError {
+    #[error("couldn't read the STL file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("a binary STL of {triangles} triangles needs {expected} bytes, got {actual}")]
+    Size {
+        triangles: u32,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StlFormat {
+    Ascii,
+    #[default]
+    Binary,
+}
+
+const HEADER_SIZE: usize = 80;
+const TRIANGLE_SIZE: usize = 50;
+
+fn vertex(position: Vec3, normal: Vec3) -> Vertex {
+    Vertex {
+        position: position.extend(1.0).to_array(),
+        color: [1.0; 4],
+        normal: normal.to_array(),
+        uv: [0.0; 2],
+    }
+}
+
+/// The triangles of a mesh in world space, each with its face normal
+fn world_triangles(mesh: &Mesh) -> Vec<(Vec3, [Vec3; 3])> {
+    mesh.vertices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|i| {
+                let position = mesh.transform * Vec4::from(triangle[i].position);
+                position.truncate() / position.w
+            });
+            ((b - a).cross(c - a).normalize_or_zero(), [a, b, c])
+        })
+        .collect()
+}
+
+impl Mesh {
+    /// Reads an ASCII or binary STL, facets without a normal are shaded flat
+    pub fn from_stl(bytes: &[u8]) -> Result<Self, StlError> {
+        // Some binary files start with `solid` too, so the size decides
+        if bytes.len() >= HEADER_SIZE + 4 {
+            let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]);
+            if bytes.len() == HEADER_SIZE + 4 + count as usize * TRIANGLE_SIZE {
+                return Ok(Self::from_binary_stl(bytes));
+            }
+        }
+        match std::str::from_utf8(bytes) {
+            Ok(source) if source.trim_start().starts_with("solid") => Self::from_ascii_stl(source),
+            _ => {
+                let triangles = bytes.get(HEADER_SIZE..HEADER_SIZE + 4).map_or(0, |count| {
+                    u32::from_le_bytes([count[0], count[1], count[2], count[3]])
+                });
+                Err(StlError::Size {
+                    triangles,
+                    expected: HEADER_SIZE + 4 + triangles as usize * TRIANGLE_SIZE,
+                    actual: bytes.len(),
+                })
+            }
+        }
+    }
+
+    fn from_binary_stl(bytes: &[u8]) -> Self {
+        let float = |offset: usize| {
+            f32::from_le_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+        let vector = |offset: usize| Vec3::new(float(offset), float(offset + 4), float(offset + 8));
+        let count = (bytes.len() - HEADER_SIZE - 4) / TRIANGLE_SIZE;
+        let vertices = (0..count)
+            .flat_map(|index| {
+                // The normal, three corners and two bytes of attributes
+                let offset = HEADER_SIZE + 4 + index * TRIANGLE_SIZE;
+                let normal = vector(offset);
+                [1, 2, 3].map(|corner| vertex(vector(offset + corner * 12), normal))
+            })
+            .collect();
+        Self::new(vertices)
+    }
+
+    fn from_ascii_stl(source: &str) -> Result<Self, StlError> {
+        let mut vertices = vec![];
+        let mut normal = Vec3::ZERO;
+        // The vertices of the open facet, if there is one
+        let mut corners = None;
+        let unfinished = |line: usize, corners: usize| StlError::Parse {
+            line,
+            message: format!("a facet has {} vertices instead of 3", corners),
+        };
+        for (index, line) in source.lines().enumerate() {
+            let number = index + 1;
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or_default();
+            if keyword == "endfacet" {
+                match corners.take() {
+                    Some(3) => {}
+                    count => return Err(unfinished(number, count.unwrap_or_default())),
+                }
+            }
+            if !matches!(keyword, "facet" | "vertex") {
+                continue;
+            }
+            // `facet normal` has one more word before the numbers
+            let values = words
+                .skip_while(|word| *word == "normal")
+                .map(|word| {
+                    word.parse::<f32>().map_err(|_| StlError::Parse {
+                        line: number,
+                        message: format!("invalid number {:?}", word),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let [x, y, z] = values[..] else {
+                return Err(StlError::Parse {
+                    line: number,
+                    message: format!("{} needs 3 numbers", keyword),
+                });
+            };
+            if keyword == "facet" {
+                // A facet without `endfacet` still has to be complete
+                if let Some(count) = corners.filter(|count| *count != 3) {
+                    return Err(unfinished(number, count));
+                }
+                normal = Vec3::new(x, y, z);
+                corners = Some(0);
+            } else {
+                let count = corners.get_or_insert(0);
+                *count += 1;
+                if *count > 3 {
+                    return Err(StlError::Parse {
+                        line: number,
+                        message: String::from("a facet has more than 3 vertices"),
+                    });
+                }
+                vertices.push(vertex(Vec3::new(x, y, z), normal));
+            }
+        }
+        if let Some(count) = corners.filter(|count| *count != 3) {
+            return Err(unfinished(source.lines().count(), count));
+        }
+        Ok(Self::new(vertices))
+    }
+
+    pub fn load_stl(path: impl AsRef<Path>) -> Result<Self, StlError> {
+        Self::from_stl(&std::fs::read(path)?)
+    }
+
+    /// The mesh moved by its transform, STL has no colors or texture coordinates
+    pub fn to_stl(&self, format: StlFormat) -> Vec<u8> {
+        let triangles = world_triangles(self);
+        match format {
+            StlFormat::Ascii => {
+                let mut stl = String::from("solid mesh\n");
+                for (normal, corners) in triangles {
+                    let _ = writeln!(stl, "facet normal {} {} {}", normal.x, normal.y, normal.z);
+                    stl.push_str("  outer loop\n");
+                    for corner in corners {
+                        let _ = writeln!(stl, "    vertex {} {} {}", corner.x, corner.y, corner.z);
+                    }
+                    stl.push_str("  endloop\nendfacet\n");
+                }
+                stl.push_str("endsolid mesh\n");
+                stl.into_bytes()
+            }
+            StlFormat::Binary => {
+                let mut stl = vec![0; HEADER_SIZE];
+                stl.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+                for (normal, corners) in triangles {
+                    for vector in std::iter::once(normal).chain(corners) {
+                        for value in vector.to_array() {
+                            stl.extend_from_slice(&value.to_le_bytes());
+                        }
+                    }
+                    stl.extend_from_slice(&[0, 0]);
+                }
+                stl
+            }
+        }
+    }
+
+    pub fn save_stl(&self, path: impl AsRef<Path>, format: StlFormat) -> Result<(), StlError> {
+        std::fs::write(path, self.to_stl(format))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+//! Bundles an asset directory into an archive the `AssetPlugin` can load.
+//!
+//! `starstruck-pack <asset directory> <archive> [--store]`, where `--store` turns off compression.
+
+use starstruck::assets::{Compression, Packer, Pak};
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let store = args.iter().any(|arg| arg == "--store");
+    let paths = args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .collect::<Vec<_>>();
+    let [directory, archive] = paths[..] else {
+        eprintln!("Usage: starstruck-pack <asset directory> <archive> [--store]");
+        std::process::exit(2);
+    };
+
+    let compression = if store {
+        Compression::None
+    } else {
+        Compression::Deflate
+    };
+    let packed = Packer::new()
+        .with_compression(compression)
+        .with_directory(directory)
+        .and_then(|packer| packer.save(archive))
+        .and_then(|_| Pak::open(archive));
+    let pak = match packed {
+        Ok(pak) => pak,
+        Err(error) => {
+            eprintln!("Couldn't pack {}: {}", directory, error);
+            std::process::exit(1);
+        }
+    };
+    let (size, stored) = pak
+        .files()
+        .filter_map(|file| pak.size(file))
+        .fold((0, 0), |(size, stored), file| {
+            (size + file.0, stored + file.1)
+        });
+    println!(
+        "Packed {} files from {} into {}, {} bytes stored as {}",
+        pak.files().count(),
+        directory,
+        archive,
+        size,
+        stored
+    );
+}
@@ -0,0 +1,188 @@
+use std::any::{type_name, TypeId};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Debug;
+
+use starry_ecs::component::Component;
+use starry_ecs::resources::Resource;
+use starry_ecs::{ComponentReadGuard, ComponentWriteGuard, StarryError, World};
+
+type Command = Box<dyn FnOnce(World) -> World>;
+
+/// Changes to the world that systems can't make while it runs.
+///
+/// The engine applies them after every update, in the order they were queued.
+#[derive(Default)]
+pub struct Commands {
+    queue: Vec<Command>,
+}
+
+impl Debug for Commands {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("Commands")
+            .field("queued", &self.queue.len())
+            .finish()
+    }
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, command: impl FnOnce(World) -> World + 'static) {
+        self.queue.push(Box::new(command));
+    }
+
+    /// Adds the component, in the place of a despawned component of the same type if there is one
+    pub fn spawn<T: Component + 'static>(&mut self, component: T) {
+        self.add(move |world| {
+            let reused = world
+                .try_get_resource_mut::<Despawned>()
+                .ok()
+                .and_then(|mut despawned| despawned.take::<T>());
+            let Some(index) = reused else {
+                return world.add_component(component);
+            };
+            if let Some(mut slot) = world
+                .try_get_components_mut::<T>()
+                .ok()
+                .and_then(|components| components.into_iter().nth(index))
+            {
+                *slot = component;
+            }
+            world
+        });
+    }
+
+    /// Despawns the component of type `T` at `index` in `World::try_get_components`.
+    ///
+    /// The component stays in the world until `spawn` reuses its place, so
+    /// `World::get_components` still returns it, only `WorldExt` queries leave it out.
+    pub fn despawn<T: Component + 'static>(&mut self, index: usize) {
+        self.add(move |world| {
+            let world = if world.try_get_resource::<Despawned>().is_ok() {
+                world
+            } else {
+                world.add_resource(Despawned::default())
+            };
+            world.get_resource_mut::<Despawned>().insert::<T>(index);
+            world
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Applies the queued commands to the world, leaving the queue empty
+    pub fn apply(world: World) -> World {
+        let queue = world
+            .try_get_resource_mut::<Commands>()
+            .map(|mut commands| std::mem::take(&mut commands.queue))
+            .unwrap_or_default();
+        queue
+            .into_iter()
+            .fold(world, |world, command| command(world))
+    }
+}
+
+impl Resource for Commands {}
+
+/// Components despawned with `Commands::despawn`, by type and index.
+///
+/// starry-ecs can't remove components, so they stay in the world and `WorldExt` leaves them out.
+/// Their memory is only freed when `Commands::spawn` puts a new component in their place.
+#[derive(Debug, Default)]
+pub struct Despawned {
+    components: HashMap<TypeId, BTreeSet<usize>>,
+}
+
+impl Despawned {
+    pub fn contains<T: Component + 'static>(&self, index: usize) -> bool {
+        self.components
+            .get(&TypeId::of::<T>())
+            .is_some_and(|indices| indices.contains(&index))
+    }
+
+    /// Returns false if the component was already despawned
+    pub fn insert<T: Component + 'static>(&mut self, index: usize) -> bool {
+        self.components
+            .entry(TypeId::of::<T>())
+            .or_default()
+            .insert(index)
+    }
+
+    pub fn count<T: Component + 'static>(&self) -> usize {
+        self.components
+            .get(&TypeId::of::<T>())
+            .map_or(0, BTreeSet::len)
+    }
+
+    /// Removes the lowest despawned index of type `T`, for a new component to take its place
+    pub fn take<T: Component + 'static>(&mut self) -> Option<usize> {
+        self.components.get_mut(&TypeId::of::<T>())?.pop_first()
+    }
+}
+
+impl Resource for Despawned {}
+
+fn spawned<G>(
+    world: &World,
+    components: Vec<G>,
+    despawned: impl Fn(&Despawned, usize) -> bool,
+) -> Vec<G> {
+    match world.try_get_resource::<Despawned>() {
+        Ok(removed) => components
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !despawned(&removed, *index))
+            .map(|(_, component)| component)
+            .collect(),
+        Err(_) => components,
+    }
+}
+
+/// Queries leaving out the components despawned with `Commands::despawn`
+pub trait WorldExt {
+    fn try_get_spawned<T: Component + 'static>(
+        &self,
+    ) -> Result<Vec<ComponentReadGuard<'_, T>>, StarryError>;
+
+    fn try_get_spawned_mut<T: Component + 'static>(
+        &self,
+    ) -> Result<Vec<ComponentWriteGuard<'_, T>>, StarryError>;
+}
+
+impl WorldExt for World {
+    fn try_get_spawned<T: Component + 'static>(
+        &self,
+    ) -> Result<Vec<ComponentReadGuard<'_, T>>, StarryError> {
+        let components = spawned(
+            self,
+            self.try_get_components::<T>()?,
+            Despawned::contains::<T>,
+        );
+        if components.is_empty() {
+            return Err(StarryError::ComponentNotFound(type_name::<T>()));
+        }
+        Ok(components)
+    }
+
+    fn try_get_spawned_mut<T: Component + 'static>(
+        &self,
+    ) -> Result<Vec<ComponentWriteGuard<'_, T>>, StarryError> {
+        let components = spawned(
+            self,
+            self.try_get_components_mut::<T>()?,
+            Despawned::contains::<T>,
+        );
+        if components.is_empty() {
+            return Err(StarryError::ComponentNotFound(type_name::<T>()));
+        }
+        Ok(components)
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use starry_ecs::resources::Resource;
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::World;
+
+use crate::plugin::Plugin;
+use crate::renderer::{Gui, GuiPlugin};
+use crate::StarstruckEngine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Track {
+    Cpu,
+    Gpu,
+}
+
+/// A named stretch of time, relative to when the profiler was created
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub name: String,
+    pub track: Track,
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameReport {
+    pub index: u64,
+    pub start: Duration,
+    pub duration: Duration,
+    pub spans: Vec<Span>,
+}
+
+impl FrameReport {
+    /// Total time of the spans with that name
+    pub fn time(&self, name: &str) -> Duration {
+        self.spans
+            .iter()
+            .filter(|span| span.name == name)
+            .map(|span| span.duration)
+            .sum()
+    }
+}
+
+/// Timings of the last frames.
+///
+/// The engine records how long its systems, uploads, encoding and presenting take,
+/// and the renderer adds the GPU time of every pass when the adapter supports
+/// timestamp queries. GPU timings arrive a few frames late.
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    pub enabled: bool,
+    pub show_panel: bool,
+    /// Where the panel's button saves the Chrome trace
+    pub trace_path: PathBuf,
+    capacity: usize,
+    origin: Instant,
+    frame_count: u64,
+    current: Option<FrameReport>,
+    history: VecDeque<FrameReport>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            show_panel: true,
+            trace_path: PathBuf::from("trace.json"),
+            capacity: 300,
+            origin: Instant::now(),
+            frame_count: 0,
+            current: None,
+            history: VecDeque::new(),
+        }
+    }
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How many finished frames are kept
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn with_panel(mut self, show_panel: bool) -> Self {
+        self.show_panel = show_panel;
+        self
+    }
+
+    pub fn with_trace_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.trace_path = path.into();
+        self
+    }
+
+    /// Finishes the current frame and starts the next one
+    pub fn begin_frame(&mut self, now: Instant) {
+        if !self.enabled {
+            return;
+        }
+        let start = now.saturating_duration_since(self.origin);
+        if let Some(mut frame) = self.current.take() {
+            frame.duration = start.saturating_sub(frame.start);
+            self.history.push_back(frame);
+            while self.history.len() > self.capacity {
+                self.history.pop_front();
+            }
+        }
+        self.current = Some(FrameReport {
+            index: self.frame_count,
+            start,
+            duration: Duration::ZERO,
+            spans: vec![],
+        });
+        self.frame_count += 1;
+    }
+
+    /// Records a span of the current frame, ignored before the first frame
+    pub fn record(&mut self, name: &str, start: Instant, end: Instant) {
+        self.push(
+            name,
+            Track::Cpu,
+            start,
+            end.saturating_duration_since(start),
+        );
+    }
+
+    /// Records the time a render pass took on the GPU, starting at `start` on the CPU's clock
+    pub fn record_gpu(&mut self, name: &str, start: Instant, duration: Duration) {
+        self.push(name, Track::Gpu, start, duration);
+    }
+
+    fn push(&mut self, name: &str, track: Track, start: Instant, duration: Duration) {
+        let origin = self.origin;
+        if let (true, Some(frame)) = (self.enabled, self.current.as_mut()) {
+            frame.spans.push(Span {
+                name: String::from(name),
+                track,
+                start: start.saturating_duration_since(origin),
+                duration,
+            });
+        }
+    }
+
+    /// Finished frames, oldest first
+    pub fn frames(&self) -> impl Iterator<Item = &FrameReport> {
+        self.history.iter()
+    }
+
+    pub fn last_frame(&self) -> Option<&FrameReport> {
+        self.history.back()
+    }
+
+    pub fn average_frame_time(&self) -> Duration {
+        if self.history.is_empty() {
+            return Duration::ZERO;
+        }
+        self.history
+            .iter()
+            .map(|frame| frame.duration)
+            .sum::<Duration>()
+            / self.history.len() as u32
+    }
+
+    pub fn fps(&self) -> f32 {
+        let average = self.average_frame_time().as_secs_f32();
+        if average > 0.0 {
+            1.0 / average
+        } else {
+            0.0
+        }
+    }
+
+    /// The kept frames in the Chrome trace event format, for chrome://tracing or Perfetto
+    pub fn chrome_trace(&self) -> String {
+        let micros = |duration: Duration| duration.as_secs_f64() * 1_000_000.0;
+        let mut events = vec![];
+        for frame in self.history.iter() {
+            events.push(json!({
+                "name": format!("frame {}", frame.index),
+                "cat": "frame",
+                "ph": "X",
+                "ts": micros(frame.start),
+                "dur": micros(frame.duration),
+                "pid": 0,
+                "tid": 0,
+            }));
+            for span in frame.spans.iter() {
+                let (category, thread) = match span.track {
+                    Track::Cpu => ("cpu", 0),
+                    Track::Gpu => ("gpu", 1),
+                };
+                events.push(json!({
+                    "name": span.name,
+                    "cat": category,
+                    "ph": "X",
+                    "ts": micros(span.start),
+                    "dur": micros(span.duration),
+                    "pid": 0,
+                    "tid": thread,
+                }));
+            }
+        }
+        let names = [(0, "CPU"), (1, "GPU")].map(|(thread, name)| {
+            json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": 0,
+                "tid": thread,
+                "args": { "name": name },
+            })
+        });
+        events.extend(names);
+        json!({ "traceEvents": events }).to_string()
+    }
+
+    pub fn write_chrome_trace(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.chrome_trace())
+    }
+}
+
+impl Resource for Profiler {}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// Shows the FPS and the timings of the last frame through the `Gui` resource
+pub fn profiler_panel_system(world: &World) {
+    let (Ok(gui), Ok(profiler)) = (
+        world.try_get_resource::<Gui>(),
+        world.try_get_resource::<Profiler>(),
+    ) else {
+        return;
+    };
+    if !profiler.show_panel {
+        return;
+    }
+
+    egui::Window::new("Profiler")
+        .default_width(260.0)
+        .show(gui.context(), |ui| {
+            ui.label(format!(
+                "{:.0} FPS, {}",
+                profiler.fps(),
+                millis(profiler.average_frame_time())
+            ));
+            let Some(frame) = profiler.last_frame() else {
+                return;
+            };
+            for (heading, track) in [("CPU", Track::Cpu), ("GPU", Track::Gpu)] {
+                let spans = frame
+                    .spans
+                    .iter()
+                    .filter(|span| span.track == track)
+                    .collect::<Vec<_>>();
+                if spans.is_empty() {
+                    continue;
+                }
+                ui.separator();
+                ui.strong(heading);
+                egui::Grid::new(heading).show(ui, |ui| {
+                    for span in spans {
+                        ui.label(&span.name);
+                        ui.label(millis(span.duration));
+                        ui.end_row();
+                    }
+                });
+            }
+            ui.separator();
+            if ui.button("Save Chrome trace").clicked() {
+                match profiler.write_chrome_trace(&profiler.trace_path) {
+                    Ok(_) => tracing::info!("Saved the trace to {}", profiler.trace_path.display()),
+                    Err(error) => tracing::error!("Couldn't save the trace: {}", error),
+                }
+            }
+        });
+}
+
+/// Adds a `Profiler` and its panel
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        let engine = if engine.has_plugin::<GuiPlugin>() {
+            engine
+        } else {
+            engine.add_plugin(GuiPlugin)
+        };
+        engine
+            .add_resource(Profiler::new())
+            .add_system(DefaultOrdering::PostRun, profiler_panel_system)
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt::Debug;
+
+use starry_ecs::resources::Resource;
+use starry_ecs::World;
+
+/// A queue of events of type `E` stored as a resource.
+///
+/// Events sent during one update become readable during the next one and are
+/// dropped after that, so every system running each frame sees every event once.
+#[derive(Debug)]
+pub struct Events<E> {
+    pending: Vec<E>,
+    current: Vec<E>,
+}
+
+impl<E> Default for Events<E> {
+    fn default() -> Self {
+        Self {
+            pending: vec![],
+            current: vec![],
+        }
+    }
+}
+
+impl<E: Debug + 'static> Events<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn send(&mut self, event: E) {
+        self.pending.push(event);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, E> {
+        self.current.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.current.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.is_empty()
+    }
+
+    /// Makes the events sent since the last update readable and drops the old ones
+    pub fn update(&mut self) {
+        self.current = std::mem::take(&mut self.pending);
+    }
+
+    pub fn update_system(world: &World) {
+        world.get_resource_mut::<Self>().update();
+    }
+}
+
+impl<E: Debug> Resource for Events<E> {}
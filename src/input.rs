@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+
+use starry_ecs::resources::Resource;
+use starry_ecs::World;
+use winit::event::{
+    ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
+};
+
+use crate::plugin::{EngineOrdering, Plugin};
+use crate::StarstruckEngine;
+
+/// Keyboard and mouse state collected from the window events
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    pressed_keys: HashSet<VirtualKeyCode>,
+    just_pressed_keys: HashSet<VirtualKeyCode>,
+    just_released_keys: HashSet<VirtualKeyCode>,
+    pressed_buttons: HashSet<MouseButton>,
+    just_pressed_buttons: HashSet<MouseButton>,
+    just_released_buttons: HashSet<MouseButton>,
+    cursor_position: Option<(f64, f64)>,
+    scroll_delta: (f32, f32),
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.pressed_keys.contains(&key)
+    }
+
+    pub fn key_just_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.just_pressed_keys.contains(&key)
+    }
+
+    pub fn key_just_released(&self, key: VirtualKeyCode) -> bool {
+        self.just_released_keys.contains(&key)
+    }
+
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_buttons.contains(&button)
+    }
+
+    pub fn mouse_just_pressed(&self, button: MouseButton) -> bool {
+        self.just_pressed_buttons.contains(&button)
+    }
+
+    pub fn mouse_just_released(&self, button: MouseButton) -> bool {
+        self.just_released_buttons.contains(&button)
+    }
+
+    /// Cursor position in physical pixels, `None` when the cursor left the window
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor_position
+    }
+
+    /// Scroll accumulated since the last frame, in lines
+    pub fn scroll_delta(&self) -> (f32, f32) {
+        self.scroll_delta
+    }
+
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    if self.pressed_keys.insert(*key) {
+                        self.just_pressed_keys.insert(*key);
+                    }
+                }
+                ElementState::Released => {
+                    self.pressed_keys.remove(key);
+                    self.just_released_keys.insert(*key);
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    if self.pressed_buttons.insert(*button) {
+                        self.just_pressed_buttons.insert(*button);
+                    }
+                }
+                ElementState::Released => {
+                    self.pressed_buttons.remove(button);
+                    self.just_released_buttons.insert(*button);
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Some((position.x, position.y));
+            }
+            WindowEvent::CursorLeft { .. } => self.cursor_position = None,
+            WindowEvent::MouseWheel { delta, .. } => {
+                let (x, y) = match delta {
+                    MouseScrollDelta::LineDelta(x, y) => (*x, *y),
+                    // Roughly one line per 20 pixels
+                    MouseScrollDelta::PixelDelta(position) => {
+                        (position.x as f32 / 20.0, position.y as f32 / 20.0)
+                    }
+                };
+                self.scroll_delta.0 += x;
+                self.scroll_delta.1 += y;
+            }
+            WindowEvent::Focused(false) => {
+                // Keys released while unfocused never reach us
+                self.just_released_keys.extend(self.pressed_keys.drain());
+                self.just_released_buttons
+                    .extend(self.pressed_buttons.drain());
+            }
+            _ => {}
+        }
+    }
+
+    /// Forgets everything that only lasts for a single frame
+    pub fn clear(&mut self) {
+        self.just_pressed_keys.clear();
+        self.just_released_keys.clear();
+        self.just_pressed_buttons.clear();
+        self.just_released_buttons.clear();
+        self.scroll_delta = (0.0, 0.0);
+    }
+}
+
+impl Resource for Input {}
+
+pub fn input_event_handler(world: &World, event: &WindowEvent) {
+    world.get_resource_mut::<Input>().handle_event(event);
+}
+
+pub fn clear_input_system(world: &World) {
+    world.get_resource_mut::<Input>().clear();
+}
+
+/// Adds the `Input` resource and feeds it the window's keyboard and mouse events
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        engine
+            .add_resource(Input::new())
+            .add_window_event_handler(input_event_handler)
+            .add_system(EngineOrdering::Last, clear_input_system)
+    }
+}
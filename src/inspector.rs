@@ -0,0 +1,562 @@
+use std::any::type_name;
+
+use glam::{Vec2, Vec3};
+use starry_ecs::component::Component;
+use starry_ecs::resources::Resource;
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::World;
+use wgpu::Color;
+use winit::event::VirtualKeyCode;
+
+use crate::commands::{Commands, Despawned, WorldExt};
+use crate::input::Input;
+use crate::plugin::Plugin;
+use crate::renderer::{
+    AmbientLight, Camera, DebugRenderSettings, DirectionalLight, Gui, GuiPlugin, RendererSettings,
+    SpotLight, Sprite, SpriteSettings,
+};
+use crate::time::Time;
+use crate::ui::UiScale;
+use crate::StarstruckEngine;
+
+/// A field of a reflected value, borrowed so it can be edited in place
+pub enum Field<'a> {
+    Bool(&'a mut bool),
+    F32(&'a mut f32),
+    I32(&'a mut i32),
+    U32(&'a mut u32),
+    String(&'a mut String),
+    Vec2(&'a mut Vec2),
+    Vec3(&'a mut Vec3),
+    Color(&'a mut Color),
+    Struct(&'a mut dyn Reflect),
+    /// Shown but not editable
+    ReadOnly(String),
+}
+
+/// Exposes the fields of a value to the inspector.
+///
+/// ```
+/// use starstruck::inspector::{Field, Reflect};
+///
+/// struct Player {
+///     speed: f32,
+///     invincible: bool,
+/// }
+///
+/// impl Reflect for Player {
+///     fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+///         vec![
+///             ("speed", Field::F32(&mut self.speed)),
+///             ("invincible", Field::Bool(&mut self.invincible)),
+///         ]
+///     }
+/// }
+/// ```
+pub trait Reflect {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)>;
+}
+
+/// The type's name without its module path
+fn short_type_name<T>() -> &'static str {
+    let name = type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn color_ui(ui: &mut egui::Ui, color: &mut Color) -> bool {
+    let mut rgba = [
+        color.r as f32,
+        color.g as f32,
+        color.b as f32,
+        color.a as f32,
+    ];
+    let changed = ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed();
+    if changed {
+        let [r, g, b, a] = rgba.map(f64::from);
+        *color = Color { r, g, b, a };
+    }
+    changed
+}
+
+fn drag_values(ui: &mut egui::Ui, values: &mut [f32]) -> bool {
+    let mut changed = false;
+    for value in values.iter_mut() {
+        changed |= ui.add(egui::DragValue::new(value).speed(0.1)).changed();
+    }
+    changed
+}
+
+/// Draws editors for the fields of a value, returns true if one of them changed
+pub fn reflect_ui(ui: &mut egui::Ui, value: &mut dyn Reflect) -> bool {
+    let mut changed = false;
+    for (name, field) in value.fields() {
+        if let Field::Struct(value) = field {
+            ui.collapsing(name, |ui| changed |= reflect_ui(ui, value));
+            continue;
+        }
+        ui.horizontal(|ui| {
+            ui.label(name);
+            changed |= match field {
+                Field::Bool(value) => ui.checkbox(value, "").changed(),
+                Field::F32(value) => ui.add(egui::DragValue::new(value).speed(0.1)).changed(),
+                Field::I32(value) => ui.add(egui::DragValue::new(value)).changed(),
+                Field::U32(value) => ui.add(egui::DragValue::new(value)).changed(),
+                Field::String(value) => ui.text_edit_singleline(value).changed(),
+                Field::Vec2(value) => {
+                    let mut values = value.to_array();
+                    let changed = drag_values(ui, &mut values);
+                    *value = Vec2::from_array(values);
+                    changed
+                }
+                Field::Vec3(value) => {
+                    let mut values = value.to_array();
+                    let changed = drag_values(ui, &mut values);
+                    *value = Vec3::from_array(values);
+                    changed
+                }
+                Field::Color(value) => color_ui(ui, value),
+                Field::ReadOnly(value) => {
+                    ui.label(value);
+                    false
+                }
+                Field::Struct(_) => unreachable!(),
+            };
+        });
+    }
+    changed
+}
+
+fn count_components<T: Component + 'static>(world: &World) -> usize {
+    world
+        .try_get_spawned::<T>()
+        .map_or(0, |components| components.len())
+}
+
+/// Pushes the index of every component whose despawn button was clicked
+fn components_ui<T: Component + Reflect + 'static>(
+    world: &World,
+    ui: &mut egui::Ui,
+    despawns: &mut Vec<usize>,
+) {
+    let Ok(mut components) = world.try_get_components_mut::<T>() else {
+        return;
+    };
+    let despawned = world.try_get_resource::<Despawned>().ok();
+    for (index, component) in components.iter_mut().enumerate() {
+        // Indices stay the ones `Commands::despawn` expects
+        if despawned
+            .as_ref()
+            .is_some_and(|despawned| despawned.contains::<T>(index))
+        {
+            continue;
+        }
+        ui.push_id(index, |ui| {
+            ui.collapsing(format!("#{index}"), |ui| {
+                reflect_ui(ui, &mut **component);
+                if ui.small_button("Despawn").clicked() {
+                    despawns.push(index);
+                }
+            });
+        });
+    }
+}
+
+fn resource_ui<T: Resource + Reflect + 'static>(world: &World, ui: &mut egui::Ui) {
+    if let Ok(mut resource) = world.try_get_resource_mut::<T>() {
+        reflect_ui(ui, &mut *resource);
+    }
+}
+
+fn spawn_default<T: Component + Default + 'static>(commands: &mut Commands) {
+    commands.spawn(T::default());
+}
+
+#[derive(Clone, Copy)]
+struct ComponentEntry {
+    name: &'static str,
+    count: fn(&World) -> usize,
+    show: fn(&World, &mut egui::Ui, &mut Vec<usize>),
+    spawn: Option<fn(&mut Commands)>,
+    despawn: fn(&mut Commands, usize),
+}
+
+#[derive(Clone, Copy)]
+struct ResourceEntry {
+    name: &'static str,
+    show: fn(&World, &mut egui::Ui),
+}
+
+/// A panel listing the registered components and resources of the world.
+///
+/// Components have no entities to group them, so they are listed by type.
+/// Despawned ones are hidden, see `Despawned`.
+pub struct Inspector {
+    pub open: bool,
+    /// Toggles the panel
+    pub key: Option<VirtualKeyCode>,
+    components: Vec<ComponentEntry>,
+    resources: Vec<ResourceEntry>,
+}
+
+impl std::fmt::Debug for Inspector {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("Inspector")
+            .field("open", &self.open)
+            .field("key", &self.key)
+            .field(
+                "components",
+                &self
+                    .components
+                    .iter()
+                    .map(|entry| entry.name)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "resources",
+                &self
+                    .resources
+                    .iter()
+                    .map(|entry| entry.name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Default for Inspector {
+    fn default() -> Self {
+        Self {
+            open: false,
+            key: Some(VirtualKeyCode::F12),
+            components: vec![],
+            resources: vec![],
+        }
+    }
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An inspector for the engine's own components and resources
+    pub fn with_engine_types() -> Self {
+        Self::new()
+            .with_spawnable_component::<DirectionalLight>()
+            .with_spawnable_component::<SpotLight>()
+            .with_spawnable_component::<Camera>()
+            .with_component::<Sprite>()
+            .with_resource::<RendererSettings>()
+            .with_resource::<AmbientLight>()
+            .with_resource::<UiScale>()
+            .with_resource::<Time>()
+    }
+
+    pub fn with_open(mut self, open: bool) -> Self {
+        self.open = open;
+        self
+    }
+
+    pub fn with_key(mut self, key: Option<VirtualKeyCode>) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn with_component<T: Component + Reflect + 'static>(mut self) -> Self {
+        self.register_component::<T>(None);
+        self
+    }
+
+    /// Also lets the panel spawn `T::default()`
+    pub fn with_spawnable_component<T: Component + Reflect + Default + 'static>(mut self) -> Self {
+        self.register_component::<T>(Some(spawn_default::<T>));
+        self
+    }
+
+    pub fn with_resource<T: Resource + Reflect + 'static>(mut self) -> Self {
+        self.resources.push(ResourceEntry {
+            name: short_type_name::<T>(),
+            show: resource_ui::<T>,
+        });
+        self
+    }
+
+    fn register_component<T: Component + Reflect + 'static>(
+        &mut self,
+        spawn: Option<fn(&mut Commands)>,
+    ) {
+        self.components.push(ComponentEntry {
+            name: short_type_name::<T>(),
+            count: count_components::<T>,
+            show: components_ui::<T>,
+            spawn,
+            despawn: Commands::despawn::<T>,
+        });
+    }
+
+    /// Names of the registered component types
+    pub fn components(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.components.iter().map(|entry| entry.name)
+    }
+
+    /// Names of the registered resource types
+    pub fn resources(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.resources.iter().map(|entry| entry.name)
+    }
+
+    /// Queues a default component of the registered type named `name`, returns false if it
+    /// isn't registered or can't be spawned
+    pub fn spawn(&self, name: &str, commands: &mut Commands) -> bool {
+        let spawn = self
+            .components
+            .iter()
+            .find(|entry| entry.name == name)
+            .and_then(|entry| entry.spawn);
+        if let Some(spawn) = spawn {
+            spawn(commands);
+        }
+        spawn.is_some()
+    }
+
+    /// Queues the despawn of the `index`th component of the registered type named `name`,
+    /// returns false if it isn't registered
+    pub fn despawn(&self, name: &str, index: usize, commands: &mut Commands) -> bool {
+        let despawn = self
+            .components
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| entry.despawn);
+        if let Some(despawn) = despawn {
+            despawn(commands, index);
+        }
+        despawn.is_some()
+    }
+}
+
+impl Resource for Inspector {}
+
+pub fn inspector_toggle_system(world: &World) {
+    let (Ok(mut inspector), Ok(input)) = (
+        world.try_get_resource_mut::<Inspector>(),
+        world.try_get_resource::<Input>(),
+    ) else {
+        return;
+    };
+    if inspector.key.is_some_and(|key| input.key_just_pressed(key)) {
+        inspector.open = !inspector.open;
+    }
+}
+
+/// Shows the inspector panel through the `Gui` resource
+pub fn inspector_system(world: &World) {
+    let Ok(gui) = world.try_get_resource::<Gui>() else {
+        return;
+    };
+    // The entries are copied out so that resources, the inspector included, stay unlocked
+    let (mut open, components, resources) = match world.try_get_resource::<Inspector>() {
+        Ok(inspector) => (
+            inspector.open,
+            inspector.components.clone(),
+            inspector.resources.clone(),
+        ),
+        Err(_) => return,
+    };
+    if !open {
+        return;
+    }
+
+    let mut spawns = vec![];
+    let mut despawns = vec![];
+    egui::Window::new("Inspector")
+        .open(&mut open)
+        .default_width(320.0)
+        .vscroll(true)
+        .show(gui.context(), |ui| {
+            ui.heading("Components");
+            for entry in components.iter() {
+                let count = (entry.count)(world);
+                ui.push_id(entry.name, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} ({count})", entry.name));
+                        if let Some(spawn) = entry.spawn {
+                            if ui.small_button("Spawn").clicked() {
+                                spawns.push(spawn);
+                            }
+                        }
+                    });
+                    if count > 0 {
+                        let mut indices = vec![];
+                        ui.indent(entry.name, |ui| (entry.show)(world, ui, &mut indices));
+                        despawns.extend(indices.into_iter().map(|index| (entry.despawn, index)));
+                    }
+                });
+            }
+
+            ui.separator();
+            ui.heading("Resources");
+            for entry in resources.iter() {
+                ui.push_id(entry.name, |ui| {
+                    ui.collapsing(entry.name, |ui| (entry.show)(world, ui));
+                });
+            }
+        });
+
+    if let Ok(mut inspector) = world.try_get_resource_mut::<Inspector>() {
+        inspector.open = open;
+    }
+    if let Ok(mut commands) = world.try_get_resource_mut::<Commands>() {
+        for spawn in spawns {
+            spawn(&mut commands);
+        }
+        for (despawn, index) in despawns {
+            despawn(&mut commands, index);
+        }
+    }
+}
+
+/// Adds an `Inspector` for the engine's types, toggled with F12
+#[derive(Debug, Clone, Default)]
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        let engine = if engine.has_plugin::<GuiPlugin>() {
+            engine
+        } else {
+            engine.add_plugin(GuiPlugin)
+        };
+        engine
+            .add_resource(Inspector::with_engine_types())
+            .add_system(DefaultOrdering::PreRun, inspector_toggle_system)
+            .add_system(DefaultOrdering::PostRun, inspector_system)
+    }
+}
+
+impl Reflect for DirectionalLight {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("direction", Field::Vec3(&mut self.direction)),
+            ("color", Field::Color(&mut self.color)),
+            ("illuminance", Field::F32(&mut self.illuminance)),
+            ("cast_shadows", Field::Bool(&mut self.cast_shadows)),
+        ]
+    }
+}
+
+impl Reflect for SpotLight {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("position", Field::Vec3(&mut self.position)),
+            ("direction", Field::Vec3(&mut self.direction)),
+            ("color", Field::Color(&mut self.color)),
+            ("intensity", Field::F32(&mut self.intensity)),
+            ("range", Field::F32(&mut self.range)),
+            (
+                "inner_angle_degrees",
+                Field::F32(&mut self.inner_angle_degrees),
+            ),
+            (
+                "outer_angle_degrees",
+                Field::F32(&mut self.outer_angle_degrees),
+            ),
+            ("cast_shadows", Field::Bool(&mut self.cast_shadows)),
+        ]
+    }
+}
+
+impl Reflect for Camera {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("position", Field::Vec3(&mut self.position)),
+            ("look_at", Field::Vec3(&mut self.look_at)),
+            ("up", Field::Vec3(&mut self.up)),
+            (
+                "projection",
+                Field::ReadOnly(format!("{:?}", self.projection)),
+            ),
+            ("order", Field::I32(&mut self.order)),
+        ]
+    }
+}
+
+impl Reflect for Sprite {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("position", Field::Vec2(&mut self.position)),
+            ("z", Field::F32(&mut self.z)),
+            ("rotation_degrees", Field::F32(&mut self.rotation_degrees)),
+            ("scale", Field::Vec2(&mut self.scale)),
+            ("tint", Field::Color(&mut self.tint)),
+            ("flip_x", Field::Bool(&mut self.flip_x)),
+            ("flip_y", Field::Bool(&mut self.flip_y)),
+        ]
+    }
+}
+
+impl Reflect for DebugRenderSettings {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("wireframe", Field::Bool(&mut self.wireframe)),
+            (
+                "cull_mode",
+                Field::ReadOnly(format!("{:?}", self.cull_mode)),
+            ),
+            ("show_normals", Field::Bool(&mut self.show_normals)),
+            ("normal_length", Field::F32(&mut self.normal_length)),
+            ("normal_color", Field::Color(&mut self.normal_color)),
+        ]
+    }
+}
+
+impl Reflect for SpriteSettings {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![(
+            "use_camera_projection",
+            Field::Bool(&mut self.use_camera_projection),
+        )]
+    }
+}
+
+impl Reflect for RendererSettings {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("clear_color", Field::Color(&mut self.clear_color)),
+            ("debug", Field::Struct(&mut self.debug)),
+            ("sprites", Field::Struct(&mut self.sprites)),
+        ]
+    }
+}
+
+impl Reflect for AmbientLight {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("color", Field::Color(&mut self.color)),
+            ("brightness", Field::F32(&mut self.brightness)),
+        ]
+    }
+}
+
+impl Reflect for UiScale {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        let (width, height) = self.window_size();
+        let factor = self.factor();
+        vec![
+            ("scale", Field::F32(&mut self.scale)),
+            ("factor", Field::ReadOnly(factor.to_string())),
+            ("window_size", Field::ReadOnly(format!("{width}x{height}"))),
+        ]
+    }
+}
+
+impl Reflect for Time {
+    fn fields(&mut self) -> Vec<(&'static str, Field<'_>)> {
+        vec![
+            ("delta", Field::ReadOnly(format!("{:?}", self.delta()))),
+            ("elapsed", Field::ReadOnly(format!("{:?}", self.elapsed()))),
+            (
+                "frame_count",
+                Field::ReadOnly(self.frame_count().to_string()),
+            ),
+        ]
+    }
+}
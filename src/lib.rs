@@ -1,92 +1,274 @@
+pub mod assets;
+pub mod commands;
+pub mod diagnostics;
+pub mod events;
 pub mod helpers;
+pub mod input;
+pub mod inspector;
+pub mod logging;
+pub mod plugin;
 pub mod renderer;
 pub mod shapes;
+pub mod state;
+pub mod time;
+pub mod ui;
 
-use renderer::Renderer;
+use std::any::TypeId;
+use std::fmt::Debug;
+use std::time::Instant;
+
+use commands::Commands;
+use diagnostics::Profiler;
+use events::Events;
+use plugin::{DefaultPlugins, EngineOrdering, Plugin};
+use renderer::{RenderPass, Renderer, RendererSettings};
 use winit::dpi::PhysicalSize;
 
+use starry_ecs::component::Component;
 use starry_ecs::resources::Resource;
-use starry_ecs::World;
+use starry_ecs::systems::SystemOrdering;
+use starry_ecs::{SystemType, World};
 
 use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 
-#[derive(Clone)]
+/// A function called with every event the window receives
+pub type WindowEventHandler = fn(world: &World, event: &WindowEvent);
+
 pub struct StarstruckEngine {
     app_name: String,
     world: World,
+    plugins: Vec<(TypeId, String)>,
+    window_event_handlers: Vec<WindowEventHandler>,
+    render_passes: Vec<Box<dyn RenderPass>>,
 }
 
 impl std::fmt::Debug for StarstruckEngine {
     fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         fmt.debug_struct("StarstruckEngine")
             .field("app_name", &self.app_name)
+            .field(
+                "plugins",
+                &self
+                    .plugins
+                    .iter()
+                    .map(|(_, name)| name)
+                    .collect::<Vec<_>>(),
+            )
             .finish()
     }
 }
 
 impl StarstruckEngine {
+    /// An engine with the `DefaultPlugins`, use `new_empty` to pick the plugins yourself
     pub fn new(app_name: &str) -> Self {
+        Self::new_empty(app_name).add_plugin(DefaultPlugins::default())
+    }
+
+    /// An engine without any plugin, it doesn't open a window until a `RendererPlugin` is added.
+    ///
+    /// ```no_run
+    /// use starstruck::input::InputPlugin;
+    /// use starstruck::plugin::DefaultPlugins;
+    /// use starstruck::StarstruckEngine;
+    ///
+    /// StarstruckEngine::new_empty("No Input")
+    ///     .add_plugin(DefaultPlugins::default().disable::<InputPlugin>());
+    /// ```
+    pub fn new_empty(app_name: &str) -> Self {
         Self {
             app_name: String::from(app_name),
-            world: World::new(),
+            world: World::new().add_resource(Commands::new()),
+            plugins: vec![],
+            window_event_handlers: vec![],
+            render_passes: vec![],
+        }
+    }
+
+    /// Adds a plugin to the engine.
+    ///
+    /// # Panics
+    /// Panics if a plugin of the same type was already added
+    pub fn add_plugin<P: Plugin>(self, plugin: P) -> Self {
+        self.add_plugin_by_id(TypeId::of::<P>(), &plugin)
+    }
+
+    pub(crate) fn add_plugin_by_id(mut self, id: TypeId, plugin: &dyn Plugin) -> Self {
+        if self.plugins.iter().any(|(added, _)| *added == id) {
+            panic!("Plugin `{}` was added twice", plugin.name());
+        }
+        self.plugins.push((id, String::from(plugin.name())));
+        let engine = plugin.build(self);
+        tracing::debug!("Added plugin {}", plugin.name());
+        engine
+    }
+
+    pub fn has_plugin<P: Plugin>(&self) -> bool {
+        self.plugins.iter().any(|(id, _)| *id == TypeId::of::<P>())
+    }
+
+    pub fn add_system<S: SystemOrdering>(mut self, ordering: S, system: SystemType) -> Self {
+        self.world = self.world.add_system(ordering, system);
+        self
+    }
+
+    pub fn add_startup_system(mut self, system: SystemType) -> Self {
+        self.world = self.world.add_startup_system(system);
+        self
+    }
+
+    pub fn add_resource<T: Resource + 'static>(mut self, resource: T) -> Self {
+        self.world = self.world.add_resource(resource);
+        self
+    }
+
+    pub fn add_component<T: Component + 'static>(mut self, component: T) -> Self {
+        self.world = self.world.add_component(component);
+        self
+    }
+
+    /// Adds an `Events<E>` resource which is updated at the start of every frame
+    pub fn add_event<E: Debug + 'static>(self) -> Self {
+        self.add_resource(Events::<E>::new())
+            .add_system(EngineOrdering::First, Events::<E>::update_system)
+    }
+
+    pub fn add_window_event_handler(mut self, handler: WindowEventHandler) -> Self {
+        self.window_event_handlers.push(handler);
+        self
+    }
+
+    pub fn add_render_pass<P: RenderPass>(mut self, pass: P) -> Self {
+        self.render_passes.push(Box::new(pass));
+        self
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Runs the startup systems once
+    pub fn startup(&mut self) {
+        let world = std::mem::replace(&mut self.world, World::new());
+        self.world = Commands::apply(world.start());
+    }
+
+    /// Runs every system once, then applies the queued `Commands`.
+    ///
+    /// Each update starts a new frame of the `Profiler`.
+    pub fn update(&mut self) {
+        let start = Instant::now();
+        if let Ok(mut profiler) = self.world.try_get_resource_mut::<Profiler>() {
+            profiler.begin_frame(start);
+        }
+        let world = std::mem::replace(&mut self.world, World::new());
+        self.world = Commands::apply(world.single_step());
+        if let Ok(mut profiler) = self.world.try_get_resource_mut::<Profiler>() {
+            profiler.record("systems", start, Instant::now());
         }
     }
-    pub async fn run(self) {
+
+    pub async fn run(mut self) {
+        let startup = tracing::info_span!("startup", app = %self.app_name).entered();
         let event_loop = EventLoop::new();
         let window = winit::window::Window::new(&event_loop).unwrap();
+        let window_id = window.id();
+
+        let settings = self
+            .world
+            .try_get_resource::<RendererSettings>()
+            .map(|settings| settings.clone());
+        // Without a renderer the window still has to be kept alive
+        let (mut renderer, headless_window) = match settings {
+            Ok(settings) => {
+                let passes = std::mem::take(&mut self.render_passes);
+                (Some(Renderer::new(window, settings, passes).await), None)
+            }
+            Err(_) => (None, Some(window)),
+        };
 
-        let mut renderer = Renderer::new(window).await;
+        self.startup();
+        drop(startup);
+        tracing::info!("Started {}", self.app_name);
 
+        let mut frame = 0u64;
         event_loop.run(move |event, _, control_flow| {
+            let _ = &headless_window;
             match event {
                 Event::WindowEvent {
                     ref event,
-                    window_id,
-                } if window_id == renderer.window().id() => {
-                    if !renderer.input(event) {
-                        // UPDATED!
-                        match event {
-                            WindowEvent::CloseRequested
-                            | WindowEvent::KeyboardInput {
-                                input:
-                                    KeyboardInput {
-                                        state: ElementState::Pressed,
-                                        virtual_keycode: Some(VirtualKeyCode::Escape),
-                                        ..
-                                    },
-                                ..
-                            } => *control_flow = ControlFlow::Exit,
-                            WindowEvent::Resized(physical_size) => {
-                                let PhysicalSize {
-                                    width: x,
-                                    height: y,
-                                } = *physical_size;
+                    window_id: id,
+                } if id == window_id => {
+                    // Handlers see every event, even ones a `Gui` panel used, so releases and focus
+                    // changes always arrive. Games ask `Gui::wants_keyboard_input` to ignore typing.
+                    let consumed = renderer
+                        .as_mut()
+                        .is_some_and(|renderer| renderer.input(&self.world, event));
+                    for handler in self.window_event_handlers.iter() {
+                        handler(&self.world, event);
+                    }
+                    // UPDATED!
+                    match event {
+                        WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                        WindowEvent::KeyboardInput {
+                            input:
+                                KeyboardInput {
+                                    state: ElementState::Pressed,
+                                    virtual_keycode: Some(VirtualKeyCode::Escape),
+                                    ..
+                                },
+                            ..
+                        } if !consumed => *control_flow = ControlFlow::Exit,
+                        WindowEvent::Resized(physical_size) => {
+                            let PhysicalSize {
+                                width: x,
+                                height: y,
+                            } = *physical_size;
+                            if let Some(renderer) = renderer.as_mut() {
                                 renderer.resize(x, y);
                             }
-                            WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                                let PhysicalSize {
-                                    width: x,
-                                    height: y,
-                                } = **new_inner_size;
+                        }
+                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                            let PhysicalSize {
+                                width: x,
+                                height: y,
+                            } = **new_inner_size;
+                            if let Some(renderer) = renderer.as_mut() {
                                 renderer.resize(x, y);
                             }
-                            _ => {}
                         }
+                        _ => {}
                     }
                 }
-                Event::RedrawRequested(window_id) if window_id == renderer.window().id() => {
-                    renderer.update();
-                    match renderer.render() {
+                Event::MainEventsCleared => match renderer.as_ref() {
+                    Some(renderer) => renderer.window().request_redraw(),
+                    None => self.update(),
+                },
+                Event::RedrawRequested(id) if id == window_id => {
+                    let _frame = tracing::trace_span!("frame", index = frame).entered();
+                    frame += 1;
+                    if let Some(renderer) = renderer.as_mut() {
+                        renderer.begin_frame(&self.world);
+                    }
+                    self.update();
+                    let Some(renderer) = renderer.as_mut() else {
+                        return;
+                    };
+                    renderer.update(&self.world);
+                    match renderer.render(&self.world) {
                         Ok(_) => {}
                         // Reconfigure the surface if lost
                         Err(wgpu::SurfaceError::Lost) => {
+                            tracing::debug!("Surface lost, reconfiguring it");
                             renderer.resize(renderer.size.width, renderer.size.height)
                         }
                         // The system is out of memory, we should probably quit
-                        Err(wgpu::SurfaceError::OutOfMemory) => *control_flow = ControlFlow::Exit,
+                        Err(wgpu::SurfaceError::OutOfMemory) => {
+                            tracing::error!("Out of memory, exiting");
+                            *control_flow = ControlFlow::Exit
+                        }
                         // All other errors (Outdated, Timeout) should be resolved by the next frame
-                        Err(e) => eprintln!("{:?}", e),
+                        Err(e) => tracing::warn!("Skipped a frame: {:?}", e),
                     }
                 }
                 _ => {}
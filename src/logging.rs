@@ -0,0 +1,110 @@
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use thiserror::Error;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::plugin::Plugin;
+use crate::StarstruckEngine;
+
+#[derive(Error, Debug)]
+pub enum LoggingError {
+    #[error("invalid log filter: {0}")]
+    Filter(#[from] tracing_subscriber::filter::ParseError),
+    #[error("couldn't open the log file: {0}")]
+    File(#[from] std::io::Error),
+    #[error("a logger is already installed: {0}")]
+    AlreadyInstalled(#[from] tracing_subscriber::util::TryInitError),
+}
+
+/// Where the engine's `tracing` events go and which ones are kept
+#[derive(Debug, Clone)]
+pub struct LogSettings {
+    /// Directives like `info,starstruck=debug`, `RUST_LOG` takes precedence when set
+    pub filter: String,
+    /// Also writes every event to this file, without colors
+    pub file: Option<PathBuf>,
+    pub ansi: bool,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            // wgpu logs every resource it creates at info
+            filter: String::from("info,wgpu_core=warn,wgpu_hal=warn,naga=warn"),
+            file: None,
+            ansi: true,
+        }
+    }
+}
+
+impl LogSettings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_filter(mut self, filter: &str) -> Self {
+        self.filter = String::from(filter);
+        self
+    }
+
+    pub fn with_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    pub fn with_ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    fn env_filter(&self) -> Result<EnvFilter, LoggingError> {
+        match std::env::var(EnvFilter::DEFAULT_ENV) {
+            Ok(filter) if !filter.is_empty() => Ok(EnvFilter::try_new(filter)?),
+            _ => Ok(EnvFilter::try_new(&self.filter)?),
+        }
+    }
+
+    /// Installs the global subscriber, `log` records from dependencies included
+    pub fn init(&self) -> Result<(), LoggingError> {
+        let file = match self.file.as_ref() {
+            Some(path) => Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(Mutex::new(File::create(path)?))
+                    .with_filter(self.env_filter()?),
+            ),
+            None => None,
+        };
+        tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(self.ansi)
+                    .with_filter(self.env_filter()?),
+            )
+            .with(file)
+            .try_init()?;
+        Ok(())
+    }
+}
+
+/// Prints the engine's logs, keeping the logger installed beforehand if there is one
+#[derive(Debug, Clone, Default)]
+pub struct LogPlugin {
+    pub settings: LogSettings,
+}
+
+impl Plugin for LogPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        match self.settings.init() {
+            Ok(_) => {}
+            Err(error @ LoggingError::AlreadyInstalled(_)) => tracing::debug!("{}", error),
+            // Nothing would receive the event without a logger
+            Err(error) => eprintln!("{}", error),
+        }
+        engine
+    }
+}
@@ -0,0 +1,135 @@
+use std::any::{type_name, TypeId};
+
+use starry_ecs::systems::SystemOrdering;
+
+use crate::input::InputPlugin;
+use crate::logging::LogPlugin;
+use crate::renderer::{RendererPlugin, SpriteAnimationPlugin};
+use crate::time::TimePlugin;
+use crate::StarstruckEngine;
+
+/// A reusable piece of engine setup.
+///
+/// Plugins register their systems, resources, events and render passes when
+/// they are added to the engine.
+///
+/// ```no_run
+/// use starstruck::plugin::Plugin;
+/// use starstruck::StarstruckEngine;
+/// use starry_ecs::systems::DefaultOrdering;
+/// use starry_ecs::World;
+///
+/// fn hello(_: &World) {
+///     println!("Hello!");
+/// }
+///
+/// struct HelloPlugin;
+///
+/// impl Plugin for HelloPlugin {
+///     fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+///         engine.add_system(DefaultOrdering::Run, hello)
+///     }
+/// }
+///
+/// StarstruckEngine::new_empty("Hello").add_plugin(HelloPlugin);
+/// ```
+pub trait Plugin: 'static {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine;
+
+    fn name(&self) -> &str {
+        type_name::<Self>()
+    }
+}
+
+/// System orderings the engine uses around the ones from `DefaultOrdering`
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EngineOrdering {
+    /// Runs before `DefaultOrdering::PreRun`
+    First = 0,
+    /// Runs after `DefaultOrdering::PostRun`
+    Last = 4,
+}
+
+impl From<EngineOrdering> for i32 {
+    fn from(ordering: EngineOrdering) -> Self {
+        ordering as i32
+    }
+}
+
+impl SystemOrdering for EngineOrdering {}
+
+/// The plugins every game wants: logging, time, input and the renderer.
+///
+/// `StarstruckEngine::new` adds them. Any of them can be disabled or swapped out
+/// before the group is added to an engine made with `StarstruckEngine::new_empty`.
+///
+/// ```no_run
+/// use starstruck::input::InputPlugin;
+/// use starstruck::plugin::DefaultPlugins;
+/// use starstruck::StarstruckEngine;
+///
+/// StarstruckEngine::new_empty("No Input")
+///     .add_plugin(DefaultPlugins::default().disable::<InputPlugin>());
+/// ```
+pub struct DefaultPlugins {
+    plugins: Vec<(TypeId, Box<dyn Plugin>)>,
+}
+
+impl Default for DefaultPlugins {
+    fn default() -> Self {
+        Self { plugins: vec![] }
+            .with(LogPlugin::default())
+            .with(TimePlugin)
+            .with(InputPlugin)
+            .with(RendererPlugin::default())
+            .with(SpriteAnimationPlugin)
+    }
+}
+
+impl DefaultPlugins {
+    /// Appends a plugin to the end of the group
+    pub fn with<P: Plugin>(mut self, plugin: P) -> Self {
+        self.plugins.push((TypeId::of::<P>(), Box::new(plugin)));
+        self
+    }
+
+    /// Removes the plugin of type `P` from the group
+    pub fn disable<P: Plugin>(mut self) -> Self {
+        self.plugins.retain(|(id, _)| *id != TypeId::of::<P>());
+        self
+    }
+
+    /// Replaces the plugin of type `P`, keeping its position in the group
+    pub fn set<P: Plugin>(mut self, plugin: P) -> Self {
+        match self
+            .plugins
+            .iter_mut()
+            .find(|(id, _)| *id == TypeId::of::<P>())
+        {
+            Some((_, existing)) => *existing = Box::new(plugin),
+            None => self.plugins.push((TypeId::of::<P>(), Box::new(plugin))),
+        }
+        self
+    }
+
+    pub fn contains<P: Plugin>(&self) -> bool {
+        self.plugins.iter().any(|(id, _)| *id == TypeId::of::<P>())
+    }
+}
+
+impl std::fmt::Debug for DefaultPlugins {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_list()
+            .entries(self.plugins.iter().map(|(_, plugin)| plugin.name()))
+            .finish()
+    }
+}
+
+impl Plugin for DefaultPlugins {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        self.plugins.iter().fold(engine, |engine, (id, plugin)| {
+            engine.add_plugin_by_id(*id, plugin.as_ref())
+        })
+    }
+}
@@ -0,0 +1,280 @@
+use starry_ecs::component::Component;
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::World;
+
+use crate::commands::WorldExt;
+use crate::events::Events;
+use crate::plugin::Plugin;
+use crate::renderer::atlas::{PlaybackMode, TextureAtlas};
+use crate::renderer::sprite::{Rect, Sprite};
+use crate::time::Time;
+use crate::StarstruckEngine;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationFrame {
+    pub region: Rect,
+    /// Seconds the frame is shown for at a speed of 1.0
+    pub duration: f32,
+    /// Sent as an `AnimationEvent` when the frame is reached
+    pub event: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimationEventKind {
+    /// An `AnimationFrame::event` was reached
+    Frame { name: String, frame: usize },
+    /// A looping animation went back to its first frame
+    Looped,
+    /// An animation played `Once` or in `Reverse` showed its last frame
+    Finished,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationEvent {
+    pub animation: String,
+    pub kind: AnimationEventKind,
+}
+
+/// Frames shown one after the other
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAnimation {
+    pub name: String,
+    pub frames: Vec<AnimationFrame>,
+    pub mode: PlaybackMode,
+    pub speed: f32,
+    pub playing: bool,
+    frame: usize,
+    elapsed: f32,
+    backwards: bool,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    pub fn new(name: &str, frames: Vec<AnimationFrame>) -> Self {
+        Self {
+            name: String::from(name),
+            frames,
+            mode: PlaybackMode::Loop,
+            speed: 1.0,
+            playing: true,
+            frame: 0,
+            elapsed: 0.0,
+            backwards: false,
+            finished: false,
+        }
+    }
+
+    /// Shows the atlas regions `from..=to`, using the sheet's durations when it has them
+    pub fn from_atlas(
+        name: &str,
+        atlas: &TextureAtlas,
+        from: usize,
+        to: usize,
+        frame_duration: f32,
+    ) -> Self {
+        let frames = (from..=to)
+            .filter_map(|index| {
+                Some(AnimationFrame {
+                    region: atlas.region(index)?,
+                    duration: atlas.duration(index).unwrap_or(frame_duration),
+                    event: None,
+                })
+            })
+            .collect();
+        Self::new(name, frames)
+    }
+
+    /// Plays one of the sheet's frame tags with the tag's direction
+    pub fn from_tag(atlas: &TextureAtlas, tag: &str, frame_duration: f32) -> Option<Self> {
+        let atlas_tag = atlas.tag(tag)?;
+        Some(
+            Self::from_atlas(tag, atlas, atlas_tag.from, atlas_tag.to, frame_duration)
+                .with_mode(atlas_tag.mode),
+        )
+    }
+
+    pub fn with_mode(mut self, mode: PlaybackMode) -> Self {
+        self.mode = mode;
+        self.restart();
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sends an event named `event` whenever `frame` is reached
+    pub fn with_event(mut self, frame: usize, event: &str) -> Self {
+        if let Some(frame) = self.frames.get_mut(frame) {
+            frame.event = Some(String::from(event));
+        }
+        self
+    }
+
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn restart(&mut self) {
+        self.backwards = self.mode == PlaybackMode::Reverse;
+        self.frame = if self.backwards {
+            self.frames.len().saturating_sub(1)
+        } else {
+            0
+        };
+        self.elapsed = 0.0;
+        self.finished = false;
+        self.playing = true;
+    }
+
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn region(&self) -> Option<Rect> {
+        self.frames.get(self.frame).map(|frame| frame.region)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn event(&self, kind: AnimationEventKind) -> AnimationEvent {
+        AnimationEvent {
+            animation: self.name.clone(),
+            kind,
+        }
+    }
+
+    /// Moves to the next frame and returns true, or returns false at the end of the animation
+    fn step(&mut self, events: &mut Vec<AnimationEvent>) -> bool {
+        let last = self.frames.len() - 1;
+        match (self.mode, self.backwards) {
+            (PlaybackMode::Once, _) | (PlaybackMode::Reverse, _) => {
+                let end = if self.backwards { 0 } else { last };
+                if self.frame == end {
+                    self.finished = true;
+                    self.playing = false;
+                    events.push(self.event(AnimationEventKind::Finished));
+                    return false;
+                }
+                self.frame = if self.backwards {
+                    self.frame - 1
+                } else {
+                    self.frame + 1
+                };
+            }
+            (PlaybackMode::Loop, _) => {
+                self.frame = (self.frame + 1) % self.frames.len();
+                if self.frame == 0 {
+                    events.push(self.event(AnimationEventKind::Looped));
+                }
+            }
+            (PlaybackMode::PingPong, false) => {
+                if self.frame == last {
+                    self.backwards = true;
+                    self.frame = last.saturating_sub(1);
+                } else {
+                    self.frame += 1;
+                }
+            }
+            (PlaybackMode::PingPong, true) => {
+                if self.frame == 0 {
+                    self.backwards = false;
+                    self.frame = last.min(1);
+                    events.push(self.event(AnimationEventKind::Looped));
+                } else {
+                    self.frame -= 1;
+                }
+            }
+        }
+
+        let frame = &self.frames[self.frame];
+        if let Some(name) = frame.event.clone() {
+            events.push(self.event(AnimationEventKind::Frame {
+                name,
+                frame: self.frame,
+            }));
+        }
+        true
+    }
+
+    /// Advances the animation by `delta_seconds`, returning the events it sent
+    pub fn advance(&mut self, delta_seconds: f32) -> Vec<AnimationEvent> {
+        let mut events = vec![];
+        if !self.playing || self.frames.is_empty() {
+            return events;
+        }
+
+        self.elapsed += delta_seconds * self.speed;
+        loop {
+            // Frames without a duration would never let the loop end
+            let duration = self.frames[self.frame].duration.max(f32::EPSILON);
+            if self.elapsed < duration {
+                break;
+            }
+            self.elapsed -= duration;
+            if !self.step(&mut events) {
+                self.elapsed = 0.0;
+                break;
+            }
+        }
+        events
+    }
+}
+
+/// A sprite showing the current frame of its animation
+#[derive(Debug, Clone)]
+pub struct AnimatedSprite {
+    pub sprite: Sprite,
+    pub animation: SpriteAnimation,
+}
+
+impl AnimatedSprite {
+    pub fn new(sprite: Sprite, animation: SpriteAnimation) -> Self {
+        let mut animated = Self { sprite, animation };
+        animated.sprite.region = animated.animation.region().or(animated.sprite.region);
+        animated
+    }
+}
+
+impl Component for AnimatedSprite {}
+
+pub fn animate_sprites_system(world: &World) {
+    let Ok(time) = world.try_get_resource::<Time>() else {
+        return;
+    };
+    let Ok(mut sprites) = world.try_get_spawned_mut::<AnimatedSprite>() else {
+        return;
+    };
+
+    let mut sent = vec![];
+    for animated in sprites.iter_mut() {
+        sent.extend(animated.animation.advance(time.delta_seconds()));
+        if let Some(region) = animated.animation.region() {
+            animated.sprite.region = Some(region);
+        }
+    }
+    if let Ok(mut events) = world.try_get_resource_mut::<Events<AnimationEvent>>() {
+        for event in sent {
+            events.send(event);
+        }
+    }
+}
+
+/// Advances every `AnimatedSprite` and sends their `AnimationEvent`s
+#[derive(Debug, Clone, Default)]
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        engine
+            .add_event::<AnimationEvent>()
+            .add_system(DefaultOrdering::PreRun, animate_sprites_system)
+    }
+}
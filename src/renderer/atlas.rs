@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+
+use glam::Vec2;
+use serde::de::{Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::renderer::image::Image;
+use crate::renderer::sprite::Rect;
+
+#[derive(Error, Debug)]
+pub enum AtlasError {
+    #[error("Images don't fit in an atlas of {max_size}x{max_size} pixels")]
+    TooLarge { max_size: u32 },
+    #[error("Failed to parse sprite sheet: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Sprite sheet is missing `{0}`")]
+    MissingField(String),
+}
+
+/// The parts of a sprite sheet the atlas reads, with frames in the order of the document.
+///
+/// `serde_json` sorts object keys, which would put `walk 10` before `walk 2`.
+#[derive(Default)]
+struct SpriteSheet {
+    frames: Option<Vec<(String, Value)>>,
+    meta: Value,
+}
+
+impl<'de> Deserialize<'de> for SpriteSheet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Frames(Vec<(String, Value)>);
+
+        impl<'de> Deserialize<'de> for Frames {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(FramesVisitor)
+            }
+        }
+
+        struct FramesVisitor;
+
+        impl<'de> Visitor<'de> for FramesVisitor {
+            type Value = Frames;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("an object or an array of frames")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Frames, A::Error> {
+                let mut frames = vec![];
+                while let Some(frame) = map.next_entry::<String, Value>()? {
+                    frames.push(frame);
+                }
+                Ok(Frames(frames))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Frames, A::Error> {
+                let mut frames = vec![];
+                while let Some(frame) = seq.next_element::<Value>()? {
+                    let name = frame["filename"].as_str().unwrap_or_default().to_string();
+                    frames.push((name, frame));
+                }
+                Ok(Frames(frames))
+            }
+        }
+
+        struct SheetVisitor;
+
+        impl<'de> Visitor<'de> for SheetVisitor {
+            type Value = SpriteSheet;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a sprite sheet object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SpriteSheet, A::Error> {
+                let mut sheet = SpriteSheet::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "frames" => sheet.frames = Some(map.next_value::<Frames>()?.0),
+                        "meta" => sheet.meta = map.next_value()?,
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(sheet)
+            }
+        }
+
+        deserializer.deserialize_map(SheetVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackMode {
+    Once,
+    #[default]
+    Loop,
+    /// Plays forward then backward, forever
+    PingPong,
+    Reverse,
+}
+
+/// A named range of frames, like Aseprite's frame tags
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasTag {
+    pub from: usize,
+    pub to: usize,
+    pub mode: PlaybackMode,
+}
+
+/// Regions of a single image, in pixels
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextureAtlas {
+    pub size: (u32, u32),
+    pub regions: Vec<Rect>,
+    names: HashMap<String, usize>,
+    // Seconds each frame is shown for, when the sheet says
+    durations: Vec<Option<f32>>,
+    tags: HashMap<String, AtlasTag>,
+}
+
+impl TextureAtlas {
+    pub fn new(size: (u32, u32)) -> Self {
+        Self {
+            size,
+            ..Default::default()
+        }
+    }
+
+    pub fn add_region(&mut self, region: Rect) -> usize {
+        self.regions.push(region);
+        self.durations.push(None);
+        self.regions.len() - 1
+    }
+
+    pub fn add_named_region(&mut self, name: &str, region: Rect) -> usize {
+        let index = self.add_region(region);
+        self.names.insert(String::from(name), index);
+        index
+    }
+
+    /// Cuts an image into `columns` by `rows` tiles, left to right then top to bottom.
+    ///
+    /// `padding` is the space between tiles and `offset` the space before the first one.
+    pub fn from_grid(
+        size: (u32, u32),
+        tile_size: (u32, u32),
+        columns: u32,
+        rows: u32,
+        padding: (u32, u32),
+        offset: (u32, u32),
+    ) -> Self {
+        let mut atlas = Self::new(size);
+        for row in 0..rows {
+            for column in 0..columns {
+                let x = offset.0 + column * (tile_size.0 + padding.0);
+                let y = offset.1 + row * (tile_size.1 + padding.1);
+                atlas.add_region(Rect::new(
+                    x as f32,
+                    y as f32,
+                    tile_size.0 as f32,
+                    tile_size.1 as f32,
+                ));
+            }
+        }
+        atlas
+    }
+
+    /// Reads the JSON written by Aseprite or TexturePacker, in the hash or array format
+    pub fn from_json(json: &str) -> Result<Self, AtlasError> {
+        let sheet: SpriteSheet = serde_json::from_str(json)?;
+        let frames = sheet
+            .frames
+            .ok_or_else(|| AtlasError::MissingField(String::from("frames")))?;
+
+        let number = |value: &Value, field: &str| {
+            value[field]
+                .as_f64()
+                .map(|number| number as f32)
+                .ok_or_else(|| AtlasError::MissingField(String::from(field)))
+        };
+        let meta = &sheet.meta;
+        let size = (
+            meta["size"]["w"].as_u64().unwrap_or_default() as u32,
+            meta["size"]["h"].as_u64().unwrap_or_default() as u32,
+        );
+
+        let mut atlas = Self::new(size);
+        for (name, frame) in frames {
+            let rect = &frame["frame"];
+            let region = Rect::new(
+                number(rect, "x")?,
+                number(rect, "y")?,
+                number(rect, "w")?,
+                number(rect, "h")?,
+            );
+            let index = atlas.add_named_region(&name, region);
+            // Aseprite durations are in milliseconds
+            atlas.durations[index] = frame["duration"]
+                .as_f64()
+                .map(|duration| duration as f32 / 1000.0);
+        }
+
+        for tag in meta["frameTags"].as_array().into_iter().flatten() {
+            let (Some(name), Some(from), Some(to)) = (
+                tag["name"].as_str(),
+                tag["from"].as_u64(),
+                tag["to"].as_u64(),
+            ) else {
+                continue;
+            };
+            let mode = match tag["direction"].as_str() {
+                Some("reverse") => PlaybackMode::Reverse,
+                Some("pingpong") => PlaybackMode::PingPong,
+                _ => PlaybackMode::Loop,
+            };
+            atlas.tags.insert(
+                String::from(name),
+                AtlasTag {
+                    from: from as usize,
+                    to: to as usize,
+                    mode,
+                },
+            );
+        }
+
+        Ok(atlas)
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn region(&self, index: usize) -> Option<Rect> {
+        self.regions.get(index).copied()
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    pub fn named_region(&self, name: &str) -> Option<Rect> {
+        self.index_of(name).and_then(|index| self.region(index))
+    }
+
+    pub fn duration(&self, index: usize) -> Option<f32> {
+        self.durations.get(index).copied().flatten()
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&AtlasTag> {
+        self.tags.get(name)
+    }
+}
+
+/// Packs images into a single one at load time
+#[derive(Debug, Clone)]
+pub struct TextureAtlasBuilder {
+    images: Vec<(Option<String>, Image)>,
+    padding: u32,
+    max_size: u32,
+}
+
+impl Default for TextureAtlasBuilder {
+    fn default() -> Self {
+        Self {
+            images: vec![],
+            padding: 1,
+            max_size: 4096,
+        }
+    }
+}
+
+impl TextureAtlasBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Empty pixels around every image, so filtering doesn't bleed into neighbours
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Returns the index of the image's region in the built atlas
+    pub fn add(&mut self, image: Image) -> usize {
+        self.images.push((None, image));
+        self.images.len() - 1
+    }
+
+    pub fn add_named(&mut self, name: &str, image: Image) -> usize {
+        self.images.push((Some(String::from(name)), image));
+        self.images.len() - 1
+    }
+
+    /// Shelf packing, tallest images first, in the smallest power of two square that fits
+    fn pack(&self, size: u32) -> Option<Vec<(u32, u32)>> {
+        let mut order = (0..self.images.len()).collect::<Vec<_>>();
+        order.sort_by_key(|index| std::cmp::Reverse(self.images[*index].1.height));
+
+        let mut positions = vec![(0, 0); self.images.len()];
+        let (mut x, mut y, mut shelf_height) = (self.padding, self.padding, 0);
+        for index in order {
+            let image = &self.images[index].1;
+            let (width, height) = (image.width + self.padding, image.height + self.padding);
+            if x + width > size {
+                x = self.padding;
+                y += shelf_height;
+                shelf_height = 0;
+            }
+            if x + width > size || y + height > size {
+                return None;
+            }
+            positions[index] = (x, y);
+            x += width;
+            shelf_height = shelf_height.max(height);
+        }
+        Some(positions)
+    }
+
+    pub fn build(&self) -> Result<(Image, TextureAtlas), AtlasError> {
+        let area = self
+            .images
+            .iter()
+            .map(|(_, image)| {
+                (image.width + self.padding) as u64 * (image.height + self.padding) as u64
+            })
+            .sum::<u64>();
+        let mut size = ((area as f64).sqrt() as u32).next_power_of_two().max(1);
+        let positions = loop {
+            if size > self.max_size {
+                return Err(AtlasError::TooLarge {
+                    max_size: self.max_size,
+                });
+            }
+            if let Some(positions) = self.pack(size) {
+                break positions;
+            }
+            size *= 2;
+        };
+
+        let mut data = vec![0; size as usize * size as usize * 4];
+        let mut atlas = TextureAtlas::new((size, size));
+        for ((name, image), (x, y)) in self.images.iter().zip(positions) {
+            let row = image.width as usize * 4;
+            for line in 0..image.height as usize {
+                let start = ((y as usize + line) * size as usize + x as usize) * 4;
+                data[start..start + row].copy_from_slice(&image.data[line * row..(line + 1) * row]);
+            }
+            let region = Rect {
+                min: Vec2::new(x as f32, y as f32),
+                max: Vec2::new((x + image.width) as f32, (y + image.height) as f32),
+            };
+            match name {
+                Some(name) => atlas.add_named_region(name, region),
+                None => atlas.add_region(region),
+            };
+        }
+
+        let filter = self
+            .images
+            .first()
+            .map(|(_, image)| image.filter)
+            .unwrap_or_default();
+        let image = Image::new(size, size, data)
+            .expect("atlas data matches its size")
+            .with_filter(filter);
+        Ok((image, atlas))
+    }
+}
@@ -0,0 +1,139 @@
+use glam::{Mat4, Vec3};
+use starry_ecs::component::Component;
+use starry_ecs::World;
+use wgpu::Color;
+
+use crate::commands::WorldExt;
+use crate::renderer::target::RenderTargetId;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective {
+        fov_y_degrees: f32,
+        near: f32,
+        far: f32,
+    },
+    /// `height` is the number of world units visible vertically
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+impl Projection {
+    pub fn matrix(&self, aspect_ratio: f32) -> Mat4 {
+        match *self {
+            Projection::Perspective {
+                fov_y_degrees,
+                near,
+                far,
+            } => Mat4::perspective_rh(fov_y_degrees.to_radians(), aspect_ratio, near, far),
+            Projection::Orthographic { height, near, far } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * aspect_ratio;
+                Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    near,
+                    far,
+                )
+            }
+        }
+    }
+}
+
+/// Where a camera draws to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CameraTarget {
+    #[default]
+    Window,
+    Texture(RenderTargetId),
+}
+
+/// Renders the scene from `position` looking at `look_at`.
+///
+/// Cameras are drawn in increasing `order`, so a camera rendering into a texture
+/// should have a lower order than the cameras that show that texture.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub position: Vec3,
+    pub look_at: Vec3,
+    pub up: Vec3,
+    pub projection: Projection,
+    pub target: CameraTarget,
+    /// Falls back to the clear color in `RendererSettings`
+    pub clear_color: Option<Color>,
+    pub order: i32,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(0.0, 0.0, 2.0),
+            look_at: Vec3::ZERO,
+            up: Vec3::Y,
+            projection: Projection::Perspective {
+                fov_y_degrees: 60.0,
+                near: 0.1,
+                far: 100.0,
+            },
+            target: CameraTarget::Window,
+            clear_color: None,
+            order: 0,
+        }
+    }
+}
+
+impl Camera {
+    /// Looks down the Z axis with `height` world units visible vertically, for 2D scenes
+    pub fn orthographic(height: f32) -> Self {
+        Self {
+            position: Vec3::new(0.0, 0.0, 500.0),
+            projection: Projection::Orthographic {
+                height,
+                near: 0.0,
+                far: 1000.0,
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn view(&self) -> Mat4 {
+        Mat4::look_at_rh(self.position, self.look_at, self.up)
+    }
+
+    pub fn view_projection(&self, aspect_ratio: f32) -> Mat4 {
+        self.projection.matrix(aspect_ratio) * self.view()
+    }
+}
+
+impl Component for Camera {}
+
+/// The camera with the lowest order drawing to the window
+pub(crate) fn window_camera(world: &World) -> Option<Camera> {
+    let cameras = world.try_get_spawned::<Camera>().ok()?;
+    cameras
+        .iter()
+        .filter(|camera| camera.target == CameraTarget::Window)
+        .min_by_key(|camera| camera.order)
+        .map(|camera| (**camera).clone())
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view_projection: [[f32; 4]; 4],
+}
+
+impl CameraUniform {
+    pub fn new(view_projection: Mat4) -> Self {
+        Self {
+            view_projection: view_projection.to_cols_array_2d(),
+        }
+    }
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self::new(Mat4::IDENTITY)
+    }
+}
@@ -0,0 +1,463 @@
+use glam::{Mat4, Vec3};
+use starry_ecs::resources::Resource;
+use starry_ecs::World;
+use wgpu::{
+    include_wgsl, BindGroup, Buffer, Color, CommandEncoder, Device, LoadOp, Operations,
+    RenderPassColorAttachment, RenderPipeline,
+};
+
+use crate::commands::WorldExt;
+use crate::renderer::camera::{window_camera, CameraUniform};
+use crate::renderer::graph::{PassBuilder, RenderResources, SCENE_COLOR};
+use crate::renderer::mesh::Mesh;
+use crate::renderer::pass::{write_buffer, RenderContext, RenderPass};
+use crate::renderer::post::PostProcessor;
+use crate::renderer::text::FontId;
+use crate::renderer::RendererSettings;
+
+/// Which faces of the meshes the scene pass skips
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CullMode {
+    None,
+    Front,
+    /// Faces wound clockwise on screen
+    #[default]
+    Back,
+}
+
+impl CullMode {
+    pub fn face(&self) -> Option<wgpu::Face> {
+        match self {
+            CullMode::None => None,
+            CullMode::Front => Some(wgpu::Face::Front),
+            CullMode::Back => Some(wgpu::Face::Back),
+        }
+    }
+}
+
+/// Ways to inspect the geometry of the scene, changed at runtime through `RendererSettings`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugRenderSettings {
+    /// Draws the edges of triangles only, with line rasterization when the GPU supports it
+    pub wireframe: bool,
+    pub cull_mode: CullMode,
+    /// Draws the normal of every mesh triangle with `DebugDraw`, following its winding
+    pub show_normals: bool,
+    pub normal_length: f32,
+    pub normal_color: Color,
+}
+
+impl Default for DebugRenderSettings {
+    fn default() -> Self {
+        Self {
+            wireframe: false,
+            cull_mode: CullMode::Back,
+            show_normals: false,
+            normal_length: 0.1,
+            normal_color: Color {
+                r: 1.0,
+                g: 1.0,
+                b: 0.0,
+                a: 1.0,
+            },
+        }
+    }
+}
+
+/// Normal of the triangle `a`, `b`, `c` wound counter-clockwise
+pub fn face_normal(a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+    (b - a).cross(c - a).normalize_or_zero()
+}
+
+/// Segments used for circles and spheres
+const CIRCLE_SEGMENTS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugLine {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub color: Color,
+}
+
+/// Text drawn on the window at the projection of `position`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugLabel {
+    pub position: Vec3,
+    pub text: String,
+    pub color: Color,
+}
+
+/// Lines and labels drawn on top of the scene for a single frame.
+///
+/// Any system can queue shapes, they are cleared at the start of the next frame.
+/// Labels are only drawn once `font` is set.
+#[derive(Debug, Clone)]
+pub struct DebugDraw {
+    lines: Vec<DebugLine>,
+    labels: Vec<DebugLabel>,
+    pub font: Option<FontId>,
+    /// Height of the labels in pixels
+    pub label_size: f32,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self {
+            lines: vec![],
+            labels: vec![],
+            font: None,
+            label_size: 16.0,
+        }
+    }
+}
+
+impl DebugDraw {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_font(mut self, font: FontId) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    pub fn lines(&self) -> &[DebugLine] {
+        &self.lines
+    }
+
+    pub fn labels(&self) -> &[DebugLabel] {
+        &self.labels
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.labels.clear();
+    }
+
+    pub fn line(&mut self, start: Vec3, end: Vec3, color: Color) {
+        self.lines.push(DebugLine { start, end, color });
+    }
+
+    pub fn ray(&mut self, origin: Vec3, direction: Vec3, color: Color) {
+        self.line(origin, origin + direction, color);
+    }
+
+    /// A line with a head at `end` a fifth of its length
+    pub fn arrow(&mut self, start: Vec3, end: Vec3, color: Color) {
+        self.line(start, end, color);
+        let direction = end - start;
+        let length = direction.length();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let head = length * 0.2;
+        let back = end - direction / length * head;
+        let (a, b) = (direction / length).any_orthonormal_pair();
+        for side in [a, -a, b, -b] {
+            self.line(end, back + side * head * 0.5, color);
+        }
+    }
+
+    /// The 12 edges of the box between `min` and `max`
+    pub fn aabb(&mut self, min: Vec3, max: Vec3, color: Color) {
+        let center = (min + max) / 2.0;
+        let transform = Mat4::from_translation(center) * Mat4::from_scale(max - min);
+        self.cuboid(transform, color);
+    }
+
+    /// A wireframe of the unit cube centered on the origin, moved by `transform`
+    pub fn cuboid(&mut self, transform: Mat4, color: Color) {
+        let corner = |index: usize| {
+            transform.transform_point3(Vec3::new(
+                if index & 1 == 0 { -0.5 } else { 0.5 },
+                if index & 2 == 0 { -0.5 } else { 0.5 },
+                if index & 4 == 0 { -0.5 } else { 0.5 },
+            ))
+        };
+        // Corners one bit apart share an edge
+        for index in 0..8 {
+            for bit in [1, 2, 4] {
+                if index & bit == 0 {
+                    self.line(corner(index), corner(index | bit), color);
+                }
+            }
+        }
+    }
+
+    /// A circle around `normal`
+    pub fn circle(&mut self, center: Vec3, normal: Vec3, radius: f32, color: Color) {
+        let (a, b) = normal.normalize_or_zero().any_orthonormal_pair();
+        let point = |segment: usize| {
+            let angle = segment as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + (a * angle.cos() + b * angle.sin()) * radius
+        };
+        for segment in 0..CIRCLE_SEGMENTS {
+            self.line(point(segment), point(segment + 1), color);
+        }
+    }
+
+    /// Three circles, one around each axis
+    pub fn sphere(&mut self, center: Vec3, radius: f32, color: Color) {
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            self.circle(center, axis, radius, color);
+        }
+    }
+
+    /// Red, green and blue arrows along the X, Y and Z axes of `transform`
+    pub fn axes(&mut self, transform: Mat4, length: f32) {
+        let origin = transform.transform_point3(Vec3::ZERO);
+        let axes = [
+            (Vec3::X, Color::RED),
+            (Vec3::Y, Color::GREEN),
+            (Vec3::Z, Color::BLUE),
+        ];
+        for (axis, color) in axes {
+            self.arrow(origin, transform.transform_point3(axis * length), color);
+        }
+    }
+
+    pub fn text(&mut self, position: Vec3, text: &str, color: Color) {
+        self.labels.push(DebugLabel {
+            position,
+            text: String::from(text),
+            color,
+        });
+    }
+
+    /// Clears the shapes of the previous frame
+    pub fn update_system(world: &World) {
+        if let Ok(mut debug) = world.try_get_resource_mut::<Self>() {
+            debug.clear();
+        }
+    }
+}
+
+impl Resource for DebugDraw {}
+
+/// Queues the face normals of every `Mesh` when `DebugRenderSettings::show_normals` is set
+pub fn draw_normals_system(world: &World) {
+    let Ok(settings) = world.try_get_resource::<RendererSettings>() else {
+        return;
+    };
+    let settings = &settings.debug;
+    if !settings.show_normals {
+        return;
+    }
+    let (Ok(meshes), Ok(mut debug)) = (
+        world.try_get_spawned::<Mesh>(),
+        world.try_get_resource_mut::<DebugDraw>(),
+    ) else {
+        return;
+    };
+
+    for mesh in meshes.iter() {
+        let positions = mesh
+            .vertices
+            .iter()
+            .map(|vertex| {
+                let [x, y, z, w] = vertex.position;
+                mesh.transform.transform_point3(Vec3::new(x, y, z) / w)
+            })
+            .collect::<Vec<_>>();
+        for triangle in positions.chunks_exact(3) {
+            let center = (triangle[0] + triangle[1] + triangle[2]) / 3.0;
+            let normal = face_normal(triangle[0], triangle[1], triangle[2]);
+            debug.ray(
+                center,
+                normal * settings.normal_length,
+                settings.normal_color,
+            );
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct LineVertex {
+    position: [f32; 3],
+    color: [f32; 4],
+}
+
+impl LineVertex {
+    const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+        wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x4];
+
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBUTES,
+        }
+    }
+}
+
+/// Draws the lines of `DebugDraw` over the scene, before post-processing
+pub struct DebugDrawPass {
+    pipeline: RenderPipeline,
+    camera_buffer: Buffer,
+    bind_group: BindGroup,
+    vertex_buffer: Option<Buffer>,
+    vertices: u32,
+}
+
+impl DebugDrawPass {
+    pub fn new(device: &Device) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("debug.wgsl"));
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Debug Camera Buffer"),
+            size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Debug Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Debug Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Debug Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Debug Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_debug",
+                buffers: &[LineVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_debug",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: PostProcessor::HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            camera_buffer,
+            bind_group,
+            vertex_buffer: None,
+            vertices: 0,
+        }
+    }
+}
+
+impl RenderPass for DebugDrawPass {
+    fn name(&self) -> &str {
+        "debug_draw"
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        builder.read(SCENE_COLOR).write(SCENE_COLOR);
+    }
+
+    fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        self.vertices = 0;
+        let Ok(debug) = world.try_get_resource::<DebugDraw>() else {
+            return;
+        };
+        if debug.lines().is_empty() {
+            return;
+        }
+
+        let vertices = debug
+            .lines()
+            .iter()
+            .flat_map(|line| {
+                let color = [
+                    line.color.r as f32,
+                    line.color.g as f32,
+                    line.color.b as f32,
+                    line.color.a as f32,
+                ];
+                [line.start, line.end].map(|position| LineVertex {
+                    position: position.to_array(),
+                    color,
+                })
+            })
+            .collect::<Vec<_>>();
+        let contents: &[u8] = bytemuck::cast_slice(&vertices);
+        write_buffer(
+            context.device,
+            context.queue,
+            &mut self.vertex_buffer,
+            "Debug Vertex Buffer",
+            wgpu::BufferUsages::VERTEX,
+            contents,
+        );
+
+        let aspect_ratio = context.size.width as f32 / context.size.height.max(1) as f32;
+        let view_projection = window_camera(world)
+            .map(|camera| camera.view_projection(aspect_ratio))
+            .unwrap_or(Mat4::IDENTITY);
+        context.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[CameraUniform::new(view_projection)]),
+        );
+        self.vertices = vertices.len() as u32;
+    }
+
+    fn render(
+        &mut self,
+        _context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    ) {
+        let (Some(view), Some(vertex_buffer)) = (
+            resources.texture_view(SCENE_COLOR),
+            self.vertex_buffer.as_ref(),
+        ) else {
+            return;
+        };
+        if self.vertices == 0 {
+            return;
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Debug Draw Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertices, 0..1);
+    }
+}
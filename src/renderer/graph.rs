@@ -0,0 +1,442 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use starry_ecs::World;
+use thiserror::Error;
+use wgpu::{
+    Buffer, BufferUsages, CommandEncoder, Device, QuerySet, Texture, TextureFormat, TextureUsages,
+    TextureView,
+};
+use winit::dpi::PhysicalSize;
+
+use crate::renderer::pass::{RenderContext, RenderPass};
+
+/// The surface texture of the current frame, always available to read and write
+pub const SWAPCHAIN: &str = "swapchain";
+/// The HDR texture the window's cameras draw into
+pub const SCENE_COLOR: &str = "scene_color";
+/// The depth texture the window's cameras draw with
+pub const SCENE_DEPTH: &str = "scene_depth";
+/// Stands for every texture in `RenderTargets`, written by the scene pass
+pub const RENDER_TARGETS: &str = "render_targets";
+/// Depth array with the cascades of the directional light followed by the spot lights
+pub const SHADOW_MAPS: &str = "shadow_maps";
+/// Uniform buffer with the lights of the frame and their shadow projections
+pub const LIGHTS: &str = "lights";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RenderGraphError {
+    #[error("Render pass `{pass}` reads `{slot}` but no pass writes it")]
+    MissingInput { pass: String, slot: String },
+    #[error("Slot `{0}` is created by more than one render pass")]
+    DuplicateSlot(String),
+    #[error("Render passes depend on each other in a cycle: {0:?}")]
+    Cycle(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlotSize {
+    /// Follows the size of the window
+    Window,
+    /// Follows the size of the window multiplied by a factor
+    Scaled(f32),
+    Fixed(u32, u32),
+}
+
+impl SlotSize {
+    pub fn resolve(&self, window: PhysicalSize<u32>) -> (u32, u32) {
+        let (width, height) = match *self {
+            SlotSize::Window => (window.width, window.height),
+            SlotSize::Scaled(factor) => (
+                (window.width as f32 * factor) as u32,
+                (window.height as f32 * factor) as u32,
+            ),
+            SlotSize::Fixed(width, height) => (width, height),
+        };
+        (width.max(1), height.max(1))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureSlot {
+    pub size: SlotSize,
+    pub format: TextureFormat,
+    pub usage: TextureUsages,
+    /// Textures with more than one layer are viewed as 2D arrays
+    pub layers: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferSlot {
+    pub size: u64,
+    pub usage: BufferUsages,
+}
+
+/// What a render pass reads, writes and needs the graph to allocate
+#[derive(Debug, Clone, Default)]
+pub struct PassBuilder {
+    reads: Vec<String>,
+    writes: Vec<String>,
+    textures: Vec<(String, TextureSlot)>,
+    buffers: Vec<(String, BufferSlot)>,
+}
+
+impl PassBuilder {
+    pub fn read(&mut self, slot: &str) -> &mut Self {
+        self.reads.push(String::from(slot));
+        self
+    }
+
+    /// Writing a slot the pass also reads loads its previous contents
+    pub fn write(&mut self, slot: &str) -> &mut Self {
+        self.writes.push(String::from(slot));
+        self
+    }
+
+    /// Asks the graph to allocate a texture, the pass creating it is its first writer
+    pub fn create_texture(&mut self, slot: &str, descriptor: TextureSlot) -> &mut Self {
+        self.textures.push((String::from(slot), descriptor));
+        self.write(slot)
+    }
+
+    /// Asks the graph to allocate a buffer, the pass creating it is its first writer
+    pub fn create_buffer(&mut self, slot: &str, descriptor: BufferSlot) -> &mut Self {
+        self.buffers.push((String::from(slot), descriptor));
+        self.write(slot)
+    }
+
+    fn reads_only(&self, slot: &str) -> bool {
+        self.reads.iter().any(|read| read == slot) && !self.writes(slot)
+    }
+
+    fn writes(&self, slot: &str) -> bool {
+        self.writes.iter().any(|write| write == slot)
+    }
+
+    fn creates(&self, slot: &str) -> bool {
+        self.textures
+            .iter()
+            .map(|(created, _)| created)
+            .chain(self.buffers.iter().map(|(created, _)| created))
+            .any(|created| created == slot)
+    }
+}
+
+/// Orders passes from what they declare in `setup`.
+///
+/// Every pass writing a slot runs before the passes only reading it. The pass
+/// creating a slot writes it first, the other writers keep the order they were added in.
+fn schedule(declarations: &[(String, PassBuilder)]) -> Result<Vec<usize>, RenderGraphError> {
+    let mut created = HashSet::new();
+    for (_, builder) in declarations {
+        let slots = builder
+            .textures
+            .iter()
+            .map(|(slot, _)| slot)
+            .chain(builder.buffers.iter().map(|(slot, _)| slot));
+        for slot in slots {
+            if !created.insert(slot) {
+                return Err(RenderGraphError::DuplicateSlot(slot.clone()));
+            }
+        }
+    }
+
+    let mut edges = vec![vec![]; declarations.len()];
+    let mut incoming = vec![0usize; declarations.len()];
+    let mut add_edge = |from: usize, to: usize| {
+        if !edges[from].contains(&to) {
+            edges[from].push(to);
+            incoming[to] += 1;
+        }
+    };
+
+    let mut slots = declarations
+        .iter()
+        .flat_map(|(_, builder)| builder.reads.iter().chain(builder.writes.iter()))
+        .collect::<Vec<_>>();
+    slots.sort();
+    slots.dedup();
+
+    for slot in slots {
+        let mut writers = declarations
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, builder))| builder.writes(slot))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        // The pass creating a slot always writes it first
+        writers.sort_by_key(|index| !declarations[*index].1.creates(slot));
+        let readers = declarations
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, builder))| builder.reads_only(slot))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        if writers.is_empty() && slot != SWAPCHAIN {
+            if let Some(&reader) = readers.first() {
+                return Err(RenderGraphError::MissingInput {
+                    pass: declarations[reader].0.clone(),
+                    slot: slot.clone(),
+                });
+            }
+        }
+
+        for pair in writers.windows(2) {
+            add_edge(pair[0], pair[1]);
+        }
+        for &writer in writers.iter() {
+            for &reader in readers.iter() {
+                add_edge(writer, reader);
+            }
+        }
+    }
+
+    // Kahn's algorithm, ties go to the pass added first
+    let mut ready = incoming
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count == 0)
+        .map(|(index, _)| Reverse(index))
+        .collect::<BinaryHeap<_>>();
+    let mut order = vec![];
+    while let Some(Reverse(index)) = ready.pop() {
+        order.push(index);
+        for &next in edges[index].iter() {
+            incoming[next] -= 1;
+            if incoming[next] == 0 {
+                ready.push(Reverse(next));
+            }
+        }
+    }
+
+    if order.len() != declarations.len() {
+        let stuck = (0..declarations.len())
+            .filter(|index| !order.contains(index))
+            .map(|index| declarations[index].0.clone())
+            .collect();
+        return Err(RenderGraphError::Cycle(stuck));
+    }
+
+    Ok(order)
+}
+
+struct GraphTexture {
+    texture: Texture,
+    view: TextureView,
+    size: (u32, u32),
+    descriptor: TextureSlot,
+}
+
+struct GraphBuffer {
+    buffer: Buffer,
+    descriptor: BufferSlot,
+}
+
+/// The slots of the graph for the frame being recorded
+pub struct RenderResources<'a> {
+    textures: &'a HashMap<String, GraphTexture>,
+    buffers: &'a HashMap<String, GraphBuffer>,
+    swapchain: &'a TextureView,
+}
+
+impl RenderResources<'_> {
+    pub fn texture_view(&self, slot: &str) -> Option<&TextureView> {
+        if slot == SWAPCHAIN {
+            return Some(self.swapchain);
+        }
+        self.textures.get(slot).map(|texture| &texture.view)
+    }
+
+    pub fn texture(&self, slot: &str) -> Option<&Texture> {
+        self.textures.get(slot).map(|texture| &texture.texture)
+    }
+
+    pub fn texture_size(&self, slot: &str) -> Option<(u32, u32)> {
+        self.textures.get(slot).map(|texture| texture.size)
+    }
+
+    pub fn buffer(&self, slot: &str) -> Option<&Buffer> {
+        self.buffers.get(slot).map(|buffer| &buffer.buffer)
+    }
+}
+
+/// Render passes along with the textures and buffers they share
+#[derive(Default)]
+pub struct RenderGraph {
+    passes: Vec<Box<dyn RenderPass>>,
+    declarations: Vec<(String, PassBuilder)>,
+    order: Vec<usize>,
+    compiled: bool,
+    textures: HashMap<String, GraphTexture>,
+    buffers: HashMap<String, GraphBuffer>,
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_pass(&mut self, pass: Box<dyn RenderPass>) {
+        self.passes.push(pass);
+        self.compiled = false;
+    }
+
+    /// Collects every pass's declarations and orders the passes.
+    ///
+    /// On an error the graph keeps its last valid order, so passes added since don't run.
+    pub fn compile(&mut self) -> Result<(), RenderGraphError> {
+        self.compiled = true;
+        let declarations = self
+            .passes
+            .iter()
+            .map(|pass| {
+                let mut builder = PassBuilder::default();
+                pass.setup(&mut builder);
+                (String::from(pass.name()), builder)
+            })
+            .collect::<Vec<_>>();
+        self.order = schedule(&declarations)?;
+        self.declarations = declarations;
+        Ok(())
+    }
+
+    /// Names of the passes in the order they run
+    pub fn order(&self) -> Vec<&str> {
+        self.order
+            .iter()
+            .map(|index| self.declarations[*index].0.as_str())
+            .collect()
+    }
+
+    /// Allocates new slots, reallocates the ones whose size or descriptor changed and frees
+    /// the ones no pass creates anymore
+    fn allocate(&mut self, device: &Device, window: PhysicalSize<u32>) {
+        let created = self
+            .declarations
+            .iter()
+            .flat_map(|(_, builder)| builder.textures.iter().map(|(slot, _)| slot))
+            .collect::<HashSet<_>>();
+        self.textures.retain(|slot, _| created.contains(slot));
+        let created = self
+            .declarations
+            .iter()
+            .flat_map(|(_, builder)| builder.buffers.iter().map(|(slot, _)| slot))
+            .collect::<HashSet<_>>();
+        self.buffers.retain(|slot, _| created.contains(slot));
+
+        for (_, builder) in self.declarations.iter() {
+            for (slot, descriptor) in builder.textures.iter() {
+                let size = descriptor.size.resolve(window);
+                if self.textures.get(slot).is_some_and(|texture| {
+                    texture.size == size && texture.descriptor == *descriptor
+                }) {
+                    continue;
+                }
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some(slot),
+                    size: wgpu::Extent3d {
+                        width: size.0,
+                        height: size.1,
+                        depth_or_array_layers: descriptor.layers.max(1),
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: descriptor.format,
+                    usage: descriptor.usage,
+                    view_formats: &[],
+                });
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+                self.textures.insert(
+                    slot.clone(),
+                    GraphTexture {
+                        texture,
+                        view,
+                        size,
+                        descriptor: descriptor.clone(),
+                    },
+                );
+            }
+            for (slot, descriptor) in builder.buffers.iter() {
+                if self
+                    .buffers
+                    .get(slot)
+                    .is_some_and(|buffer| buffer.descriptor == *descriptor)
+                {
+                    continue;
+                }
+                let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some(slot),
+                    size: descriptor.size,
+                    usage: descriptor.usage,
+                    mapped_at_creation: false,
+                });
+                self.buffers.insert(
+                    slot.clone(),
+                    GraphBuffer {
+                        buffer,
+                        descriptor: descriptor.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn ensure_compiled(&mut self, context: &RenderContext<'_>) {
+        if self.compiled {
+            return;
+        }
+        if let Err(error) = self.compile() {
+            tracing::error!("Kept the last valid render graph: {}", error);
+        }
+        self.allocate(context.device, context.size);
+    }
+
+    pub fn init(&mut self, context: &RenderContext<'_>) {
+        for pass in self.passes.iter_mut() {
+            pass.init(context);
+        }
+        self.ensure_compiled(context);
+    }
+
+    pub fn resize(&mut self, context: &RenderContext<'_>) {
+        self.allocate(context.device, context.size);
+        for pass in self.passes.iter_mut() {
+            pass.resize(context);
+        }
+    }
+
+    pub fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        self.ensure_compiled(context);
+        for index in self.order.iter() {
+            self.passes[*index].prepare(context, world);
+        }
+    }
+
+    /// Records every pass, writing timestamps `2 * i` and `2 * i + 1` around the `i`th one
+    /// when a query set is given
+    pub fn execute(
+        &mut self,
+        context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        swapchain: &TextureView,
+        timestamps: Option<&QuerySet>,
+    ) {
+        let resources = RenderResources {
+            textures: &self.textures,
+            buffers: &self.buffers,
+            swapchain,
+        };
+        for (position, index) in self.order.iter().enumerate() {
+            let query = position as u32 * 2;
+            if let Some(timestamps) = timestamps {
+                encoder.write_timestamp(timestamps, query);
+            }
+            self.passes[*index].render(context, encoder, &resources);
+            if let Some(timestamps) = timestamps {
+                encoder.write_timestamp(timestamps, query + 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use starry_ecs::resources::Resource;
+use starry_ecs::World;
+use wgpu::{CommandEncoder, LoadOp, Operations, RenderPassColorAttachment};
+use winit::event::WindowEvent;
+use winit::window::Window;
+
+use crate::plugin::Plugin;
+use crate::renderer::graph::{PassBuilder, RenderResources, SWAPCHAIN};
+use crate::renderer::pass::{RenderContext, RenderPass};
+use crate::StarstruckEngine;
+
+/// The egui context systems build their panels with.
+///
+/// A frame starts before the systems run and ends when the renderer draws it,
+/// so any system can call `gui.context()` and show windows.
+#[derive(Default)]
+pub struct Gui {
+    context: egui::Context,
+    // Created with the first frame, once there is a window
+    state: Option<egui_winit::State>,
+    output: Option<egui::FullOutput>,
+}
+
+impl std::fmt::Debug for Gui {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fmt.debug_struct("Gui")
+            .field("context", &self.context)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Gui {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn context(&self) -> &egui::Context {
+        &self.context
+    }
+
+    /// Whether the pointer is over a panel, so the game shouldn't react to it
+    pub fn wants_pointer_input(&self) -> bool {
+        self.context.wants_pointer_input()
+    }
+
+    pub fn wants_keyboard_input(&self) -> bool {
+        self.context.wants_keyboard_input()
+    }
+
+    /// Returns true when the event was used by a panel, window event handlers still get it
+    pub fn on_window_event(&mut self, event: &WindowEvent) -> bool {
+        match self.state.as_mut() {
+            Some(state) => state.on_event(&self.context, event).consumed,
+            None => false,
+        }
+    }
+
+    pub fn begin_frame(&mut self, window: &Window) {
+        let state = self.state.get_or_insert_with(|| {
+            let mut state = egui_winit::State::new(window);
+            state.set_pixels_per_point(egui_winit::native_pixels_per_point(window));
+            state
+        });
+        let input = state.take_egui_input(window);
+        self.context.begin_frame(input);
+    }
+
+    /// Ends the frame, the output is taken by the pass drawing it
+    pub fn end_frame(&mut self) {
+        self.output = Some(self.context.end_frame());
+    }
+
+    pub(crate) fn take_output(&mut self) -> Option<egui::FullOutput> {
+        self.output.take()
+    }
+
+    pub(crate) fn pixels_per_point(&self) -> f32 {
+        self.state
+            .as_ref()
+            .map_or(1.0, |state| state.pixels_per_point())
+    }
+}
+
+impl Resource for Gui {}
+
+/// Draws the panels of `Gui` over everything else
+#[derive(Default)]
+pub struct GuiPass {
+    renderer: Option<egui_wgpu::Renderer>,
+    primitives: Vec<egui::ClippedPrimitive>,
+    screen: Option<egui_wgpu::renderer::ScreenDescriptor>,
+    freed: Vec<egui::TextureId>,
+}
+
+impl GuiPass {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RenderPass for GuiPass {
+    fn name(&self) -> &str {
+        "gui"
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        builder.read(SWAPCHAIN).write(SWAPCHAIN);
+    }
+
+    fn init(&mut self, context: &RenderContext<'_>) {
+        self.renderer = Some(egui_wgpu::Renderer::new(
+            context.device,
+            context.format,
+            None,
+            1,
+        ));
+    }
+
+    fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        self.primitives.clear();
+        let Some(renderer) = self.renderer.as_mut() else {
+            return;
+        };
+        for id in self.freed.drain(..) {
+            renderer.free_texture(&id);
+        }
+        let Ok(mut gui) = world.try_get_resource_mut::<Gui>() else {
+            return;
+        };
+        gui.end_frame();
+        let Some(output) = gui.take_output() else {
+            return;
+        };
+
+        for (id, delta) in output.textures_delta.set.iter() {
+            renderer.update_texture(context.device, context.queue, *id, delta);
+        }
+        // Textures are freed once the frame using them was drawn
+        self.freed = output.textures_delta.free;
+        self.primitives = gui.context().tessellate(output.shapes);
+        self.screen = Some(egui_wgpu::renderer::ScreenDescriptor {
+            size_in_pixels: [context.size.width, context.size.height],
+            pixels_per_point: gui.pixels_per_point(),
+        });
+    }
+
+    fn render(
+        &mut self,
+        context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    ) {
+        let (Some(renderer), Some(screen), Some(view)) = (
+            self.renderer.as_mut(),
+            self.screen.as_ref(),
+            resources.texture_view(SWAPCHAIN),
+        ) else {
+            return;
+        };
+        if self.primitives.is_empty() {
+            return;
+        }
+
+        renderer.update_buffers(
+            context.device,
+            context.queue,
+            encoder,
+            &self.primitives,
+            screen,
+        );
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Gui Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        renderer.render(&mut render_pass, &self.primitives, screen);
+    }
+}
+
+/// Lets systems draw egui panels through the `Gui` resource
+#[derive(Debug, Clone, Default)]
+pub struct GuiPlugin;
+
+impl Plugin for GuiPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        engine
+            .add_resource(Gui::new())
+            .add_render_pass(GuiPass::new())
+    }
+}
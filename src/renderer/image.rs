@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use starry_ecs::resources::Resource;
+use thiserror::Error;
+use wgpu::util::DeviceExt;
+use wgpu::{Device, FilterMode, Queue, Sampler, Texture, TextureFormat, TextureView};
+
+#[derive(Error, Debug)]
+pub enum ImageError {
+    #[error("Failed to read image: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to decode PNG: {0}")]
+    Decode(#[from] png::DecodingError),
+    #[error("Image of {width}x{height} pixels needs {expected} bytes, got {actual}")]
+    Size {
+        width: u32,
+        height: u32,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+/// RGBA pixels with 8 bits per channel, in sRGB
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    pub filter: FilterMode,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, data: Vec<u8>) -> Result<Self, ImageError> {
+        let expected = width as usize * height as usize * 4;
+        if data.len() != expected {
+            return Err(ImageError::Size {
+                width,
+                height,
+                expected,
+                actual: data.len(),
+            });
+        }
+        Ok(Self {
+            width,
+            height,
+            data,
+            filter: FilterMode::Linear,
+        })
+    }
+
+    pub fn filled(width: u32, height: u32, rgba: [u8; 4]) -> Self {
+        Self {
+            width,
+            height,
+            data: rgba.repeat(width as usize * height as usize),
+            filter: FilterMode::Linear,
+        }
+    }
+
+    pub fn from_png(bytes: &[u8]) -> Result<Self, ImageError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let pixels = &buffer[..info.buffer_size()];
+
+        let data = match info.color_type {
+            png::ColorType::Rgba => pixels.to_vec(),
+            png::ColorType::Rgb => pixels
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => pixels
+                .chunks_exact(2)
+                .flat_map(|gray| [gray[0], gray[0], gray[0], gray[1]])
+                .collect(),
+            // Indexed images are expanded by `normalize_to_color8`
+            png::ColorType::Grayscale | png::ColorType::Indexed => pixels
+                .iter()
+                .flat_map(|gray| [*gray, *gray, *gray, 255])
+                .collect(),
+        };
+        Self::new(info.width, info.height, data)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ImageError> {
+        Self::from_png(&std::fs::read(path)?)
+    }
+
+    /// Nearest filtering keeps pixel art sharp
+    pub fn with_filter(mut self, filter: FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ImageId(u32);
+
+/// The images the renderer should keep on the GPU.
+///
+/// Like `RenderTargets`, images can be added, changed and removed from any
+/// system and the renderer uploads them before the next frame.
+#[derive(Debug, Default, Clone)]
+pub struct Images {
+    next_id: u32,
+    // Each image is stored with the number of times it was borrowed mutably
+    images: HashMap<ImageId, (Image, u32)>,
+}
+
+impl Images {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, image: Image) -> ImageId {
+        let id = ImageId(self.next_id);
+        self.next_id += 1;
+        self.images.insert(id, (image, 0));
+        id
+    }
+
+    pub fn get(&self, id: ImageId) -> Option<&Image> {
+        self.images.get(&id).map(|(image, _)| image)
+    }
+
+    /// The image is uploaded again before the next frame
+    pub fn get_mut(&mut self, id: ImageId) -> Option<&mut Image> {
+        self.images.get_mut(&id).map(|(image, version)| {
+            *version += 1;
+            image
+        })
+    }
+
+    pub fn remove(&mut self, id: ImageId) -> Option<Image> {
+        self.images.remove(&id).map(|(image, _)| image)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ImageId, &Image)> {
+        self.images.iter().map(|(id, (image, _))| (*id, image))
+    }
+
+    pub(crate) fn version(&self, id: ImageId) -> Option<u32> {
+        self.images.get(&id).map(|(_, version)| *version)
+    }
+}
+
+impl Resource for Images {}
+
+/// An `Image` uploaded to a texture
+#[derive(Debug)]
+pub struct GpuImage {
+    texture: Texture,
+    view: TextureView,
+    sampler: Sampler,
+    size: (u32, u32),
+    pub(crate) version: u32,
+}
+
+impl GpuImage {
+    pub const FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+    pub fn new(device: &Device, queue: &Queue, image: &Image, version: u32) -> Self {
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Image"),
+                size: wgpu::Extent3d {
+                    width: image.width.max(1),
+                    height: image.height.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: Self::FORMAT,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            &image.data,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Image Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: image.filter,
+            min_filter: image.filter,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+            size: image.size(),
+            version,
+        }
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    pub fn sampler(&self) -> &Sampler {
+        &self.sampler
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+}
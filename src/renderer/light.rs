@@ -0,0 +1,98 @@
+use glam::{Mat4, Vec3};
+use starry_ecs::component::Component;
+use starry_ecs::resources::Resource;
+use wgpu::Color;
+
+/// Light coming from infinitely far away along `direction`, like the sun.
+///
+/// Only the first directional light of the world is used.
+#[derive(Debug, Clone)]
+pub struct DirectionalLight {
+    pub direction: Vec3,
+    pub color: Color,
+    pub illuminance: f32,
+    pub cast_shadows: bool,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::new(-0.4, -1.0, -0.3),
+            color: Color::WHITE,
+            illuminance: 1.0,
+            cast_shadows: true,
+        }
+    }
+}
+
+impl Component for DirectionalLight {}
+
+/// A cone of light starting at `position`, fading out between the inner and outer angles
+#[derive(Debug, Clone)]
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    pub color: Color,
+    pub intensity: f32,
+    pub range: f32,
+    pub inner_angle_degrees: f32,
+    pub outer_angle_degrees: f32,
+    pub cast_shadows: bool,
+}
+
+impl Default for SpotLight {
+    fn default() -> Self {
+        Self {
+            position: Vec3::new(0.0, 2.0, 0.0),
+            direction: Vec3::NEG_Y,
+            color: Color::WHITE,
+            intensity: 1.0,
+            range: 10.0,
+            inner_angle_degrees: 20.0,
+            outer_angle_degrees: 30.0,
+            cast_shadows: true,
+        }
+    }
+}
+
+impl SpotLight {
+    /// The projection the spot light's shadow map is rendered with
+    pub fn view_projection(&self) -> Mat4 {
+        let direction = self.direction.normalize_or_zero();
+        let up = if direction.y.abs() > 0.99 {
+            Vec3::Z
+        } else {
+            Vec3::Y
+        };
+        let near = (self.range * 0.001).max(0.01);
+        let projection = Mat4::perspective_rh(
+            (self.outer_angle_degrees * 2.0).to_radians(),
+            1.0,
+            near,
+            self.range,
+        );
+        projection * Mat4::look_at_rh(self.position, self.position + direction, up)
+    }
+}
+
+impl Component for SpotLight {}
+
+/// Light reaching every surface, including the ones in shadow.
+///
+/// The scene is drawn unlit until the world contains a light.
+#[derive(Debug, Clone)]
+pub struct AmbientLight {
+    pub color: Color,
+    pub brightness: f32,
+}
+
+impl Default for AmbientLight {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            brightness: 0.1,
+        }
+    }
+}
+
+impl Resource for AmbientLight {}
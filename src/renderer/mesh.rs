@@ -0,0 +1,236 @@
+use std::ops::Range;
+
+use glam::{Mat3, Mat4};
+use starry_ecs::component::Component;
+use wgpu::{BindGroup, BindGroupLayout, Buffer, Device, Queue};
+
+use crate::assets::Handle;
+use crate::renderer::image::ImageId;
+use crate::shapes::Vertex;
+
+/// Vertices drawn by the scene pass, placed in the world by `transform`
+#[derive(Debug, Clone)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub transform: Mat4,
+    /// Drawn into the shadow maps of every light
+    pub cast_shadows: bool,
+    /// Darkened where a shadow-casting mesh is between it and a light
+    pub receive_shadows: bool,
+    /// Multiplied with the vertex colors at their texture coordinates
+    pub texture: Option<ImageId>,
+    /// The asset whose vertices the mesh gets once it is loaded, and again when it is reloaded
+    pub asset: Option<Handle<Mesh>>,
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vertex>) -> Self {
+        Self {
+            vertices,
+            transform: Mat4::IDENTITY,
+            cast_shadows: true,
+            receive_shadows: true,
+            texture: None,
+            asset: None,
+        }
+    }
+
+    /// An empty mesh filled in by the `AssetPlugin`
+    pub fn from_asset(asset: Handle<Mesh>) -> Self {
+        Self {
+            asset: Some(asset),
+            ..Self::new(vec![])
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Mat4) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_cast_shadows(mut self, cast_shadows: bool) -> Self {
+        self.cast_shadows = cast_shadows;
+        self
+    }
+
+    pub fn with_receive_shadows(mut self, receive_shadows: bool) -> Self {
+        self.receive_shadows = receive_shadows;
+        self
+    }
+
+    pub fn with_texture(mut self, texture: ImageId) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+
+    /// The inverse transpose of the transform, which keeps normals perpendicular to their
+    /// surface when the mesh is scaled unevenly
+    pub fn normal_matrix(&self) -> Mat4 {
+        let linear = Mat3::from_mat4(self.transform);
+        if linear.determinant() == 0.0 {
+            return Mat4::from_mat3(linear);
+        }
+        Mat4::from_mat3(linear.inverse().transpose())
+    }
+}
+
+impl Component for Mesh {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct MeshUniform {
+    model: [[f32; 4]; 4],
+    normal: [[f32; 4]; 4],
+    receive_shadows: u32,
+    _padding: [u32; 3],
+}
+
+/// The meshes of a frame packed into one vertex buffer,
+/// each with its own slice of a uniform buffer bound at a dynamic offset
+pub(crate) struct MeshBatch {
+    vertex_buffer: Option<Buffer>,
+    vertex_capacity: usize,
+    uniforms: Option<(Buffer, BindGroup)>,
+    uniform_capacity: usize,
+    uniform_stride: u64,
+    ranges: Vec<Range<u32>>,
+    textures: Vec<Option<ImageId>>,
+}
+
+impl MeshBatch {
+    pub(crate) fn bind_group_layout(device: &Device) -> BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Mesh Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(
+                        std::mem::size_of::<MeshUniform>() as u64
+                    ),
+                },
+                count: None,
+            }],
+        })
+    }
+
+    pub(crate) fn new(device: &Device) -> Self {
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let size = std::mem::size_of::<MeshUniform>() as u64;
+        Self {
+            vertex_buffer: None,
+            vertex_capacity: 0,
+            uniforms: None,
+            uniform_capacity: 0,
+            uniform_stride: size.div_ceil(alignment) * alignment,
+            ranges: vec![],
+            textures: vec![],
+        }
+    }
+
+    pub(crate) fn prepare<'m>(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        layout: &BindGroupLayout,
+        meshes: impl Iterator<Item = &'m Mesh>,
+    ) {
+        let mut vertices = vec![];
+        let mut uniforms = vec![];
+        self.ranges.clear();
+        self.textures.clear();
+        for mesh in meshes {
+            if mesh.vertices.is_empty() {
+                continue;
+            }
+            let start = vertices.len() as u32;
+            vertices.extend_from_slice(&mesh.vertices);
+            self.ranges.push(start..vertices.len() as u32);
+            self.textures.push(mesh.texture);
+
+            let uniform = MeshUniform {
+                model: mesh.transform.to_cols_array_2d(),
+                normal: mesh.normal_matrix().to_cols_array_2d(),
+                receive_shadows: mesh.receive_shadows as u32,
+                _padding: [0; 3],
+            };
+            uniforms.extend_from_slice(bytemuck::bytes_of(&uniform));
+            uniforms.resize(self.ranges.len() * self.uniform_stride as usize, 0);
+        }
+        if self.ranges.is_empty() {
+            return;
+        }
+
+        if vertices.len() > self.vertex_capacity {
+            self.vertex_capacity = vertices.len().next_power_of_two();
+            self.vertex_buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Mesh Vertex Buffer"),
+                size: (self.vertex_capacity * std::mem::size_of::<Vertex>()) as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }));
+        }
+        if let Some(buffer) = self.vertex_buffer.as_ref() {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&vertices));
+        }
+
+        if self.ranges.len() > self.uniform_capacity {
+            self.uniform_capacity = self.ranges.len().next_power_of_two();
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Mesh Uniform Buffer"),
+                size: self.uniform_capacity as u64 * self.uniform_stride,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mesh Bind Group"),
+                layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<MeshUniform>() as u64),
+                    }),
+                }],
+            });
+            self.uniforms = Some((buffer, bind_group));
+        }
+        if let Some((buffer, _)) = self.uniforms.as_ref() {
+            queue.write_buffer(buffer, 0, &uniforms);
+        }
+    }
+
+    /// The textures of the prepared meshes
+    pub(crate) fn textures(&self) -> impl Iterator<Item = ImageId> + '_ {
+        self.textures.iter().flatten().copied()
+    }
+
+    /// Draws every mesh, binding its uniform at `group`
+    pub(crate) fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, group: u32) {
+        self.draw_with(render_pass, group, |_, _| {});
+    }
+
+    /// Draws every mesh, letting `bind` set the other bind groups of a mesh given its texture
+    pub(crate) fn draw_with<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        group: u32,
+        mut bind: impl FnMut(&mut wgpu::RenderPass<'a>, Option<ImageId>),
+    ) {
+        let (Some(vertex_buffer), Some((_, bind_group))) =
+            (self.vertex_buffer.as_ref(), self.uniforms.as_ref())
+        else {
+            return;
+        };
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        for (index, (range, texture)) in self.ranges.iter().zip(self.textures.iter()).enumerate() {
+            let offset = (index as u64 * self.uniform_stride) as u32;
+            render_pass.set_bind_group(group, bind_group, &[offset]);
+            bind(render_pass, *texture);
+            render_pass.draw(range.clone(), 0..1);
+        }
+    }
+}
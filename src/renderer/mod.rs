@@ -0,0 +1,445 @@
+pub mod animation;
+pub mod atlas;
+pub mod camera;
+pub mod debug;
+pub mod graph;
+pub mod gui;
+pub mod image;
+pub mod light;
+pub mod mesh;
+pub mod pass;
+pub mod post;
+pub mod scene;
+pub mod shadow;
+pub mod sprite;
+pub mod target;
+pub mod text;
+pub(crate) mod timer;
+
+pub use crate::renderer::animation::{
+    AnimatedSprite, AnimationEvent, AnimationEventKind, AnimationFrame, SpriteAnimation,
+    SpriteAnimationPlugin,
+};
+pub use crate::renderer::atlas::{
+    AtlasError, AtlasTag, PlaybackMode, TextureAtlas, TextureAtlasBuilder,
+};
+pub use crate::renderer::camera::{Camera, CameraTarget, Projection};
+pub use crate::renderer::debug::{CullMode, DebugDraw, DebugLabel, DebugLine, DebugRenderSettings};
+pub use crate::renderer::graph::{RenderGraph, RenderGraphError, RenderResources};
+pub use crate::renderer::gui::{Gui, GuiPlugin};
+pub use crate::renderer::image::{GpuImage, Image, ImageError, ImageId, Images};
+pub use crate::renderer::light::{AmbientLight, DirectionalLight, SpotLight};
+pub use crate::renderer::mesh::Mesh;
+pub use crate::renderer::pass::{RenderContext, RenderPass};
+pub use crate::renderer::post::{
+    PostEffect, PostEffectError, PostProcessing, PostProcessor, Tonemapper,
+};
+pub use crate::renderer::shadow::ShadowSettings;
+pub use crate::renderer::sprite::{Anchor, Rect, Sprite, SpriteSettings};
+pub use crate::renderer::target::{
+    RenderTarget, RenderTargetDescriptor, RenderTargetId, RenderTargets,
+};
+pub use crate::renderer::text::{
+    Font, FontError, FontId, Fonts, ScreenText, TextAlignment, TextStyle, WorldText,
+};
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::diagnostics::Profiler;
+use crate::helpers::rgba;
+use crate::plugin::{EngineOrdering, Plugin};
+use crate::renderer::debug::{draw_normals_system, DebugDrawPass};
+use crate::renderer::post::PostProcessPass;
+use crate::renderer::scene::ScenePass;
+use crate::renderer::shadow::ShadowPass;
+use crate::renderer::sprite::SpritePass;
+use crate::renderer::text::{TextPass, TextSpace};
+use crate::renderer::timer::GpuTimer;
+use crate::ui::UiScale;
+use crate::StarstruckEngine;
+
+use starry_ecs::resources::Resource;
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::World;
+use wgpu::{
+    Backends, Color, CommandEncoderDescriptor, Device, DeviceDescriptor, Instance,
+    InstanceDescriptor, Queue, Surface, SurfaceConfiguration, SurfaceError, TextureUsages,
+    TextureViewDescriptor,
+};
+use winit::{dpi::PhysicalSize, event::WindowEvent, window::Window};
+
+#[derive(Debug, Clone)]
+pub struct RendererSettings {
+    pub clear_color: Color,
+    pub shadows: ShadowSettings,
+    pub debug: DebugRenderSettings,
+    pub sprites: SpriteSettings,
+}
+
+impl Default for RendererSettings {
+    fn default() -> Self {
+        let (r, g, b, a) = rgba(255, 255, 255, 255);
+        Self {
+            clear_color: Color {
+                r: r.into(),
+                g: g.into(),
+                b: b.into(),
+                a: a.into(),
+            },
+            shadows: ShadowSettings::default(),
+            debug: DebugRenderSettings::default(),
+            sprites: SpriteSettings::default(),
+        }
+    }
+}
+
+impl Resource for RendererSettings {}
+
+/// Opens the window's surface and draws the scene every frame.
+///
+/// Without this plugin the engine still runs its systems but never renders.
+#[derive(Debug, Clone, Default)]
+pub struct RendererPlugin {
+    pub settings: RendererSettings,
+}
+
+impl Plugin for RendererPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        engine
+            .add_resource(self.settings.clone())
+            .add_resource(RenderTargets::new())
+            .add_resource(Images::new())
+            .add_resource(Fonts::new())
+            .add_resource(PostProcessing::new())
+            .add_resource(AmbientLight::default())
+            .add_resource(DebugDraw::new())
+            .add_system(EngineOrdering::First, DebugDraw::update_system)
+            .add_system(DefaultOrdering::PostRun, draw_normals_system)
+    }
+}
+
+pub struct Renderer {
+    pub(crate) surface: Surface,
+    pub(crate) device: Device,
+    pub(crate) queue: Queue,
+    pub(crate) surface_configuration: SurfaceConfiguration,
+    pub(crate) size: PhysicalSize<u32>,
+    pub(crate) render_targets: HashMap<RenderTargetId, RenderTarget>,
+    pub(crate) images: HashMap<ImageId, GpuImage>,
+    // The scene, post-processing and every pass added by plugins
+    pub(crate) graph: RenderGraph,
+    // Only when the adapter supports timestamp queries
+    timer: Option<GpuTimer>,
+
+    // Window must be dropped after the surface
+    pub(crate) window: Window,
+}
+
+impl Renderer {
+    pub async fn new(
+        window: Window,
+        settings: RendererSettings,
+        passes: Vec<Box<dyn RenderPass>>,
+    ) -> Self {
+        let size = window.inner_size();
+
+        let instance = Instance::new(InstanceDescriptor {
+            backends: Backends::all(),
+            ..Default::default()
+        });
+
+        let surface = unsafe { instance.create_surface(&window).unwrap() };
+
+        let adapter = instance
+            .enumerate_adapters(wgpu::Backends::all())
+            .find(|adapter| {
+                // Check if this adapter supports our surface
+                adapter.is_surface_supported(&surface)
+            })
+            .unwrap();
+        let info = adapter.get_info();
+        tracing::info!(
+            backend = ?info.backend,
+            device_type = ?info.device_type,
+            "Using adapter {}",
+            info.name
+        );
+
+        let (device, queue) = adapter
+            .request_device(
+                &DeviceDescriptor {
+                    // Wireframes fall back to a shader when lines can't be rasterized
+                    // and passes are only timed on the GPU when timestamps are supported
+                    features: adapter.features()
+                        & (wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::TIMESTAMP_QUERY),
+                    limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        tracing::debug!(features = ?device.features(), "Created the device");
+
+        let surface_caps = surface.get_capabilities(&adapter);
+
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .unwrap_or(surface_caps.formats[0]);
+        let config = SurfaceConfiguration {
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode: surface_caps.present_modes[0],
+            alpha_mode: surface_caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(&device, &config);
+
+        let render_targets = HashMap::new();
+        let images = HashMap::new();
+        let context = RenderContext {
+            device: &device,
+            queue: &queue,
+            format: config.format,
+            size,
+            render_targets: &render_targets,
+            images: &images,
+        };
+        let mut graph = RenderGraph::new();
+        graph.add_pass(Box::new(ShadowPass::new(&device, &settings.shadows)));
+        graph.add_pass(Box::new(ScenePass::new(&device, settings.clear_color)));
+        graph.add_pass(Box::new(SpritePass::new(&device)));
+        graph.add_pass(Box::new(TextPass::new(
+            &device,
+            TextSpace::World,
+            config.format,
+        )));
+        graph.add_pass(Box::new(DebugDrawPass::new(&device)));
+        graph.add_pass(Box::new(PostProcessPass::new(&context)));
+        graph.add_pass(Box::new(TextPass::new(
+            &device,
+            TextSpace::Screen,
+            config.format,
+        )));
+        for pass in passes {
+            graph.add_pass(pass);
+        }
+        graph.init(&context);
+        let timer = GpuTimer::new(&device, &queue, graph.order().len() as u32);
+
+        Self {
+            surface,
+            device,
+            queue,
+            surface_configuration: config,
+            size,
+            render_targets,
+            images,
+            graph,
+            timer,
+            window,
+        }
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Returns true when the event was used by the renderer, like a click on a `Gui` panel.
+    /// The game still gets the event and can check `Gui::wants_pointer_input` itself.
+    pub fn input(&mut self, world: &World, event: &WindowEvent) -> bool {
+        world
+            .try_get_resource_mut::<Gui>()
+            .is_ok_and(|mut gui| gui.on_window_event(event))
+    }
+
+    /// Called before the systems of a frame run
+    pub fn begin_frame(&mut self, world: &World) {
+        if let Ok(mut gui) = world.try_get_resource_mut::<Gui>() {
+            gui.begin_frame(&self.window);
+        }
+        if let Ok(mut scale) = world.try_get_resource_mut::<UiScale>() {
+            scale.set_window(
+                (self.size.width, self.size.height),
+                self.window.scale_factor(),
+            );
+        }
+    }
+
+    pub fn context(&self) -> RenderContext<'_> {
+        RenderContext {
+            device: &self.device,
+            queue: &self.queue,
+            format: self.surface_configuration.format,
+            size: self.size,
+            render_targets: &self.render_targets,
+            images: &self.images,
+        }
+    }
+
+    pub fn render_target(&self, id: RenderTargetId) -> Option<&RenderTarget> {
+        self.render_targets.get(&id)
+    }
+
+    pub fn graph(&self) -> &RenderGraph {
+        &self.graph
+    }
+
+    /// Adds a pass once the renderer is running, the graph is recompiled before the next frame
+    pub fn add_pass(&mut self, mut pass: Box<dyn RenderPass>) {
+        pass.init(&self.context());
+        self.graph.add_pass(pass);
+    }
+
+    /// Creates, recreates and drops textures to match the `RenderTargets` resource
+    fn sync_render_targets(&mut self, world: &World) {
+        let Ok(targets) = world.try_get_resource::<RenderTargets>() else {
+            self.render_targets.clear();
+            return;
+        };
+
+        self.render_targets
+            .retain(|id, _| targets.get(*id).is_some());
+        for (id, descriptor) in targets.iter() {
+            let outdated = self
+                .render_targets
+                .get(&id)
+                .is_none_or(|target| target.descriptor() != descriptor);
+            if outdated {
+                self.render_targets
+                    .insert(id, RenderTarget::new(&self.device, descriptor));
+            }
+        }
+    }
+
+    /// Uploads new and changed images and drops the removed ones
+    fn sync_images(&mut self, world: &World) {
+        let Ok(images) = world.try_get_resource::<Images>() else {
+            self.images.clear();
+            return;
+        };
+
+        self.images.retain(|id, _| images.get(*id).is_some());
+        for (id, image) in images.iter() {
+            let version = images.version(id).unwrap_or_default();
+            let outdated = self
+                .images
+                .get(&id)
+                .is_none_or(|uploaded| uploaded.version != version);
+            if outdated {
+                self.images
+                    .insert(id, GpuImage::new(&self.device, &self.queue, image, version));
+            }
+        }
+    }
+
+    pub fn update(&mut self, world: &World) {
+        let start = Instant::now();
+        self.sync_render_targets(world);
+        self.sync_images(world);
+        let synced = Instant::now();
+
+        let mut graph = std::mem::take(&mut self.graph);
+        graph.prepare(&self.context(), world);
+        self.graph = graph;
+
+        if let Ok(mut profiler) = world.try_get_resource_mut::<Profiler>() {
+            profiler.record("upload", start, synced);
+            profiler.record("prepare", synced, Instant::now());
+        }
+    }
+
+    pub fn render(&mut self, world: &World) -> Result<(), SurfaceError> {
+        let mut profiler = world.try_get_resource_mut::<Profiler>().ok();
+        let profiling = profiler.as_ref().is_some_and(|profiler| profiler.enabled);
+        if let (Some(profiler), Some(timer)) = (profiler.as_mut(), self.timer.as_mut()) {
+            for (pass, start, duration) in timer.collect(&self.device).unwrap_or_default() {
+                profiler.record_gpu(&pass, start, duration);
+            }
+        }
+
+        let start = Instant::now();
+        let output = self.surface.get_current_texture()?;
+        let acquired = Instant::now();
+
+        let view = output
+            .texture
+            .create_view(&TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        let mut graph = std::mem::take(&mut self.graph);
+        let mut timer = self.timer.take();
+        let passes = graph.order().len() as u32;
+        let timestamps = match timer.as_mut() {
+            Some(timer) if profiling => timer.begin(&self.device, passes),
+            _ => None,
+        };
+        let timed = timestamps.is_some();
+        graph.execute(&self.context(), &mut encoder, &view, timestamps);
+        if let (true, Some(timer)) = (timed, timer.as_ref()) {
+            timer.resolve(&mut encoder, passes);
+        }
+        let encoded = Instant::now();
+
+        // submit will accept anything that implements IntoIter
+        self.queue.submit(std::iter::once(encoder.finish()));
+        let submitted = Instant::now();
+        if let (true, Some(timer)) = (timed, timer.as_mut()) {
+            let names = graph.order().into_iter().map(String::from).collect();
+            timer.submitted(names, submitted);
+        }
+        self.graph = graph;
+        self.timer = timer;
+        output.present();
+
+        if let Some(profiler) = profiler.as_mut() {
+            profiler.record("acquire", start, acquired);
+            profiler.record("encode", acquired, encoded);
+            profiler.record("submit", encoded, submitted);
+            profiler.record("present", submitted, Instant::now());
+        }
+
+        Ok(())
+    }
+
+    pub fn resize(&mut self, x: u32, y: u32) {
+        tracing::debug!(width = x, height = y, "Resizing the surface");
+        if x > 0 {
+            self.size = PhysicalSize {
+                width: x,
+                height: self.size.height,
+            };
+            self.surface_configuration.width = x;
+            self.surface
+                .configure(&self.device, &self.surface_configuration);
+        }
+        if y > 0 {
+            self.size = PhysicalSize {
+                width: self.size.width,
+                height: y,
+            };
+            self.surface_configuration.height = y;
+            self.surface
+                .configure(&self.device, &self.surface_configuration);
+        }
+
+        let mut graph = std::mem::take(&mut self.graph);
+        graph.resize(&self.context());
+        self.graph = graph;
+    }
+}
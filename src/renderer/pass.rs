@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use starry_ecs::World;
+use wgpu::{Buffer, CommandEncoder, Device, Queue, TextureFormat};
+use winit::dpi::PhysicalSize;
+
+use crate::renderer::graph::{PassBuilder, RenderResources, SWAPCHAIN};
+use crate::renderer::image::{GpuImage, ImageId};
+use crate::renderer::target::{RenderTarget, RenderTargetId};
+
+/// Grows a buffer to fit `contents` and writes them to it
+pub(crate) fn write_buffer(
+    device: &Device,
+    queue: &Queue,
+    buffer: &mut Option<Buffer>,
+    label: &str,
+    usage: wgpu::BufferUsages,
+    contents: &[u8],
+) {
+    let fits = buffer
+        .as_ref()
+        .is_some_and(|buffer| buffer.size() >= contents.len() as u64);
+    if !fits {
+        *buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: (contents.len() as u64).next_power_of_two(),
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }));
+    }
+    if let Some(buffer) = buffer.as_ref() {
+        queue.write_buffer(buffer, 0, contents);
+    }
+}
+
+/// Everything a render pass needs from the renderer
+pub struct RenderContext<'a> {
+    pub device: &'a Device,
+    pub queue: &'a Queue,
+    pub format: TextureFormat,
+    pub size: PhysicalSize<u32>,
+    pub render_targets: &'a HashMap<RenderTargetId, RenderTarget>,
+    pub images: &'a HashMap<ImageId, GpuImage>,
+}
+
+impl RenderContext<'_> {
+    pub fn render_target(&self, id: RenderTargetId) -> Option<&RenderTarget> {
+        self.render_targets.get(&id)
+    }
+
+    pub fn image(&self, id: ImageId) -> Option<&GpuImage> {
+        self.images.get(&id)
+    }
+}
+
+/// A node of the render graph.
+///
+/// Passes declare the slots they read and write in `setup` and the graph runs
+/// them in an order satisfying those dependencies. By default a pass draws on
+/// top of the window after the scene has been post-processed.
+pub trait RenderPass: 'static {
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Declares the slots the pass uses, called whenever the graph is compiled
+    fn setup(&self, builder: &mut PassBuilder) {
+        builder.read(SWAPCHAIN).write(SWAPCHAIN);
+    }
+
+    /// Called once the device exists, create pipelines and buffers here
+    fn init(&mut self, _context: &RenderContext<'_>) {}
+
+    fn resize(&mut self, _context: &RenderContext<'_>) {}
+
+    /// Called every frame before `render` with the current state of the world
+    fn prepare(&mut self, _context: &RenderContext<'_>, _world: &World) {}
+
+    fn render(
+        &mut self,
+        context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    );
+}
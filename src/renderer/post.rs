@@ -0,0 +1,626 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use starry_ecs::resources::Resource;
+use starry_ecs::World;
+use thiserror::Error;
+use wgpu::{
+    BindGroupLayout, Buffer, CommandEncoder, Device, LoadOp, Operations, PipelineLayout, Queue,
+    RenderPassColorAttachment, RenderPipeline, Sampler, Texture, TextureFormat, TextureView,
+};
+
+use crate::assets::{Handle, Shader};
+use crate::renderer::graph::{PassBuilder, RenderResources, SCENE_COLOR, SWAPCHAIN};
+use crate::renderer::pass::{RenderContext, RenderPass};
+use crate::time::Time;
+
+const PRELUDE: &str = include_str!("post_prelude.wgsl");
+const BUILT_IN_EFFECTS: &str = include_str!("post.wgsl");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tonemapper {
+    Reinhard,
+    Aces,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("the {label} effect doesn't compile: {message}")]
+pub struct PostEffectError {
+    pub label: String,
+    pub message: String,
+}
+
+/// Checks that the source of a custom effect compiles after the prelude and has an `fs_main`
+pub(crate) fn validate_shader(source: &str) -> Result<(), String> {
+    let source = format!("{}\n{}", PRELUDE, source);
+    let module =
+        naga::front::wgsl::parse_str(&source).map_err(|error| error.emit_to_string(&source))?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|error| error.to_string())?;
+    if !module
+        .entry_points
+        .iter()
+        .any(|entry| entry.name == "fs_main")
+    {
+        return Err(String::from("there is no fs_main"));
+    }
+    Ok(())
+}
+
+/// A single fullscreen pass of the post-processing chain
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostEffect {
+    Tonemapping {
+        tonemapper: Tonemapper,
+        exposure: f32,
+    },
+    Gamma {
+        gamma: f32,
+    },
+    /// Adds a blurred copy of everything brighter than `threshold`
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+    },
+    Vignette {
+        intensity: f32,
+        radius: f32,
+        smoothness: f32,
+    },
+    Fxaa,
+    /// A WGSL fragment shader defining `fs_main`.
+    ///
+    /// It is compiled after the prelude in `post_prelude.wgsl`, which declares
+    /// `input_texture`, `input_sampler` and `params`, with `values` exposed as `params.values`.
+    /// Effects with a `shader` asset get its source once it is loaded, and again when it is
+    /// reloaded.
+    Custom {
+        label: String,
+        source: String,
+        values: [f32; 4],
+        shader: Option<Handle<Shader>>,
+    },
+}
+
+impl PostEffect {
+    pub fn tonemapping(tonemapper: Tonemapper) -> Self {
+        Self::Tonemapping {
+            tonemapper,
+            exposure: 1.0,
+        }
+    }
+
+    pub fn gamma() -> Self {
+        Self::Gamma { gamma: 2.2 }
+    }
+
+    pub fn bloom() -> Self {
+        Self::Bloom {
+            threshold: 1.0,
+            intensity: 0.5,
+            radius: 8.0,
+        }
+    }
+
+    pub fn vignette() -> Self {
+        Self::Vignette {
+            intensity: 0.4,
+            radius: 0.9,
+            smoothness: 0.5,
+        }
+    }
+
+    pub fn custom(label: &str, source: &str) -> Self {
+        Self::Custom {
+            label: String::from(label),
+            source: String::from(source),
+            values: [0.0; 4],
+            shader: None,
+        }
+    }
+
+    /// A custom effect skipped until `shader` is loaded by the `AssetPlugin`
+    pub fn custom_shader(label: &str, shader: Handle<Shader>) -> Self {
+        Self::Custom {
+            label: String::from(label),
+            source: String::new(),
+            values: [0.0; 4],
+            shader: Some(shader),
+        }
+    }
+
+    /// Custom effects without a source yet, like ones waiting for their shader, are skipped
+    pub fn is_ready(&self) -> bool {
+        !matches!(self, PostEffect::Custom { source, .. } if source.is_empty())
+    }
+
+    /// Compiles the source of a custom effect without a GPU, built-in effects are always valid
+    pub fn validate(&self) -> Result<(), PostEffectError> {
+        match self {
+            PostEffect::Custom { label, source, .. } if !source.is_empty() => {
+                validate_shader(source).map_err(|message| PostEffectError {
+                    label: label.clone(),
+                    message,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn entry_point(&self) -> &str {
+        match self {
+            PostEffect::Tonemapping {
+                tonemapper: Tonemapper::Reinhard,
+                ..
+            } => "fs_tonemap_reinhard",
+            PostEffect::Tonemapping {
+                tonemapper: Tonemapper::Aces,
+                ..
+            } => "fs_tonemap_aces",
+            PostEffect::Gamma { .. } => "fs_gamma",
+            PostEffect::Bloom { .. } => "fs_bloom",
+            PostEffect::Vignette { .. } => "fs_vignette",
+            PostEffect::Fxaa => "fs_fxaa",
+            PostEffect::Custom { .. } => "fs_main",
+        }
+    }
+
+    fn pipeline_key(&self) -> String {
+        match self {
+            // Effects sharing a label, or a label with a new source, get their own pipeline
+            PostEffect::Custom { label, source, .. } => {
+                let mut hasher = DefaultHasher::new();
+                source.hash(&mut hasher);
+                format!("custom:{}:{:016x}", label, hasher.finish())
+            }
+            effect => String::from(effect.entry_point()),
+        }
+    }
+
+    fn values(&self) -> [f32; 4] {
+        match *self {
+            PostEffect::Tonemapping { exposure, .. } => [exposure, 0.0, 0.0, 0.0],
+            PostEffect::Gamma { gamma } => [gamma, 0.0, 0.0, 0.0],
+            PostEffect::Bloom {
+                threshold,
+                intensity,
+                radius,
+            } => [threshold, intensity, radius, 0.0],
+            PostEffect::Vignette {
+                intensity,
+                radius,
+                smoothness,
+            } => [intensity, radius, smoothness, 0.0],
+            PostEffect::Fxaa => [0.0; 4],
+            PostEffect::Custom { values, .. } => values,
+        }
+    }
+}
+
+/// The effects applied, in order, to everything the window's cameras draw
+#[derive(Debug, Clone, Default)]
+pub struct PostProcessing {
+    pub effects: Vec<PostEffect>,
+}
+
+impl PostProcessing {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, effect: PostEffect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+
+    /// The effects that run this frame, in the order they were added
+    pub fn ready(&self) -> impl Iterator<Item = &PostEffect> {
+        self.effects.iter().filter(|effect| effect.is_ready())
+    }
+}
+
+impl Resource for PostProcessing {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostParams {
+    resolution: [f32; 2],
+    time: f32,
+    _padding: f32,
+    values: [f32; 4],
+}
+
+/// Runs the effect chain between two HDR textures
+pub struct PostProcessor {
+    // Ping-pong targets
+    targets: [(Texture, TextureView); 2],
+    width: u32,
+    height: u32,
+    sampler: Sampler,
+    bind_group_layout: BindGroupLayout,
+    pipeline_layout: PipelineLayout,
+    pipelines: HashMap<String, RenderPipeline>,
+    // Custom effects that didn't compile, so they are only reported once
+    rejected: HashSet<String>,
+    blit_pipeline: RenderPipeline,
+    params_buffers: Vec<Buffer>,
+}
+
+impl PostProcessor {
+    pub const HDR_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+    pub fn new(device: &Device, width: u32, height: u32, output_format: TextureFormat) -> Self {
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Processing Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Processing Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Processing Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let built_in = Self::create_shader(device, "Post Processing Shader", BUILT_IN_EFFECTS);
+        let blit_pipeline = Self::create_pipeline(
+            device,
+            &pipeline_layout,
+            &built_in,
+            "fs_blit",
+            output_format,
+        );
+
+        Self {
+            targets: [
+                Self::create_target(device, width, height),
+                Self::create_target(device, width, height),
+            ],
+            width,
+            height,
+            sampler,
+            bind_group_layout,
+            pipeline_layout,
+            pipelines: HashMap::new(),
+            rejected: HashSet::new(),
+            blit_pipeline,
+            params_buffers: vec![],
+        }
+    }
+
+    fn create_target(device: &Device, width: u32, height: u32) -> (Texture, TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("HDR Target"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::HDR_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    fn create_shader(device: &Device, label: &str, source: &str) -> wgpu::ShaderModule {
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(format!("{}\n{}", PRELUDE, source).into()),
+        })
+    }
+
+    fn create_pipeline(
+        device: &Device,
+        layout: &PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        entry_point: &str,
+        format: TextureFormat,
+    ) -> RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(entry_point),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_fullscreen",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        })
+    }
+
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32) {
+        if (width, height) == (self.width, self.height) {
+            return;
+        }
+        self.width = width;
+        self.height = height;
+        self.targets = [
+            Self::create_target(device, width, height),
+            Self::create_target(device, width, height),
+        ];
+    }
+
+    /// Compiles the pipelines of new or edited effects, custom ones that don't compile are skipped
+    pub fn prepare(&mut self, device: &Device, effects: &[PostEffect]) {
+        let keys = effects
+            .iter()
+            .map(PostEffect::pipeline_key)
+            .collect::<HashSet<_>>();
+        self.pipelines.retain(|key, _| keys.contains(key));
+        self.rejected.retain(|key| keys.contains(key));
+
+        let mut built_in = None;
+        for effect in effects.iter().filter(|effect| effect.is_ready()) {
+            let key = effect.pipeline_key();
+            if self.pipelines.contains_key(&key) || self.rejected.contains(&key) {
+                continue;
+            }
+
+            let pipeline = match effect {
+                PostEffect::Custom { source, .. } => {
+                    // wgpu panics on shaders that don't compile
+                    if let Err(error) = effect.validate() {
+                        tracing::error!("Skipped a post effect: {}", error);
+                        self.rejected.insert(key);
+                        continue;
+                    }
+                    let shader = Self::create_shader(device, &key, source);
+                    Self::create_pipeline(
+                        device,
+                        &self.pipeline_layout,
+                        &shader,
+                        effect.entry_point(),
+                        Self::HDR_FORMAT,
+                    )
+                }
+                _ => {
+                    let shader = built_in.get_or_insert_with(|| {
+                        Self::create_shader(device, "Post Processing Shader", BUILT_IN_EFFECTS)
+                    });
+                    Self::create_pipeline(
+                        device,
+                        &self.pipeline_layout,
+                        shader,
+                        effect.entry_point(),
+                        Self::HDR_FORMAT,
+                    )
+                }
+            };
+            self.pipelines.insert(key, pipeline);
+        }
+    }
+
+    fn draw(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        pipeline: &RenderPipeline,
+        params: &Buffer,
+        input: &TextureView,
+        output: &TextureView,
+    ) {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Processing Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params.as_entire_binding(),
+                },
+            ],
+        });
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Post Processing Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Applies `effects` to `input` and writes the result to `output`
+    #[allow(clippy::too_many_arguments)]
+    pub fn run(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        effects: &[PostEffect],
+        time: f32,
+        input: &TextureView,
+        output: &TextureView,
+    ) {
+        // One uniform buffer per pass, the blit included
+        while self.params_buffers.len() < effects.len() + 1 {
+            self.params_buffers
+                .push(device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Post Processing Params"),
+                    size: std::mem::size_of::<PostParams>() as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }));
+        }
+        let values = effects
+            .iter()
+            .map(PostEffect::values)
+            .chain(std::iter::once([0.0; 4]));
+        for (values, buffer) in values.zip(self.params_buffers.iter()) {
+            let params = PostParams {
+                resolution: [self.width as f32, self.height as f32],
+                time,
+                _padding: 0.0,
+                values,
+            };
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(&[params]));
+        }
+
+        let mut input = input;
+        let mut next = 0;
+        for (effect, params) in effects.iter().zip(self.params_buffers.iter()) {
+            let Some(pipeline) = self.pipelines.get(&effect.pipeline_key()) else {
+                continue;
+            };
+            self.draw(
+                device,
+                encoder,
+                pipeline,
+                params,
+                input,
+                &self.targets[next].1,
+            );
+            input = &self.targets[next].1;
+            next = 1 - next;
+        }
+
+        self.draw(
+            device,
+            encoder,
+            &self.blit_pipeline,
+            &self.params_buffers[effects.len()],
+            input,
+            output,
+        );
+    }
+}
+
+/// Post-processes the scene onto the window using the `PostProcessing` resource
+pub struct PostProcessPass {
+    processor: PostProcessor,
+    effects: Vec<PostEffect>,
+    elapsed_seconds: f32,
+}
+
+impl PostProcessPass {
+    pub fn new(context: &RenderContext<'_>) -> Self {
+        Self {
+            processor: PostProcessor::new(
+                context.device,
+                context.size.width,
+                context.size.height,
+                context.format,
+            ),
+            effects: vec![],
+            elapsed_seconds: 0.0,
+        }
+    }
+}
+
+impl RenderPass for PostProcessPass {
+    fn name(&self) -> &str {
+        "post_processing"
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        builder.read(SCENE_COLOR).write(SWAPCHAIN);
+    }
+
+    fn resize(&mut self, context: &RenderContext<'_>) {
+        self.processor
+            .resize(context.device, context.size.width, context.size.height);
+    }
+
+    fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        if let Ok(post_processing) = world.try_get_resource::<PostProcessing>() {
+            self.effects = post_processing.ready().cloned().collect();
+        }
+        if let Ok(time) = world.try_get_resource::<Time>() {
+            self.elapsed_seconds = time.elapsed_seconds();
+        }
+        self.processor.prepare(context.device, &self.effects);
+    }
+
+    fn render(
+        &mut self,
+        context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    ) {
+        let (Some(scene), Some(swapchain)) = (
+            resources.texture_view(SCENE_COLOR),
+            resources.texture_view(SWAPCHAIN),
+        ) else {
+            return;
+        };
+        self.processor.run(
+            context.device,
+            context.queue,
+            encoder,
+            &self.effects,
+            self.elapsed_seconds,
+            scene,
+            swapchain,
+        );
+    }
+}
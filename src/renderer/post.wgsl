@@ -0,0 +1,97 @@
+@fragment
+fn fs_blit(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    return textureSample(input_texture, input_sampler, in.uv);
+}
+
+// values.x: exposure
+@fragment
+fn fs_tonemap_reinhard(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let exposed = color.rgb * params.values.x;
+    return vec4<f32>(exposed / (vec3<f32>(1.0) + exposed), color.a);
+}
+
+// values.x: exposure
+@fragment
+fn fs_tonemap_aces(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let x = color.rgb * params.values.x;
+    // Narkowicz's fit of the ACES curve
+    let mapped = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+    return vec4<f32>(clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0)), color.a);
+}
+
+// values.x: gamma
+@fragment
+fn fs_gamma(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    return vec4<f32>(pow(max(color.rgb, vec3<f32>(0.0)), vec3<f32>(1.0 / params.values.x)), color.a);
+}
+
+// values.x: threshold, values.y: intensity, values.z: radius in pixels
+@fragment
+fn fs_bloom(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let texel = params.values.z / params.resolution;
+
+    var glow = vec3<f32>(0.0);
+    var total = 0.0;
+    for (var x = -3; x <= 3; x++) {
+        for (var y = -3; y <= 3; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) / 3.0;
+            let weight = exp(-dot(offset, offset) * 2.0);
+            let tap = textureSample(input_texture, input_sampler, in.uv + offset * texel).rgb;
+            glow += max(tap - vec3<f32>(params.values.x), vec3<f32>(0.0)) * weight;
+            total += weight;
+        }
+    }
+
+    return vec4<f32>(color.rgb + glow / total * params.values.y, color.a);
+}
+
+// values.x: intensity, values.y: radius, values.z: smoothness
+@fragment
+fn fs_vignette(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    let distance = length(in.uv - vec2<f32>(0.5)) * 1.41421356;
+    let shade = smoothstep(params.values.y, params.values.y - params.values.z, distance);
+    return vec4<f32>(color.rgb * mix(1.0 - params.values.x, 1.0, shade), color.a);
+}
+
+@fragment
+fn fs_fxaa(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let texel = 1.0 / params.resolution;
+    let center = textureSample(input_texture, input_sampler, in.uv);
+
+    let luma_m = luminance(center.rgb);
+    let luma_nw = luminance(textureSample(input_texture, input_sampler, in.uv + vec2<f32>(-1.0, -1.0) * texel).rgb);
+    let luma_ne = luminance(textureSample(input_texture, input_sampler, in.uv + vec2<f32>(1.0, -1.0) * texel).rgb);
+    let luma_sw = luminance(textureSample(input_texture, input_sampler, in.uv + vec2<f32>(-1.0, 1.0) * texel).rgb);
+    let luma_se = luminance(textureSample(input_texture, input_sampler, in.uv + vec2<f32>(1.0, 1.0) * texel).rgb);
+
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    var direction = vec2<f32>(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    );
+    let reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * (1.0 / 8.0), 1.0 / 128.0);
+    let scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + reduce);
+    direction = clamp(direction * scale, vec2<f32>(-8.0), vec2<f32>(8.0)) * texel;
+
+    let a = 0.5 * (
+        textureSample(input_texture, input_sampler, in.uv + direction * (1.0 / 3.0 - 0.5)).rgb +
+        textureSample(input_texture, input_sampler, in.uv + direction * (2.0 / 3.0 - 0.5)).rgb
+    );
+    let b = a * 0.5 + 0.25 * (
+        textureSample(input_texture, input_sampler, in.uv + direction * -0.5).rgb +
+        textureSample(input_texture, input_sampler, in.uv + direction * 0.5).rgb
+    );
+
+    let luma_b = luminance(b);
+    if luma_b < luma_min || luma_b > luma_max {
+        return vec4<f32>(a, center.a);
+    }
+    return vec4<f32>(b, center.a);
+}
@@ -0,0 +1,575 @@
+use std::collections::{HashMap, HashSet};
+
+use starry_ecs::World;
+use wgpu::{
+    include_wgsl, BindGroup, BindGroupLayout, Buffer, Color, CommandEncoder, Device, LoadOp,
+    Operations, PipelineLayout, RenderPassColorAttachment, RenderPassDepthStencilAttachment,
+    RenderPipeline, Sampler, ShaderModule, TextureFormat, TextureUsages, TextureView,
+};
+
+use crate::commands::WorldExt;
+use crate::renderer::camera::{Camera, CameraTarget, CameraUniform};
+use crate::renderer::debug::{CullMode, DebugRenderSettings};
+use crate::renderer::graph::{
+    PassBuilder, RenderResources, SlotSize, TextureSlot, LIGHTS, RENDER_TARGETS, SCENE_COLOR,
+    SCENE_DEPTH, SHADOW_MAPS,
+};
+use crate::renderer::image::{GpuImage, Image, ImageId};
+use crate::renderer::mesh::{Mesh, MeshBatch};
+use crate::renderer::pass::{RenderContext, RenderPass};
+use crate::renderer::post::PostProcessor;
+use crate::renderer::target::{RenderTargetDescriptor, RenderTargetId};
+use crate::renderer::RendererSettings;
+use crate::shapes::Vertex;
+
+/// A camera resolved for the current frame
+#[derive(Debug, Clone, Copy)]
+struct PreparedCamera {
+    target: CameraTarget,
+    uniform: CameraUniform,
+    clear_color: Color,
+}
+
+/// What the scene pipelines differ by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PipelineKey {
+    pub format: TextureFormat,
+    pub depth_format: Option<TextureFormat>,
+    pub cull_mode: CullMode,
+    pub wireframe: bool,
+}
+
+impl PipelineKey {
+    /// The key of the pipeline drawing into `target`, `None` if its render target doesn't exist
+    pub fn for_target<'a>(
+        target: CameraTarget,
+        render_target: impl FnOnce(RenderTargetId) -> Option<&'a RenderTargetDescriptor>,
+        debug: &DebugRenderSettings,
+    ) -> Option<Self> {
+        let (format, depth_format) = match target {
+            CameraTarget::Window => (PostProcessor::HDR_FORMAT, Some(ScenePass::DEPTH_FORMAT)),
+            CameraTarget::Texture(id) => {
+                let descriptor = render_target(id)?;
+                (descriptor.format, descriptor.depth_format)
+            }
+        };
+        Some(Self {
+            format,
+            depth_format,
+            cull_mode: debug.cull_mode,
+            wireframe: debug.wireframe,
+        })
+    }
+}
+
+fn create_scene_pipeline(
+    device: &Device,
+    layout: &PipelineLayout,
+    shader: &ShaderModule,
+    key: PipelineKey,
+) -> RenderPipeline {
+    // Without line rasterization wireframes are cut out of the triangles by the shader
+    let line_mode = key.wireframe
+        && device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE);
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",     // 1.
+            buffers: &[Vertex::desc()], // 2.
+        },
+        fragment: Some(wgpu::FragmentState {
+            // 3.
+            module: shader,
+            entry_point: if key.wireframe {
+                "fs_wireframe"
+            } else {
+                "fs_main"
+            },
+            targets: &[Some(wgpu::ColorTargetState {
+                // 4.
+                format: key.format,
+                blend: Some(wgpu::BlendState::REPLACE),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList, // 1.
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw, // 2.
+            cull_mode: key.cull_mode.face(),
+            // Line requires Features::POLYGON_MODE_LINE
+            polygon_mode: if line_mode {
+                wgpu::PolygonMode::Line
+            } else {
+                wgpu::PolygonMode::Fill
+            },
+            // Requires Features::DEPTH_CLIP_CONTROL
+            unclipped_depth: false,
+            // Requires Features::CONSERVATIVE_RASTERIZATION
+            conservative: false,
+        },
+        depth_stencil: key.depth_format.map(|format| wgpu::DepthStencilState {
+            format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: 1,                         // 2.
+            mask: !0,                         // 3.
+            alpha_to_coverage_enabled: false, // 4.
+        },
+        multiview: None, // 5.
+    })
+}
+
+/// Draws the scene once for every camera, into the window's HDR texture or a render target
+pub struct ScenePass {
+    // Pipelines, one per combination of color and depth format
+    shader: ShaderModule,
+    pipeline_layout: PipelineLayout,
+    pipelines: HashMap<PipelineKey, RenderPipeline>,
+
+    // Cameras
+    camera_bind_group_layout: BindGroupLayout,
+    camera_bindings: Vec<(Buffer, BindGroup)>,
+    cameras: Vec<PreparedCamera>,
+
+    // Meshes
+    mesh_bind_group_layout: BindGroupLayout,
+    meshes: MeshBatch,
+
+    // Mesh textures, meshes without one get a white pixel
+    texture_bind_group_layout: BindGroupLayout,
+    texture_sampler: Sampler,
+    white: Option<(GpuImage, BindGroup)>,
+    textures: HashMap<ImageId, (u32, BindGroup)>,
+
+    // Lights and shadow maps, written by the shadow pass
+    lights_bind_group_layout: BindGroupLayout,
+    shadow_sampler: Sampler,
+
+    color: Color,
+    debug: DebugRenderSettings,
+}
+
+impl ScenePass {
+    /// The format of `SCENE_DEPTH`
+    pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+    pub fn new(device: &Device, clear_color: Color) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("shader.wgsl"));
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Camera Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let mesh_bind_group_layout = MeshBatch::bind_group_layout(device);
+        let lights_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Lights Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Depth,
+                            view_dimension: wgpu::TextureViewDimension::D2Array,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                        count: None,
+                    },
+                ],
+            });
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Mesh Texture Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+        // Models often tile their textures
+        let texture_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mesh Texture Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let shadow_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Render Pipeline Layout"),
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                &mesh_bind_group_layout,
+                &lights_bind_group_layout,
+                &texture_bind_group_layout,
+            ],
+            push_constant_ranges: &[],
+        });
+        let debug = DebugRenderSettings::default();
+        let key = PipelineKey::for_target(CameraTarget::Window, |_| None, &debug)
+            .expect("the window is always a target");
+        let render_pipeline = create_scene_pipeline(device, &pipeline_layout, &shader, key);
+
+        Self {
+            shader,
+            pipeline_layout,
+            pipelines: HashMap::from([(key, render_pipeline)]),
+            camera_bind_group_layout,
+            camera_bindings: vec![],
+            cameras: vec![],
+            mesh_bind_group_layout,
+            meshes: MeshBatch::new(device),
+            texture_bind_group_layout,
+            texture_sampler,
+            white: None,
+            textures: HashMap::new(),
+            lights_bind_group_layout,
+            shadow_sampler,
+            color: clear_color,
+            debug,
+        }
+    }
+
+    fn pipeline_key(
+        &self,
+        context: &RenderContext<'_>,
+        target: CameraTarget,
+    ) -> Option<PipelineKey> {
+        PipelineKey::for_target(
+            target,
+            |id| Some(context.render_target(id)?.descriptor()),
+            &self.debug,
+        )
+    }
+
+    fn texture_bind_group(&self, device: &Device, image: &GpuImage) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Mesh Texture Bind Group"),
+            layout: &self.texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(image.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.texture_sampler),
+                },
+            ],
+        })
+    }
+
+    /// Binds the textures of the prepared meshes, again whenever their image changed
+    fn prepare_textures(&mut self, context: &RenderContext<'_>) {
+        if self.white.is_none() {
+            let image = Image::filled(1, 1, [255; 4]);
+            let white = GpuImage::new(context.device, context.queue, &image, 0);
+            let bind_group = self.texture_bind_group(context.device, &white);
+            self.white = Some((white, bind_group));
+        }
+
+        let used = self.meshes.textures().collect::<HashSet<_>>();
+        self.textures.retain(|id, (version, _)| {
+            used.contains(id)
+                && context
+                    .images
+                    .get(id)
+                    .is_some_and(|image| image.version == *version)
+        });
+        for id in used {
+            if self.textures.contains_key(&id) {
+                continue;
+            }
+            if let Some(image) = context.images.get(&id) {
+                let bind_group = self.texture_bind_group(context.device, image);
+                self.textures.insert(id, (image.version, bind_group));
+            }
+        }
+    }
+
+    fn prepare_cameras(&mut self, context: &RenderContext<'_>, world: &World) {
+        let mut cameras = world
+            .try_get_spawned::<Camera>()
+            .map(|cameras| cameras.iter().map(|camera| (**camera).clone()).collect())
+            .unwrap_or_else(|_| vec![]);
+        cameras.sort_by_key(|camera| camera.order);
+
+        let window_aspect_ratio = context.size.width as f32 / context.size.height.max(1) as f32;
+        self.cameras = cameras
+            .iter()
+            .filter_map(|camera| {
+                let aspect_ratio = match camera.target {
+                    CameraTarget::Window => window_aspect_ratio,
+                    CameraTarget::Texture(id) => context.render_target(id)?.aspect_ratio(),
+                };
+                Some(PreparedCamera {
+                    target: camera.target,
+                    uniform: CameraUniform::new(camera.view_projection(aspect_ratio)),
+                    clear_color: camera.clear_color.unwrap_or(self.color),
+                })
+            })
+            .collect();
+        // Without any camera the scene is drawn straight in clip space
+        if self.cameras.is_empty() {
+            self.cameras.push(PreparedCamera {
+                target: CameraTarget::Window,
+                uniform: CameraUniform::default(),
+                clear_color: self.color,
+            });
+        }
+
+        while self.camera_bindings.len() < self.cameras.len() {
+            let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Camera Buffer"),
+                size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = context
+                .device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Camera Bind Group"),
+                    layout: &self.camera_bind_group_layout,
+                    entries: &[wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                });
+            self.camera_bindings.push((buffer, bind_group));
+        }
+        for (camera, (buffer, _)) in self.cameras.iter().zip(self.camera_bindings.iter()) {
+            context
+                .queue
+                .write_buffer(buffer, 0, bytemuck::cast_slice(&[camera.uniform]));
+        }
+
+        for camera in self.cameras.iter() {
+            let Some(key) = self.pipeline_key(context, camera.target) else {
+                continue;
+            };
+            if !self.pipelines.contains_key(&key) {
+                let pipeline =
+                    create_scene_pipeline(context.device, &self.pipeline_layout, &self.shader, key);
+                self.pipelines.insert(key, pipeline);
+            }
+        }
+    }
+}
+
+impl RenderPass for ScenePass {
+    fn name(&self) -> &str {
+        "scene"
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        builder
+            .create_texture(
+                SCENE_COLOR,
+                TextureSlot {
+                    size: SlotSize::Window,
+                    format: PostProcessor::HDR_FORMAT,
+                    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                    layers: 1,
+                },
+            )
+            .create_texture(
+                SCENE_DEPTH,
+                TextureSlot {
+                    size: SlotSize::Window,
+                    format: Self::DEPTH_FORMAT,
+                    usage: TextureUsages::RENDER_ATTACHMENT,
+                    layers: 1,
+                },
+            )
+            .read(SHADOW_MAPS)
+            .read(LIGHTS)
+            .write(RENDER_TARGETS);
+    }
+
+    fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        if let Ok(settings) = world.try_get_resource::<RendererSettings>() {
+            self.color = settings.clear_color;
+            self.debug = settings.debug.clone();
+        }
+        self.prepare_cameras(context, world);
+
+        let meshes = world.try_get_spawned::<Mesh>().unwrap_or_default();
+        self.meshes.prepare(
+            context.device,
+            context.queue,
+            &self.mesh_bind_group_layout,
+            meshes.iter().map(|mesh| &**mesh),
+        );
+        self.prepare_textures(context);
+    }
+
+    fn render(
+        &mut self,
+        context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    ) {
+        let (
+            Some(scene_view),
+            Some(scene_depth),
+            Some(shadow_maps),
+            Some(lights),
+            Some((_, white)),
+        ) = (
+            resources.texture_view(SCENE_COLOR),
+            resources.texture_view(SCENE_DEPTH),
+            resources.texture_view(SHADOW_MAPS),
+            resources.buffer(LIGHTS),
+            self.white.as_ref(),
+        )
+        else {
+            return;
+        };
+        let lights_bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Lights Bind Group"),
+                layout: &self.lights_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: lights.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(shadow_maps),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::Sampler(&self.shadow_sampler),
+                    },
+                ],
+            });
+
+        // Only the first camera drawing to a target clears it
+        let mut cleared = HashSet::new();
+        for (camera, (_, camera_bind_group)) in self.cameras.iter().zip(self.camera_bindings.iter())
+        {
+            let Some(key) = self.pipeline_key(context, camera.target) else {
+                continue;
+            };
+            let (color_view, depth_view): (&TextureView, Option<&TextureView>) = match camera.target
+            {
+                CameraTarget::Window => (scene_view, Some(scene_depth)),
+                CameraTarget::Texture(id) => {
+                    let target = &context.render_targets[&id];
+                    (target.color_view(), target.depth_view())
+                }
+            };
+            let clear = cleared.insert(camera.target);
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: color_view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: if clear {
+                            LoadOp::Clear(camera.clear_color)
+                        } else {
+                            LoadOp::Load
+                        },
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: depth_view.map(|view| RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+
+            render_pass.set_pipeline(&self.pipelines[&key]);
+            render_pass.set_bind_group(0, camera_bind_group, &[]);
+            render_pass.set_bind_group(2, &lights_bind_group, &[]);
+            let textures = &self.textures;
+            self.meshes
+                .draw_with(&mut render_pass, 1, |render_pass, texture| {
+                    let bind_group = texture
+                        .and_then(|id| textures.get(&id))
+                        .map_or(white, |(_, bind_group)| bind_group);
+                    render_pass.set_bind_group(3, bind_group, &[]);
+                });
+        }
+
+        // The scene texture still has to be cleared when no camera draws to the window
+        if !cleared.contains(&CameraTarget::Window) {
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Clear Pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: scene_view,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: LoadOp::Clear(self.color),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+        }
+    }
+}
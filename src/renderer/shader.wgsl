@@ -0,0 +1,185 @@
+struct CameraUniform {
+    view_projection: mat4x4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+struct MeshUniform {
+    model: mat4x4<f32>,
+    // Inverse transpose of the model matrix
+    normal: mat4x4<f32>,
+    receive_shadows: u32,
+}
+@group(1) @binding(0)
+var<uniform> mesh: MeshUniform;
+
+struct DirectionalLight {
+    // w is 1.0 when the light casts shadows
+    direction: vec4<f32>,
+    color: vec4<f32>,
+}
+
+struct SpotLight {
+    // w is the range
+    position: vec4<f32>,
+    // w is the cosine of the outer angle
+    direction: vec4<f32>,
+    // w is the cosine of the inner angle
+    color: vec4<f32>,
+    view_projection: mat4x4<f32>,
+    // x is the shadow map layer, negative without shadows
+    shadow: vec4<f32>,
+}
+
+struct Lights {
+    ambient: vec4<f32>,
+    directional: DirectionalLight,
+    cascades: array<mat4x4<f32>, 4>,
+    spot_lights: array<SpotLight, 4>,
+    // Directional lights, spot lights, cascades and PCF radius
+    counts: vec4<u32>,
+    // Depth bias, normal bias and texel size
+    shadow: vec4<f32>,
+}
+@group(2) @binding(0)
+var<uniform> lights: Lights;
+@group(2) @binding(1)
+var shadow_maps: texture_depth_2d_array;
+@group(2) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+// White for meshes without a texture
+@group(3) @binding(0)
+var mesh_texture: texture_2d<f32>;
+@group(3) @binding(1)
+var mesh_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) uv: vec2<f32>,
+}
+
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) world_position: vec3<f32>,
+    @location(2) @interpolate(flat) receive_shadows: u32,
+    // One corner of the triangle per component, for wireframes without line rasterization
+    @location(3) barycentric: vec3<f32>,
+    // Zero when the mesh has no normals
+    @location(4) normal: vec3<f32>,
+    @location(5) uv: vec2<f32>,
+};
+
+
+@vertex
+fn vs_main(model: VertexInput, @builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    let corner = vertex_index % 3u;
+    out.barycentric = vec3<f32>(f32(corner == 0u), f32(corner == 1u), f32(corner == 2u));
+    let world_position = mesh.model * model.position;
+    out.color = model.color;
+    out.world_position = world_position.xyz / world_position.w;
+    // Normalized per fragment, interpolation shortens it
+    out.normal = (mesh.normal * vec4<f32>(model.normal, 0.0)).xyz;
+    out.uv = model.uv;
+    out.receive_shadows = mesh.receive_shadows;
+    out.clip_position = camera.view_projection * world_position;
+    return out;
+}
+
+// Shadow map coordinates in xy, depth in z and w set to 1.0 when inside the map
+fn shadow_coordinates(view_projection: mat4x4<f32>, position: vec3<f32>) -> vec4<f32> {
+    let clip = view_projection * vec4<f32>(position, 1.0);
+    let ndc = clip.xyz / clip.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + 0.5;
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0)) && ndc.z >= 0.0 && ndc.z <= 1.0;
+    return vec4<f32>(uv, ndc.z, select(0.0, 1.0, inside));
+}
+
+// Percentage closer filtering, 1.0 is fully lit
+fn sample_shadow(layer: i32, coordinates: vec4<f32>) -> f32 {
+    let radius = i32(lights.counts.w);
+    let depth = coordinates.z - lights.shadow.x;
+    var lit = 0.0;
+    for (var x = -radius; x <= radius; x++) {
+        for (var y = -radius; y <= radius; y++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * lights.shadow.z;
+            lit += textureSampleCompareLevel(shadow_maps, shadow_sampler, coordinates.xy + offset, layer, depth);
+        }
+    }
+    let taps = f32((2 * radius + 1) * (2 * radius + 1));
+    return lit / taps;
+}
+
+fn directional_shadow(position: vec3<f32>) -> f32 {
+    for (var cascade = 0u; cascade < lights.counts.z; cascade++) {
+        let coordinates = shadow_coordinates(lights.cascades[cascade], position);
+        if coordinates.w > 0.0 {
+            return sample_shadow(i32(cascade), coordinates);
+        }
+    }
+    return 1.0;
+}
+
+fn spot_shadow(light: SpotLight, position: vec3<f32>) -> f32 {
+    let coordinates = shadow_coordinates(light.view_projection, position);
+    if coordinates.w > 0.0 {
+        return sample_shadow(i32(light.shadow.x), coordinates);
+    }
+    return 1.0;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = in.color * textureSample(mesh_texture, mesh_sampler, in.uv);
+    // Flat normal facing the camera for meshes without normals
+    let flat_normal = normalize(cross(dpdy(in.world_position), dpdx(in.world_position)));
+    let has_normal = dot(in.normal, in.normal) > 0.0;
+    let normal = select(flat_normal, normalize(in.normal), has_normal);
+    if lights.counts.x + lights.counts.y == 0u {
+        return color;
+    }
+
+    let receive_shadows = in.receive_shadows != 0u;
+    let shadow_position = in.world_position + normal * lights.shadow.y;
+    var light = lights.ambient.rgb;
+
+    if lights.counts.x > 0u {
+        let directional = lights.directional;
+        var lit = max(dot(normal, -directional.direction.xyz), 0.0);
+        if receive_shadows && directional.direction.w > 0.0 {
+            lit *= directional_shadow(shadow_position);
+        }
+        light += directional.color.rgb * lit;
+    }
+
+    for (var index = 0u; index < lights.counts.y; index++) {
+        let spot = lights.spot_lights[index];
+        let to_light = spot.position.xyz - in.world_position;
+        let distance = length(to_light);
+        let direction = to_light / max(distance, 0.0001);
+        let cone = smoothstep(spot.direction.w, spot.color.w, dot(-direction, spot.direction.xyz));
+        let falloff = pow(clamp(1.0 - pow(distance / spot.position.w, 2.0), 0.0, 1.0), 2.0);
+        var lit = max(dot(normal, direction), 0.0) * cone * falloff;
+        if receive_shadows && spot.shadow.x >= 0.0 {
+            lit *= spot_shadow(spot, shadow_position);
+        }
+        light += spot.color.rgb * lit;
+    }
+
+    return vec4<f32>(color.rgb * light, color.a);
+}
+
+// Keeps the fragments within a pixel of a triangle's edges
+@fragment
+fn fs_wireframe(in: VertexOutput) -> @location(0) vec4<f32> {
+    let pixels = in.barycentric / max(fwidth(in.barycentric), vec3<f32>(0.000001));
+    if min(pixels.x, min(pixels.y, pixels.z)) > 1.0 {
+        discard;
+    }
+    return vec4<f32>(in.color.rgb, 1.0);
+}
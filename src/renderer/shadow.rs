@@ -0,0 +1,509 @@
+use glam::{Mat4, Vec3, Vec4Swizzles};
+use starry_ecs::World;
+use wgpu::{
+    include_wgsl, BindGroup, BindGroupLayout, Buffer, Color, CommandEncoder, Device, LoadOp,
+    Operations, RenderPassDepthStencilAttachment, RenderPipeline, TextureFormat, TextureUsages,
+};
+
+use crate::commands::WorldExt;
+use crate::renderer::camera::{window_camera, Projection};
+use crate::renderer::graph::{
+    BufferSlot, PassBuilder, RenderResources, SlotSize, TextureSlot, LIGHTS, SHADOW_MAPS,
+};
+use crate::renderer::light::{AmbientLight, DirectionalLight, SpotLight};
+use crate::renderer::mesh::{Mesh, MeshBatch};
+use crate::renderer::pass::{RenderContext, RenderPass};
+use crate::renderer::RendererSettings;
+use crate::shapes::Vertex;
+
+pub const MAX_CASCADES: usize = 4;
+/// Spot lights past this many are ignored
+pub const MAX_SPOT_LIGHTS: usize = 4;
+/// Layers of the shadow map array, the cascades come first
+pub const SHADOW_LAYERS: u32 = (MAX_CASCADES + MAX_SPOT_LIGHTS) as u32;
+pub const SHADOW_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowSettings {
+    /// Width and height of every shadow map, only read when the renderer starts
+    pub map_size: u32,
+    /// Up to `MAX_CASCADES`
+    pub cascade_count: usize,
+    /// Distance from the camera the last cascade ends at
+    pub max_distance: f32,
+    /// Blends uniform (0.0) and logarithmic (1.0) cascade splits
+    pub cascade_split_lambda: f32,
+    /// Filters `(2 * pcf_radius + 1)²` shadow map texels
+    pub pcf_radius: u32,
+    pub depth_bias: f32,
+    /// Moves the surface along its normal before looking it up in the shadow map
+    pub normal_bias: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            map_size: 2048,
+            cascade_count: MAX_CASCADES,
+            max_distance: 50.0,
+            cascade_split_lambda: 0.75,
+            pcf_radius: 1,
+            depth_bias: 0.002,
+            normal_bias: 0.02,
+        }
+    }
+}
+
+/// The view distance each cascade ends at, mixing logarithmic and uniform splits
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|index| {
+            let fraction = index as f32 / count as f32;
+            let uniform = near + (far - near) * fraction;
+            if near <= 0.0 {
+                return uniform;
+            }
+            let logarithmic = near * (far / near).powf(fraction);
+            lambda * logarithmic + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+/// Fits an orthographic projection along `light_direction` around each slice of the camera frustum.
+///
+/// `splits` are the view distances returned by `cascade_splits`, the projections
+/// are snapped to shadow map texels so shadows don't shimmer when the camera moves.
+pub fn cascade_view_projections(
+    camera_view_projection: Mat4,
+    near: f32,
+    far: f32,
+    splits: &[f32],
+    light_direction: Vec3,
+    map_size: u32,
+) -> Vec<Mat4> {
+    let inverse = camera_view_projection.inverse();
+    let corners = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)].map(|(x, y)| {
+        (
+            inverse.project_point3(Vec3::new(x, y, 0.0)),
+            inverse.project_point3(Vec3::new(x, y, 1.0)),
+        )
+    });
+
+    let direction = light_direction.normalize_or_zero();
+    let up = if direction.y.abs() > 0.99 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    };
+    let depth = (far - near).max(f32::EPSILON);
+
+    let mut start = near;
+    splits
+        .iter()
+        .map(|&end| {
+            let slice = corners
+                .iter()
+                .flat_map(|(near_corner, far_corner)| {
+                    [start, end]
+                        .map(|distance| near_corner.lerp(*far_corner, (distance - near) / depth))
+                })
+                .collect::<Vec<_>>();
+            start = end;
+
+            // A bounding sphere keeps the projection's size constant as the camera rotates
+            let center = slice.iter().sum::<Vec3>() / slice.len() as f32;
+            let radius = slice
+                .iter()
+                .map(|corner| corner.distance(center))
+                .fold(0.0, f32::max);
+            let radius = (radius * 16.0).ceil() / 16.0;
+
+            let view = Mat4::look_at_rh(center - direction * radius, center, up);
+            // Meshes behind the slice still cast shadows into it
+            let projection = Mat4::orthographic_rh(
+                -radius,
+                radius,
+                -radius,
+                radius,
+                -radius * 4.0,
+                radius * 2.0,
+            );
+
+            let view_projection = projection * view;
+            let half_size = map_size as f32 / 2.0;
+            let origin = (view_projection * Vec3::ZERO.extend(1.0)).xy() * half_size;
+            let offset = (origin.round() - origin) / half_size;
+            Mat4::from_translation(offset.extend(0.0)) * view_projection
+        })
+        .collect()
+}
+
+fn linear_color(color: Color, scale: f32) -> [f32; 4] {
+    [
+        color.r as f32 * scale,
+        color.g as f32 * scale,
+        color.b as f32 * scale,
+        1.0,
+    ]
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct DirectionalLightUniform {
+    // w is 1.0 when the light casts shadows
+    direction: [f32; 4],
+    color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpotLightUniform {
+    // w is the range
+    position: [f32; 4],
+    // w is the cosine of the outer angle
+    direction: [f32; 4],
+    // w is the cosine of the inner angle
+    color: [f32; 4],
+    view_projection: [[f32; 4]; 4],
+    // x is the shadow map layer, negative without shadows
+    shadow: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LightsUniform {
+    ambient: [f32; 4],
+    directional: DirectionalLightUniform,
+    cascades: [[[f32; 4]; 4]; MAX_CASCADES],
+    spot_lights: [SpotLightUniform; MAX_SPOT_LIGHTS],
+    // Directional lights, spot lights, cascades and PCF radius
+    counts: [u32; 4],
+    // Depth bias, normal bias and texel size
+    shadow: [f32; 4],
+}
+
+/// The camera the cascades follow
+fn main_camera(world: &World, aspect_ratio: f32) -> (Mat4, f32, f32) {
+    match window_camera(world) {
+        Some(camera) => {
+            let (near, far) = match camera.projection {
+                Projection::Perspective { near, far, .. } => (near, far),
+                Projection::Orthographic { near, far, .. } => (near, far),
+            };
+            (camera.view_projection(aspect_ratio), near, far)
+        }
+        // The scene is in clip space without a camera
+        None => (Mat4::IDENTITY, 0.0, 1.0),
+    }
+}
+
+/// Renders the depth of every shadow casting mesh from each light and
+/// fills the `LIGHTS` buffer the scene is shaded with
+pub struct ShadowPass {
+    map_size: u32,
+    pipeline: RenderPipeline,
+    mesh_bind_group_layout: BindGroupLayout,
+    meshes: MeshBatch,
+    // One view projection per shadow map layer, bound at a dynamic offset
+    matrix_buffer: Buffer,
+    matrix_bind_group: BindGroup,
+    matrix_stride: u64,
+    layers: Vec<u32>,
+    lights: LightsUniform,
+}
+
+impl ShadowPass {
+    pub fn new(device: &Device, settings: &ShadowSettings) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("shadow.wgsl"));
+        let matrix_size = std::mem::size_of::<[[f32; 4]; 4]>() as u64;
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let matrix_stride = matrix_size.div_ceil(alignment) * alignment;
+
+        let matrix_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Shadow Matrix Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(matrix_size),
+                    },
+                    count: None,
+                }],
+            });
+        let matrix_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Matrix Buffer"),
+            size: matrix_stride * SHADOW_LAYERS as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let matrix_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Matrix Bind Group"),
+            layout: &matrix_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &matrix_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(matrix_size),
+                }),
+            }],
+        });
+        let mesh_bind_group_layout = MeshBatch::bind_group_layout(device);
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&matrix_bind_group_layout, &mesh_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_shadow",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                // Both faces cast shadows, meshes aren't guaranteed to be closed
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: SHADOW_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            map_size: settings.map_size.max(1),
+            pipeline,
+            mesh_bind_group_layout,
+            meshes: MeshBatch::new(device),
+            matrix_buffer,
+            matrix_bind_group,
+            matrix_stride,
+            layers: vec![],
+            lights: LightsUniform::default(),
+        }
+    }
+
+    fn prepare_lights(&mut self, context: &RenderContext<'_>, world: &World) -> Vec<Mat4> {
+        let settings = world
+            .try_get_resource::<RendererSettings>()
+            .map(|settings| settings.shadows.clone())
+            .unwrap_or_default();
+        let ambient = world
+            .try_get_resource::<AmbientLight>()
+            .map(|ambient| ambient.clone())
+            .unwrap_or_default();
+
+        let mut lights = LightsUniform {
+            ambient: linear_color(ambient.color, ambient.brightness),
+            shadow: [
+                settings.depth_bias,
+                settings.normal_bias,
+                1.0 / self.map_size as f32,
+                0.0,
+            ],
+            ..Default::default()
+        };
+        lights.counts[3] = settings.pcf_radius;
+        let mut matrices = vec![Mat4::IDENTITY; SHADOW_LAYERS as usize];
+        self.layers.clear();
+
+        let directional = world
+            .try_get_spawned::<DirectionalLight>()
+            .ok()
+            .and_then(|lights| lights.first().map(|light| (**light).clone()));
+        if let Some(light) = directional {
+            let direction = light.direction.normalize_or_zero();
+            lights.counts[0] = 1;
+            lights.directional = DirectionalLightUniform {
+                direction: direction
+                    .extend(light.cast_shadows as u32 as f32)
+                    .to_array(),
+                color: linear_color(light.color, light.illuminance),
+            };
+
+            if light.cast_shadows {
+                let aspect_ratio = context.size.width as f32 / context.size.height.max(1) as f32;
+                let (view_projection, near, far) = main_camera(world, aspect_ratio);
+                let count = settings.cascade_count.clamp(1, MAX_CASCADES);
+                let splits = cascade_splits(
+                    near,
+                    far.min(settings.max_distance),
+                    count,
+                    settings.cascade_split_lambda,
+                );
+                let cascades = cascade_view_projections(
+                    view_projection,
+                    near,
+                    far,
+                    &splits,
+                    direction,
+                    self.map_size,
+                );
+                lights.counts[2] = cascades.len() as u32;
+                for (index, cascade) in cascades.into_iter().enumerate() {
+                    lights.cascades[index] = cascade.to_cols_array_2d();
+                    matrices[index] = cascade;
+                    self.layers.push(index as u32);
+                }
+            }
+        }
+
+        let spot_lights = world
+            .try_get_spawned::<SpotLight>()
+            .map(|lights| {
+                lights
+                    .iter()
+                    .take(MAX_SPOT_LIGHTS)
+                    .map(|light| (**light).clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        lights.counts[1] = spot_lights.len() as u32;
+        for (index, light) in spot_lights.iter().enumerate() {
+            let layer = (MAX_CASCADES + index) as u32;
+            let view_projection = light.view_projection();
+            lights.spot_lights[index] = SpotLightUniform {
+                position: light.position.extend(light.range).to_array(),
+                direction: light
+                    .direction
+                    .normalize_or_zero()
+                    .extend(light.outer_angle_degrees.to_radians().cos())
+                    .to_array(),
+                color: {
+                    let mut color = linear_color(light.color, light.intensity);
+                    color[3] = light.inner_angle_degrees.to_radians().cos();
+                    color
+                },
+                view_projection: view_projection.to_cols_array_2d(),
+                shadow: [
+                    if light.cast_shadows {
+                        layer as f32
+                    } else {
+                        -1.0
+                    },
+                    0.0,
+                    0.0,
+                    0.0,
+                ],
+            };
+            if light.cast_shadows {
+                matrices[layer as usize] = view_projection;
+                self.layers.push(layer);
+            }
+        }
+
+        self.lights = lights;
+        matrices
+    }
+}
+
+impl RenderPass for ShadowPass {
+    fn name(&self) -> &str {
+        "shadows"
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        builder
+            .create_texture(
+                SHADOW_MAPS,
+                TextureSlot {
+                    size: SlotSize::Fixed(self.map_size, self.map_size),
+                    format: SHADOW_FORMAT,
+                    usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                    layers: SHADOW_LAYERS,
+                },
+            )
+            .create_buffer(
+                LIGHTS,
+                BufferSlot {
+                    size: std::mem::size_of::<LightsUniform>() as u64,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                },
+            );
+    }
+
+    fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        let matrices = self.prepare_lights(context, world);
+        for (index, matrix) in matrices.iter().enumerate() {
+            context.queue.write_buffer(
+                &self.matrix_buffer,
+                index as u64 * self.matrix_stride,
+                bytemuck::cast_slice(&matrix.to_cols_array()),
+            );
+        }
+
+        let meshes = world.try_get_spawned::<Mesh>().unwrap_or_default();
+        self.meshes.prepare(
+            context.device,
+            context.queue,
+            &self.mesh_bind_group_layout,
+            meshes
+                .iter()
+                .map(|mesh| &**mesh)
+                .filter(|mesh| mesh.cast_shadows),
+        );
+    }
+
+    fn render(
+        &mut self,
+        context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    ) {
+        if let Some(buffer) = resources.buffer(LIGHTS) {
+            context
+                .queue
+                .write_buffer(buffer, 0, bytemuck::bytes_of(&self.lights));
+        }
+        let Some(texture) = resources.texture(SHADOW_MAPS) else {
+            return;
+        };
+
+        for &layer in self.layers.iter() {
+            let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Shadow Map Layer"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            });
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                    view: &view,
+                    depth_ops: Some(Operations {
+                        load: LoadOp::Clear(1.0),
+                        store: true,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(
+                0,
+                &self.matrix_bind_group,
+                &[(layer as u64 * self.matrix_stride) as u32],
+            );
+            self.meshes.draw(&mut render_pass, 1);
+        }
+    }
+}
@@ -0,0 +1,19 @@
+struct LightMatrix {
+    view_projection: mat4x4<f32>,
+}
+@group(0) @binding(0)
+var<uniform> light: LightMatrix;
+
+struct MeshUniform {
+    model: mat4x4<f32>,
+    // Inverse transpose of the model matrix
+    normal: mat4x4<f32>,
+    receive_shadows: u32,
+}
+@group(1) @binding(0)
+var<uniform> mesh: MeshUniform;
+
+@vertex
+fn vs_shadow(@location(0) position: vec4<f32>) -> @builtin(position) vec4<f32> {
+    return light.view_projection * mesh.model * position;
+}
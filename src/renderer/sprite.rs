@@ -0,0 +1,549 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use glam::{Mat4, Vec2};
+use starry_ecs::component::Component;
+use starry_ecs::World;
+use wgpu::{
+    include_wgsl, BindGroup, BindGroupLayout, Buffer, Color, CommandEncoder, Device, LoadOp,
+    Operations, RenderPassColorAttachment, RenderPipeline,
+};
+
+use crate::commands::WorldExt;
+use crate::renderer::animation::AnimatedSprite;
+use crate::renderer::camera::{window_camera, Camera, CameraUniform, Projection};
+use crate::renderer::graph::{PassBuilder, RenderResources, SCENE_COLOR};
+use crate::renderer::image::ImageId;
+use crate::renderer::pass::{write_buffer, RenderContext, RenderPass};
+use crate::renderer::post::PostProcessor;
+use crate::renderer::RendererSettings;
+
+/// An axis aligned rectangle, in pixels when it describes a region of an image
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            min: Vec2::new(x, y),
+            max: Vec2::new(x + width, y + height),
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmplt(self.max).all()
+    }
+}
+
+/// The point of a sprite placed at its position and rotated around
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Anchor {
+    #[default]
+    Center,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+    CenterLeft,
+    CenterRight,
+    TopLeft,
+    TopCenter,
+    TopRight,
+    /// From (-0.5, -0.5) at the bottom left to (0.5, 0.5) at the top right
+    Custom(Vec2),
+}
+
+impl Anchor {
+    pub fn offset(&self) -> Vec2 {
+        match *self {
+            Anchor::Center => Vec2::ZERO,
+            Anchor::BottomLeft => Vec2::new(-0.5, -0.5),
+            Anchor::BottomCenter => Vec2::new(0.0, -0.5),
+            Anchor::BottomRight => Vec2::new(0.5, -0.5),
+            Anchor::CenterLeft => Vec2::new(-0.5, 0.0),
+            Anchor::CenterRight => Vec2::new(0.5, 0.0),
+            Anchor::TopLeft => Vec2::new(-0.5, 0.5),
+            Anchor::TopCenter => Vec2::new(0.0, 0.5),
+            Anchor::TopRight => Vec2::new(0.5, 0.5),
+            Anchor::Custom(offset) => offset,
+        }
+    }
+}
+
+/// How sprites are projected, changed at runtime through `RendererSettings`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpriteSettings {
+    /// Draws sprites through the projection of a perspective window camera too,
+    /// instead of only following its position
+    pub use_camera_projection: bool,
+}
+
+impl SpriteSettings {
+    /// Orthographic cameras, and perspective ones with `use_camera_projection`, draw sprites
+    /// with their own projection. Otherwise sprites are in pixels from the camera position.
+    pub fn view_projection(&self, camera: Option<&Camera>, width: f32, height: f32) -> Mat4 {
+        if let Some(camera) = camera {
+            let orthographic = matches!(camera.projection, Projection::Orthographic { .. });
+            if orthographic || self.use_camera_projection {
+                return camera.view_projection(width / height.max(1.0));
+            }
+        }
+        let position = camera.map_or(Vec2::ZERO, |camera| camera.position.truncate());
+        Mat4::orthographic_rh(
+            -width / 2.0,
+            width / 2.0,
+            -height / 2.0,
+            height / 2.0,
+            -1000.0,
+            1000.0,
+        ) * Mat4::from_translation(-position.extend(0.0))
+    }
+}
+
+/// An image, or a region of one, drawn on top of the scene.
+///
+/// Sprites with a higher `z` are drawn over the ones with a lower `z`.
+#[derive(Debug, Clone)]
+pub struct Sprite {
+    pub image: ImageId,
+    /// Region of the image in pixels, the whole image when `None`
+    pub region: Option<Rect>,
+    pub position: Vec2,
+    pub z: f32,
+    pub rotation_degrees: f32,
+    pub scale: Vec2,
+    /// Size in world units, the size of the region in pixels when `None`
+    pub size: Option<Vec2>,
+    pub tint: Color,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub anchor: Anchor,
+}
+
+impl Sprite {
+    pub fn new(image: ImageId) -> Self {
+        Self {
+            image,
+            region: None,
+            position: Vec2::ZERO,
+            z: 0.0,
+            rotation_degrees: 0.0,
+            scale: Vec2::ONE,
+            size: None,
+            tint: Color::WHITE,
+            flip_x: false,
+            flip_y: false,
+            anchor: Anchor::Center,
+        }
+    }
+
+    pub fn with_region(mut self, region: Rect) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    pub fn with_position(mut self, position: Vec2) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_z(mut self, z: f32) -> Self {
+        self.z = z;
+        self
+    }
+
+    pub fn with_rotation(mut self, degrees: f32) -> Self {
+        self.rotation_degrees = degrees;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec2) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_size(mut self, size: Vec2) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn with_tint(mut self, tint: Color) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    pub fn with_flip(mut self, flip_x: bool, flip_y: bool) -> Self {
+        self.flip_x = flip_x;
+        self.flip_y = flip_y;
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// The corners of the sprite, counter-clockwise from the bottom left
+    pub fn vertices(&self, image_size: (u32, u32)) -> [SpriteVertex; 4] {
+        let image_size = Vec2::new(image_size.0.max(1) as f32, image_size.1.max(1) as f32);
+        let region = self.region.unwrap_or(Rect {
+            min: Vec2::ZERO,
+            max: image_size,
+        });
+        let size = self.size.unwrap_or(region.size()) * self.scale;
+
+        // Image rows go down while world Y goes up
+        let (mut left, mut right) = (region.min.x, region.max.x);
+        let (mut bottom, mut top) = (region.max.y, region.min.y);
+        if self.flip_x {
+            std::mem::swap(&mut left, &mut right);
+        }
+        if self.flip_y {
+            std::mem::swap(&mut bottom, &mut top);
+        }
+
+        let rotation = Mat4::from_rotation_z(self.rotation_degrees.to_radians());
+        let anchor = self.anchor.offset();
+        let color = [
+            self.tint.r as f32,
+            self.tint.g as f32,
+            self.tint.b as f32,
+            self.tint.a as f32,
+        ];
+        [
+            (Vec2::new(-0.5, -0.5), Vec2::new(left, bottom)),
+            (Vec2::new(0.5, -0.5), Vec2::new(right, bottom)),
+            (Vec2::new(0.5, 0.5), Vec2::new(right, top)),
+            (Vec2::new(-0.5, 0.5), Vec2::new(left, top)),
+        ]
+        .map(|(corner, pixel)| {
+            let local = (corner - anchor) * size;
+            let position =
+                self.position.extend(self.z) + rotation.transform_vector3(local.extend(0.0));
+            SpriteVertex {
+                position: position.to_array(),
+                uv: (pixel / image_size).to_array(),
+                color,
+            }
+        })
+    }
+}
+
+impl Component for Sprite {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpriteVertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl SpriteVertex {
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 3] =
+            wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x2, 2 => Float32x4];
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &ATTRIBUTES,
+        }
+    }
+}
+
+/// Consecutive sprites sharing an image, drawn with a single call
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteBatch {
+    pub image: ImageId,
+    pub sprites: Range<u32>,
+}
+
+/// Sorts sprites back to front and groups the neighbours sharing an image.
+///
+/// Sprites with the same `z` are sorted by image, so they share as few batches as possible.
+pub fn batch_sprites(sprites: &mut [Sprite]) -> Vec<SpriteBatch> {
+    sprites.sort_by(|a, b| a.z.total_cmp(&b.z).then(a.image.cmp(&b.image)));
+
+    let mut batches: Vec<SpriteBatch> = vec![];
+    for (index, sprite) in sprites.iter().enumerate() {
+        let index = index as u32;
+        match batches.last_mut() {
+            Some(batch) if batch.image == sprite.image => batch.sprites.end = index + 1,
+            _ => batches.push(SpriteBatch {
+                image: sprite.image,
+                sprites: index..index + 1,
+            }),
+        }
+    }
+    batches
+}
+
+/// Draws every `Sprite` into the scene texture, one draw call per batch
+pub struct SpritePass {
+    pipeline: RenderPipeline,
+    camera_buffer: Buffer,
+    camera_bind_group: BindGroup,
+    image_bind_group_layout: BindGroupLayout,
+    // Keyed by the version of the image they were created for
+    image_bind_groups: HashMap<ImageId, (u32, BindGroup)>,
+    vertex_buffer: Option<Buffer>,
+    index_buffer: Option<Buffer>,
+    batches: Vec<SpriteBatch>,
+}
+
+impl SpritePass {
+    pub fn new(device: &Device) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("sprite.wgsl"));
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Sprite Camera Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let image_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Sprite Image Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sprite Camera Buffer"),
+            size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Sprite Camera Bind Group"),
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sprite Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout, &image_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Sprite Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_sprite",
+                buffers: &[SpriteVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_sprite",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: PostProcessor::HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            // Flipped sprites are wound the other way
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            camera_buffer,
+            camera_bind_group,
+            image_bind_group_layout,
+            image_bind_groups: HashMap::new(),
+            vertex_buffer: None,
+            index_buffer: None,
+            batches: vec![],
+        }
+    }
+
+    fn prepare_images(&mut self, context: &RenderContext<'_>) {
+        self.image_bind_groups
+            .retain(|id, _| context.image(*id).is_some());
+        for batch in self.batches.iter() {
+            let Some(image) = context.image(batch.image) else {
+                continue;
+            };
+            let current = self
+                .image_bind_groups
+                .get(&batch.image)
+                .is_some_and(|(version, _)| *version == image.version);
+            if current {
+                continue;
+            }
+            let bind_group = context
+                .device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Sprite Image Bind Group"),
+                    layout: &self.image_bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(image.view()),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(image.sampler()),
+                        },
+                    ],
+                });
+            self.image_bind_groups
+                .insert(batch.image, (image.version, bind_group));
+        }
+    }
+}
+
+impl RenderPass for SpritePass {
+    fn name(&self) -> &str {
+        "sprites"
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        builder.read(SCENE_COLOR).write(SCENE_COLOR);
+    }
+
+    fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        // Sprites whose image isn't uploaded yet are skipped
+        let mut sprites = world
+            .try_get_spawned::<Sprite>()
+            .map(|sprites| sprites.iter().map(|sprite| (**sprite).clone()).collect())
+            .unwrap_or_else(|_| vec![]);
+        if let Ok(animated) = world.try_get_spawned::<AnimatedSprite>() {
+            sprites.extend(animated.iter().map(|animated| animated.sprite.clone()));
+        }
+        sprites.retain(|sprite| context.image(sprite.image).is_some());
+        self.batches = batch_sprites(&mut sprites);
+        self.prepare_images(context);
+        if sprites.is_empty() {
+            return;
+        }
+
+        let (width, height) = (context.size.width as f32, context.size.height as f32);
+        let settings = world
+            .try_get_resource::<RendererSettings>()
+            .map(|settings| settings.sprites.clone())
+            .unwrap_or_default();
+        let view_projection =
+            settings.view_projection(window_camera(world).as_ref(), width, height);
+        context.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[CameraUniform::new(view_projection)]),
+        );
+
+        let vertices = sprites
+            .iter()
+            .flat_map(|sprite| {
+                let size = context
+                    .image(sprite.image)
+                    .map(|image| image.size())
+                    .unwrap_or((1, 1));
+                sprite.vertices(size)
+            })
+            .collect::<Vec<_>>();
+        let indices = (0..sprites.len() as u32)
+            .flat_map(|sprite| [0, 1, 2, 0, 2, 3].map(|corner| sprite * 4 + corner))
+            .collect::<Vec<_>>();
+        write_buffer(
+            context.device,
+            context.queue,
+            &mut self.vertex_buffer,
+            "Sprite Vertex Buffer",
+            wgpu::BufferUsages::VERTEX,
+            bytemuck::cast_slice(&vertices),
+        );
+        write_buffer(
+            context.device,
+            context.queue,
+            &mut self.index_buffer,
+            "Sprite Index Buffer",
+            wgpu::BufferUsages::INDEX,
+            bytemuck::cast_slice(&indices),
+        );
+    }
+
+    fn render(
+        &mut self,
+        _context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    ) {
+        let (Some(view), Some(vertex_buffer), Some(index_buffer)) = (
+            resources.texture_view(SCENE_COLOR),
+            self.vertex_buffer.as_ref(),
+            self.index_buffer.as_ref(),
+        ) else {
+            return;
+        };
+        if self.batches.is_empty() {
+            return;
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Sprite Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        for batch in self.batches.iter() {
+            let Some((_, bind_group)) = self.image_bind_groups.get(&batch.image) else {
+                continue;
+            };
+            render_pass.set_bind_group(1, bind_group, &[]);
+            render_pass.draw_indexed(batch.sprites.start * 6..batch.sprites.end * 6, 0, 0..1);
+        }
+    }
+}
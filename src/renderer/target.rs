@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use starry_ecs::resources::Resource;
+use wgpu::{
+    Device, Extent3d, Sampler, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureUsages, TextureView, TextureViewDescriptor,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RenderTargetId(u32);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderTargetDescriptor {
+    pub label: Option<String>,
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    pub depth_format: Option<TextureFormat>,
+}
+
+impl RenderTargetDescriptor {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            label: None,
+            width,
+            height,
+            format: TextureFormat::Rgba8UnormSrgb,
+            depth_format: Some(TextureFormat::Depth32Float),
+        }
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = Some(String::from(label));
+        self
+    }
+
+    pub fn with_format(mut self, format: TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_depth_format(mut self, depth_format: Option<TextureFormat>) -> Self {
+        self.depth_format = depth_format;
+        self
+    }
+}
+
+/// The render targets the renderer should keep allocated.
+///
+/// Descriptors can be added, changed and removed from any system,
+/// the renderer recreates the textures before the next frame.
+#[derive(Debug, Default, Clone)]
+pub struct RenderTargets {
+    next_id: u32,
+    descriptors: HashMap<RenderTargetId, RenderTargetDescriptor>,
+}
+
+impl RenderTargets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn create(&mut self, descriptor: RenderTargetDescriptor) -> RenderTargetId {
+        let id = RenderTargetId(self.next_id);
+        self.next_id += 1;
+        self.descriptors.insert(id, descriptor);
+        id
+    }
+
+    pub fn get(&self, id: RenderTargetId) -> Option<&RenderTargetDescriptor> {
+        self.descriptors.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: RenderTargetId) -> Option<&mut RenderTargetDescriptor> {
+        self.descriptors.get_mut(&id)
+    }
+
+    pub fn remove(&mut self, id: RenderTargetId) -> Option<RenderTargetDescriptor> {
+        self.descriptors.remove(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (RenderTargetId, &RenderTargetDescriptor)> {
+        self.descriptors
+            .iter()
+            .map(|(id, descriptor)| (*id, descriptor))
+    }
+}
+
+impl Resource for RenderTargets {}
+
+/// The textures behind a `RenderTargetDescriptor`.
+///
+/// The color texture can be sampled through `color_view` and `sampler`.
+#[derive(Debug)]
+pub struct RenderTarget {
+    descriptor: RenderTargetDescriptor,
+    color: Texture,
+    color_view: TextureView,
+    depth: Option<(Texture, TextureView)>,
+    sampler: Sampler,
+}
+
+impl RenderTarget {
+    pub fn new(device: &Device, descriptor: &RenderTargetDescriptor) -> Self {
+        let size = Extent3d {
+            width: descriptor.width.max(1),
+            height: descriptor.height.max(1),
+            depth_or_array_layers: 1,
+        };
+        let color = device.create_texture(&TextureDescriptor {
+            label: descriptor.label.as_deref(),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: descriptor.format,
+            usage: TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let color_view = color.create_view(&TextureViewDescriptor::default());
+        let depth = descriptor.depth_format.map(|format| {
+            let texture = device.create_texture(&TextureDescriptor {
+                label: Some("Render Target Depth"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&TextureViewDescriptor::default());
+            (texture, view)
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Render Target Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            descriptor: descriptor.clone(),
+            color,
+            color_view,
+            depth,
+            sampler,
+        }
+    }
+
+    pub fn descriptor(&self) -> &RenderTargetDescriptor {
+        &self.descriptor
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.descriptor.width, self.descriptor.height)
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.descriptor.width as f32 / self.descriptor.height.max(1) as f32
+    }
+
+    pub fn color_texture(&self) -> &Texture {
+        &self.color
+    }
+
+    pub fn color_view(&self) -> &TextureView {
+        &self.color_view
+    }
+
+    pub fn depth_view(&self) -> Option<&TextureView> {
+        self.depth.as_ref().map(|(_, view)| view)
+    }
+
+    pub fn sampler(&self) -> &Sampler {
+        &self.sampler
+    }
+}
@@ -0,0 +1,824 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use ab_glyph::{Font as _, FontArc, GlyphId, ScaleFont};
+use glam::{Mat4, Vec2, Vec3};
+use starry_ecs::component::Component;
+use starry_ecs::resources::Resource;
+use starry_ecs::World;
+use thiserror::Error;
+use wgpu::{
+    include_wgsl, BindGroup, Buffer, Color, CommandEncoder, Device, LoadOp, Operations, Queue,
+    RenderPassColorAttachment, RenderPipeline, Texture, TextureFormat,
+};
+
+use crate::commands::WorldExt;
+use crate::renderer::camera::{window_camera, CameraUniform};
+use crate::renderer::debug::DebugDraw;
+use crate::renderer::graph::{PassBuilder, RenderResources, SCENE_COLOR, SWAPCHAIN};
+use crate::renderer::pass::{write_buffer, RenderContext, RenderPass};
+use crate::renderer::post::PostProcessor;
+use crate::renderer::sprite::{Rect, SpriteVertex};
+
+#[derive(Error, Debug)]
+pub enum FontError {
+    #[error("Failed to read font: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid TrueType or OpenType font")]
+    Invalid(#[from] ab_glyph::InvalidFont),
+}
+
+/// A TrueType or OpenType font
+#[derive(Debug, Clone)]
+pub struct Font {
+    font: FontArc,
+}
+
+impl Font {
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, FontError> {
+        Ok(Self {
+            font: FontArc::try_from_vec(bytes)?,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Distance between two baselines at `size` pixels, before `TextStyle::line_height`
+    pub fn line_height(&self, size: f32) -> f32 {
+        let scaled = self.font.as_scaled(size);
+        scaled.height() + scaled.line_gap()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FontId(u32);
+
+/// The fonts text components can use
+#[derive(Debug, Default, Clone)]
+pub struct Fonts {
+    next_id: u32,
+    fonts: HashMap<FontId, Font>,
+}
+
+impl Fonts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, font: Font) -> FontId {
+        let id = FontId(self.next_id);
+        self.next_id += 1;
+        self.fonts.insert(id, font);
+        id
+    }
+
+    pub fn get(&self, id: FontId) -> Option<&Font> {
+        self.fonts.get(&id)
+    }
+
+    pub fn remove(&mut self, id: FontId) -> Option<Font> {
+        self.fonts.remove(&id)
+    }
+}
+
+impl Resource for Fonts {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextStyle {
+    pub font: FontId,
+    /// Height of the font in pixels
+    pub size: f32,
+    pub color: Color,
+    /// Multiplies the font's own line height
+    pub line_height: f32,
+    pub alignment: TextAlignment,
+    /// Lines are wrapped between words to fit, in pixels
+    pub max_width: Option<f32>,
+}
+
+impl TextStyle {
+    pub fn new(font: FontId, size: f32) -> Self {
+        Self {
+            font,
+            size,
+            color: Color::WHITE,
+            line_height: 1.0,
+            alignment: TextAlignment::Left,
+            max_width: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.max_width = Some(max_width);
+        self
+    }
+}
+
+/// Text drawn over everything else, `position` is the top left corner in pixels from the top left of the window
+#[derive(Debug, Clone)]
+pub struct ScreenText {
+    pub text: String,
+    pub style: TextStyle,
+    pub position: Vec2,
+}
+
+impl Component for ScreenText {}
+
+/// Text placed in the scene on the XY plane of `transform`, one pixel of the text is `scale` world units
+#[derive(Debug, Clone)]
+pub struct WorldText {
+    pub text: String,
+    pub style: TextStyle,
+    pub transform: Mat4,
+    pub scale: f32,
+}
+
+impl Component for WorldText {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaidOutGlyph {
+    pub glyph: GlyphId,
+    pub character: char,
+    /// Start of the glyph on its baseline, in pixels from the top left of the text
+    pub position: Vec2,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<LaidOutGlyph>,
+    pub size: Vec2,
+    pub lines: usize,
+}
+
+/// Places each character of `text`, wrapping lines between words to fit `max_width`
+pub fn layout_text(
+    font: &Font,
+    text: &str,
+    size: f32,
+    line_height: f32,
+    alignment: TextAlignment,
+    max_width: Option<f32>,
+) -> TextLayout {
+    let scaled = font.font.as_scaled(size);
+    let advance = |previous: Option<GlyphId>, glyph: GlyphId| {
+        previous.map_or(0.0, |previous| scaled.kern(previous, glyph)) + scaled.h_advance(glyph)
+    };
+
+    let space_advance = scaled.h_advance(scaled.glyph_id(' '));
+
+    // Each line is a list of characters along with its width
+    let mut lines: Vec<(Vec<char>, f32)> = vec![];
+    for paragraph in text.split('\n') {
+        let mut line: Vec<char> = vec![];
+        let mut width = 0.0;
+        for word in paragraph.split_inclusive(' ') {
+            let (word_width, _) = word.chars().fold((0.0, None), |(total, previous), c| {
+                let glyph = scaled.glyph_id(c);
+                (total + advance(previous, glyph), Some(glyph))
+            });
+            let kerning = match (line.last(), word.chars().next()) {
+                (Some(last), Some(first)) => {
+                    scaled.kern(scaled.glyph_id(*last), scaled.glyph_id(first))
+                }
+                _ => 0.0,
+            };
+            // The trailing space of a word is allowed to overflow
+            let trailing = if word.ends_with(' ') {
+                space_advance
+            } else {
+                0.0
+            };
+            let overflows =
+                max_width.is_some_and(|max| width + kerning + word_width - trailing > max);
+            if overflows && !line.is_empty() {
+                let trimmed = trim_trailing_spaces(&mut line, width, space_advance);
+                lines.push((std::mem::take(&mut line), trimmed));
+                width = 0.0;
+            } else {
+                width += kerning;
+            }
+            line.extend(word.chars());
+            width += word_width;
+        }
+        let trimmed = trim_trailing_spaces(&mut line, width, space_advance);
+        lines.push((line, trimmed));
+    }
+
+    let line_advance = font.line_height(size) * line_height;
+    let widest = lines.iter().map(|(_, width)| *width).fold(0.0, f32::max);
+    let box_width = max_width.unwrap_or(widest);
+
+    let mut layout = TextLayout {
+        glyphs: vec![],
+        size: Vec2::new(widest, 0.0),
+        lines: lines.len(),
+    };
+    for (index, (line, width)) in lines.iter().enumerate() {
+        let mut x = match alignment {
+            TextAlignment::Left => 0.0,
+            TextAlignment::Center => (box_width - width) / 2.0,
+            TextAlignment::Right => box_width - width,
+        };
+        let baseline = scaled.ascent() + index as f32 * line_advance;
+        let mut previous = None;
+        for c in line.iter() {
+            let glyph = scaled.glyph_id(*c);
+            if let Some(previous) = previous {
+                x += scaled.kern(previous, glyph);
+            }
+            layout.glyphs.push(LaidOutGlyph {
+                glyph,
+                character: *c,
+                position: Vec2::new(x, baseline),
+                line: index,
+            });
+            x += scaled.h_advance(glyph);
+            previous = Some(glyph);
+        }
+    }
+    layout.size.y = scaled.ascent() - scaled.descent() + (lines.len() - 1) as f32 * line_advance;
+    layout
+}
+
+fn trim_trailing_spaces(line: &mut Vec<char>, mut width: f32, space_advance: f32) -> f32 {
+    while line.last() == Some(&' ') {
+        line.pop();
+        width -= space_advance;
+    }
+    width
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("The glyph cache texture is full")]
+pub struct GlyphCacheFull;
+
+/// Where a rasterized glyph is in the cache texture
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CachedGlyph {
+    /// Region of the cache texture, in pixels
+    pub region: Rect,
+    /// From the glyph's position on the baseline to the top left of its pixels
+    pub offset: Vec2,
+}
+
+/// Rasterized glyphs packed in rows of a single channel texture.
+///
+/// When the texture is full the whole cache is cleared.
+#[derive(Debug)]
+pub struct GlyphCache {
+    size: u32,
+    glyphs: HashMap<(FontId, GlyphId, u32), Option<CachedGlyph>>,
+    cursor: (u32, u32),
+    row_height: u32,
+    // Pixels waiting to be written to the texture
+    pending: Vec<(Rect, Vec<u8>)>,
+}
+
+impl GlyphCache {
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            row_height: 0,
+            pending: vec![],
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn clear(&mut self) {
+        self.glyphs.clear();
+        self.cursor = (0, 0);
+        self.row_height = 0;
+        self.pending.clear();
+    }
+
+    /// Rasterizes the glyph the first time it's used.
+    ///
+    /// Returns `Ok(None)` for glyphs without pixels, like spaces.
+    pub fn get_or_insert(
+        &mut self,
+        font_id: FontId,
+        font: &Font,
+        glyph: GlyphId,
+        size: f32,
+    ) -> Result<Option<CachedGlyph>, GlyphCacheFull> {
+        // Sizes are told apart down to a tenth of a pixel
+        let key = (font_id, glyph, (size * 10.0).round() as u32);
+        if let Some(cached) = self.glyphs.get(&key) {
+            return Ok(*cached);
+        }
+
+        let Some(outline) = font.font.outline_glyph(glyph.with_scale(size)) else {
+            self.glyphs.insert(key, None);
+            return Ok(None);
+        };
+        let bounds = outline.px_bounds();
+        let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+        if width == 0 || height == 0 {
+            self.glyphs.insert(key, None);
+            return Ok(None);
+        }
+
+        // A pixel of padding keeps filtering from picking up the neighbours
+        if self.cursor.0 + width + 1 > self.size {
+            self.cursor = (0, self.cursor.1 + self.row_height + 1);
+            self.row_height = 0;
+        }
+        if self.cursor.0 + width > self.size || self.cursor.1 + height > self.size {
+            return Err(GlyphCacheFull);
+        }
+        let (x, y) = self.cursor;
+        self.cursor.0 += width + 1;
+        self.row_height = self.row_height.max(height);
+
+        let mut pixels = vec![0; (width * height) as usize];
+        outline.draw(|px, py, coverage| {
+            if px < width && py < height {
+                pixels[(py * width + px) as usize] = (coverage * 255.0) as u8;
+            }
+        });
+        let region = Rect::new(x as f32, y as f32, width as f32, height as f32);
+        self.pending.push((region, pixels));
+
+        let cached = CachedGlyph {
+            region,
+            offset: Vec2::new(bounds.min.x, bounds.min.y),
+        };
+        self.glyphs.insert(key, Some(cached));
+        Ok(Some(cached))
+    }
+
+    fn take_pending(&mut self) -> Vec<(Rect, Vec<u8>)> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Where a `TextPass` draws
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextSpace {
+    /// `WorldText` drawn into the scene before post-processing
+    World,
+    /// `ScreenText` drawn on the window after post-processing
+    Screen,
+}
+
+const GLYPH_CACHE_SIZE: u32 = 1024;
+
+/// Draws `WorldText` or `ScreenText` components
+pub struct TextPass {
+    space: TextSpace,
+    pipeline: RenderPipeline,
+    cache: GlyphCache,
+    cache_texture: Texture,
+    camera_buffer: Buffer,
+    bind_group: BindGroup,
+    vertex_buffer: Option<Buffer>,
+    index_buffer: Option<Buffer>,
+    indices: u32,
+}
+
+impl TextPass {
+    pub fn new(device: &Device, space: TextSpace, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("text.wgsl"));
+        let cache_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Glyph Cache"),
+            size: wgpu::Extent3d {
+                width: GLYPH_CACHE_SIZE,
+                height: GLYPH_CACHE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let cache_view = cache_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Glyph Cache Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Text Camera Buffer"),
+            size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Text Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Text Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&cache_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_text",
+                buffers: &[SpriteVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_text",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: match space {
+                        TextSpace::World => PostProcessor::HDR_FORMAT,
+                        TextSpace::Screen => format,
+                    },
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            space,
+            pipeline,
+            cache: GlyphCache::new(GLYPH_CACHE_SIZE),
+            cache_texture,
+            camera_buffer,
+            bind_group,
+            vertex_buffer: None,
+            index_buffer: None,
+            indices: 0,
+        }
+    }
+
+    /// The texts of the frame, each with the transform from its layout's pixels to the world
+    fn collect_texts(&self, world: &World, size: Vec2) -> Vec<(String, TextStyle, Mat4)> {
+        match self.space {
+            TextSpace::World => world
+                .try_get_spawned::<WorldText>()
+                .map(|texts| {
+                    texts
+                        .iter()
+                        .map(|text| {
+                            // Layouts go down in Y while the world goes up
+                            let scale = Mat4::from_scale(Vec3::new(text.scale, -text.scale, 1.0));
+                            (
+                                text.text.clone(),
+                                text.style.clone(),
+                                text.transform * scale,
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            TextSpace::Screen => {
+                let mut texts: Vec<_> = world
+                    .try_get_spawned::<ScreenText>()
+                    .map(|texts| {
+                        texts
+                            .iter()
+                            .map(|text| {
+                                let translation = Mat4::from_translation(text.position.extend(0.0));
+                                (text.text.clone(), text.style.clone(), translation)
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                texts.extend(Self::debug_labels(world, size));
+                texts
+            }
+        }
+    }
+
+    /// `DebugDraw` labels moved to where their position is on the window
+    fn debug_labels(world: &World, size: Vec2) -> Vec<(String, TextStyle, Mat4)> {
+        let Ok(debug) = world.try_get_resource::<DebugDraw>() else {
+            return vec![];
+        };
+        let Some(font) = debug.font else {
+            return vec![];
+        };
+        let view_projection = window_camera(world)
+            .map(|camera| camera.view_projection(size.x / size.y.max(1.0)))
+            .unwrap_or(Mat4::IDENTITY);
+        debug
+            .labels()
+            .iter()
+            .filter_map(|label| {
+                let clip = view_projection * label.position.extend(1.0);
+                // Behind the camera
+                if clip.w <= 0.0 {
+                    return None;
+                }
+                let ndc = clip.truncate() / clip.w;
+                let pixel = Vec2::new((ndc.x + 1.0) / 2.0 * size.x, (1.0 - ndc.y) / 2.0 * size.y);
+                let style = TextStyle::new(font, debug.label_size).with_color(label.color);
+                Some((
+                    label.text.clone(),
+                    style,
+                    Mat4::from_translation(pixel.extend(0.0)),
+                ))
+            })
+            .collect()
+    }
+
+    /// Builds the quads of every glyph, or returns `None` if the glyph cache filled up
+    fn build_vertices(
+        &mut self,
+        fonts: &Fonts,
+        texts: &[(String, TextStyle, Mat4)],
+    ) -> Option<Vec<SpriteVertex>> {
+        let mut vertices = vec![];
+        let cache_size = self.cache.size() as f32;
+        for (text, style, transform) in texts {
+            let Some(font) = fonts.get(style.font) else {
+                continue;
+            };
+            let layout = layout_text(
+                font,
+                text,
+                style.size,
+                style.line_height,
+                style.alignment,
+                style.max_width,
+            );
+            let color = [
+                style.color.r as f32,
+                style.color.g as f32,
+                style.color.b as f32,
+                style.color.a as f32,
+            ];
+            for glyph in layout.glyphs.iter() {
+                let cached = self
+                    .cache
+                    .get_or_insert(style.font, font, glyph.glyph, style.size)
+                    .ok()?;
+                let Some(cached) = cached else {
+                    continue;
+                };
+                let min = glyph.position + cached.offset;
+                let max = min + cached.region.size();
+                let uv_min = cached.region.min / cache_size;
+                let uv_max = cached.region.max / cache_size;
+                let corners = [
+                    (Vec2::new(min.x, max.y), Vec2::new(uv_min.x, uv_max.y)),
+                    (max, uv_max),
+                    (Vec2::new(max.x, min.y), Vec2::new(uv_max.x, uv_min.y)),
+                    (min, uv_min),
+                ];
+                vertices.extend(corners.map(|(position, uv)| SpriteVertex {
+                    position: transform.transform_point3(position.extend(0.0)).to_array(),
+                    uv: uv.to_array(),
+                    color,
+                }));
+            }
+        }
+        Some(vertices)
+    }
+
+    fn upload_glyphs(&mut self, queue: &Queue) {
+        for (region, pixels) in self.cache.take_pending() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.cache_texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: region.min.x as u32,
+                        y: region.min.y as u32,
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &pixels,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(region.width() as u32),
+                    rows_per_image: None,
+                },
+                wgpu::Extent3d {
+                    width: region.width() as u32,
+                    height: region.height() as u32,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+    }
+
+    /// Lays out `texts`, each moved by its matrix into the space `view_projection` projects
+    pub(crate) fn prepare_texts(
+        &mut self,
+        context: &RenderContext<'_>,
+        fonts: &Fonts,
+        texts: &[(String, TextStyle, Mat4)],
+        view_projection: Mat4,
+    ) {
+        self.indices = 0;
+        if texts.is_empty() {
+            return;
+        }
+
+        let vertices = match self.build_vertices(fonts, texts) {
+            Some(vertices) => vertices,
+            None => {
+                self.cache.clear();
+                self.build_vertices(fonts, texts).unwrap_or_default()
+            }
+        };
+        self.upload_glyphs(context.queue);
+        if vertices.is_empty() {
+            return;
+        }
+
+        context.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[CameraUniform::new(view_projection)]),
+        );
+
+        let quads = vertices.len() as u32 / 4;
+        let indices = (0..quads)
+            .flat_map(|quad| [0, 1, 2, 0, 2, 3].map(|corner| quad * 4 + corner))
+            .collect::<Vec<_>>();
+        write_buffer(
+            context.device,
+            context.queue,
+            &mut self.vertex_buffer,
+            "Text Vertex Buffer",
+            wgpu::BufferUsages::VERTEX,
+            bytemuck::cast_slice(&vertices),
+        );
+        write_buffer(
+            context.device,
+            context.queue,
+            &mut self.index_buffer,
+            "Text Index Buffer",
+            wgpu::BufferUsages::INDEX,
+            bytemuck::cast_slice(&indices),
+        );
+        self.indices = indices.len() as u32;
+    }
+
+    /// Draws the texts of the last `prepare_texts` into a render pass that already began
+    pub(crate) fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        let (Some(vertex_buffer), Some(index_buffer)) =
+            (self.vertex_buffer.as_ref(), self.index_buffer.as_ref())
+        else {
+            return;
+        };
+        if self.indices == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+        render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.indices, 0, 0..1);
+    }
+}
+
+impl RenderPass for TextPass {
+    fn name(&self) -> &str {
+        match self.space {
+            TextSpace::World => "world_text",
+            TextSpace::Screen => "screen_text",
+        }
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        let slot = match self.space {
+            TextSpace::World => SCENE_COLOR,
+            TextSpace::Screen => SWAPCHAIN,
+        };
+        builder.read(slot).write(slot);
+    }
+
+    fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        self.indices = 0;
+        let (width, height) = (context.size.width as f32, context.size.height as f32);
+        let texts = self.collect_texts(world, Vec2::new(width, height));
+        let Ok(fonts) = world.try_get_resource::<Fonts>() else {
+            return;
+        };
+        let view_projection = match self.space {
+            TextSpace::World => match window_camera(world) {
+                Some(camera) => camera.view_projection(width / height.max(1.0)),
+                None => Mat4::IDENTITY,
+            },
+            TextSpace::Screen => Mat4::orthographic_rh(0.0, width, height, 0.0, -1.0, 1.0),
+        };
+        self.prepare_texts(context, &fonts, &texts, view_projection);
+    }
+
+    fn render(
+        &mut self,
+        _context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    ) {
+        let slot = match self.space {
+            TextSpace::World => SCENE_COLOR,
+            TextSpace::Screen => SWAPCHAIN,
+        };
+        let Some(view) = resources.texture_view(slot) else {
+            return;
+        };
+        if self.indices == 0 {
+            return;
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Text Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        self.draw(&mut render_pass);
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use wgpu::{Buffer, BufferAsyncError, CommandEncoder, Device, QuerySet, Queue};
+
+// Timestamps are resolved as ticks of 8 bytes each
+const TIMESTAMP_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+struct Readback {
+    passes: Vec<String>,
+    submitted: Instant,
+}
+
+/// Times every render pass with timestamp queries written around it.
+///
+/// Results are read back without waiting on the GPU, so while a readback is in
+/// flight the following frames aren't timed.
+pub(crate) struct GpuTimer {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    readback_buffer: Buffer,
+    capacity: u32,
+    period: f32,
+    in_flight: Option<Readback>,
+    // Set by the map callback, an error means the buffer never got mapped
+    mapped: Arc<Mutex<Option<Result<(), BufferAsyncError>>>>,
+}
+
+impl GpuTimer {
+    /// `None` when the device can't write timestamps
+    pub(crate) fn new(device: &Device, queue: &Queue, passes: u32) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let capacity = (passes * 2).max(2);
+        let (query_set, resolve_buffer, readback_buffer) = Self::allocate(device, capacity);
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            capacity,
+            period: queue.get_timestamp_period(),
+            in_flight: None,
+            mapped: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    fn allocate(device: &Device, capacity: u32) -> (QuerySet, Buffer, Buffer) {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Pass Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: capacity,
+        });
+        let size = capacity as u64 * TIMESTAMP_SIZE;
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        (query_set, resolve_buffer, readback_buffer)
+    }
+
+    /// The query set to time `passes` passes with, `None` while the last results are read back
+    pub(crate) fn begin(&mut self, device: &Device, passes: u32) -> Option<&QuerySet> {
+        if self.in_flight.is_some() || passes == 0 {
+            return None;
+        }
+        if passes * 2 > self.capacity {
+            self.capacity = passes * 2;
+            (self.query_set, self.resolve_buffer, self.readback_buffer) =
+                Self::allocate(device, self.capacity);
+        }
+        Some(&self.query_set)
+    }
+
+    /// Copies the timestamps of the passes to the readback buffer
+    pub(crate) fn resolve(&self, encoder: &mut CommandEncoder, passes: u32) {
+        encoder.resolve_query_set(&self.query_set, 0..passes * 2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            passes as u64 * 2 * TIMESTAMP_SIZE,
+        );
+    }
+
+    /// Starts reading back the timestamps once the frame was submitted
+    pub(crate) fn submitted(&mut self, passes: Vec<String>, submitted: Instant) {
+        let size = passes.len() as u64 * 2 * TIMESTAMP_SIZE;
+        let mapped = self.mapped.clone();
+        self.readback_buffer
+            .slice(..size)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                *mapped.lock().unwrap() = Some(result);
+            });
+        self.in_flight = Some(Readback { passes, submitted });
+    }
+
+    /// Returns the passes with when they started and how long they took, once they were read back
+    pub(crate) fn collect(&mut self, device: &Device) -> Option<Vec<(String, Instant, Duration)>> {
+        self.in_flight.as_ref()?;
+        device.poll(wgpu::Maintain::Poll);
+        let result = self.mapped.lock().unwrap().take()?;
+        let readback = self.in_flight.take()?;
+        if let Err(error) = result {
+            // Nothing is mapped, the next frame is timed again
+            tracing::warn!("Couldn't read back the pass timings: {}", error);
+            return None;
+        }
+
+        let size = readback.passes.len() as u64 * 2 * TIMESTAMP_SIZE;
+        let timestamps = {
+            let view = self.readback_buffer.slice(..size).get_mapped_range();
+            bytemuck::cast_slice::<u8, u64>(&view).to_vec()
+        };
+        self.readback_buffer.unmap();
+
+        let nanos = |ticks: u64| Duration::from_nanos((ticks as f64 * self.period as f64) as u64);
+        let first = timestamps.first().copied().unwrap_or_default();
+        // The GPU clock has no relation to the CPU's, the passes are placed after the submit
+        Some(
+            readback
+                .passes
+                .into_iter()
+                .zip(timestamps.chunks_exact(2))
+                .map(|(name, pair)| {
+                    let start = readback.submitted + nanos(pair[0].saturating_sub(first));
+                    (name, start, nanos(pair[1].saturating_sub(pair[0])))
+                })
+                .collect(),
+        )
+    }
+}
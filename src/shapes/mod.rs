@@ -1,5 +1,13 @@
+pub mod path;
+pub mod polygon;
+pub mod primitives;
 pub mod vertex;
 
+pub use crate::shapes::path::{
+    FillRule, LineCap, LineJoin, Path, PathCommand, PathError, StrokeStyle,
+};
+pub use crate::shapes::polygon::{Polygon, PolygonError};
+pub use crate::shapes::primitives::{Capsule, Cone, Cylinder, IcoSphere, Plane, Torus, UvSphere};
 pub use crate::shapes::vertex::Vertex;
 
 #[derive(Clone)]
@@ -16,18 +24,9 @@ impl Triangle {
     ) -> Self {
         Self {
             vertices: [
-                Vertex {
-                    position: p1.into(),
-                    color: color.into(),
-                },
-                Vertex {
-                    position: p2.into(),
-                    color: color.into(),
-                },
-                Vertex {
-                    position: p3.into(),
-                    color: color.into(),
-                },
+                Vertex::new(p1.into(), color.into()),
+                Vertex::new(p2.into(), color.into()),
+                Vertex::new(p3.into(), color.into()),
             ],
         }
     }
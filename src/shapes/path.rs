@@ -0,0 +1,723 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use glam::{Vec2, Vec3};
+use thiserror::Error;
+
+use crate::shapes::Vertex;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PathError {
+    #[error("an SVG path has to start with a move")]
+    ExpectedMoveTo,
+    #[error("unknown SVG path command {0:?} at {1}")]
+    UnknownCommand(char, usize),
+    #[error("expected a number at {0} in the SVG path")]
+    ExpectedNumber(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathCommand {
+    MoveTo(Vec2),
+    LineTo(Vec2),
+    QuadraticTo { control: Vec2, to: Vec2 },
+    CubicTo { first: Vec2, second: Vec2, to: Vec2 },
+    Close,
+}
+
+/// Which areas enclosed by a path are filled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// Everything the outline winds around, like SVG's default
+    #[default]
+    NonZero,
+    /// Areas enclosed an odd number of times, nested outlines alternate
+    EvenOdd,
+}
+
+impl FillRule {
+    fn inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Square,
+    Round,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Miters longer than this many times the width become bevels
+    pub miter_limit: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.0,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+}
+
+/// Pushes a triangle wound counter-clockwise, dropping degenerate ones
+fn push_triangle(triangles: &mut Vec<[Vec2; 3]>, a: Vec2, b: Vec2, c: Vec2) {
+    let area = (b - a).perp_dot(c - a);
+    if area > f32::EPSILON * 1e-3 {
+        triangles.push([a, b, c]);
+    } else if area < -f32::EPSILON * 1e-3 {
+        triangles.push([a, c, b]);
+    }
+}
+
+/// Where two edges cross strictly between their ends
+fn crossing(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> Option<Vec2> {
+    let denominator = (b - a).perp_dot(d - c);
+    if denominator == 0.0 {
+        return None;
+    }
+    let t = (c - a).perp_dot(d - c) / denominator;
+    let u = (c - a).perp_dot(b - a) / denominator;
+    (t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0).then(|| a + (b - a) * t)
+}
+
+/// A 2D vector path made of lines and Bezier curves, in the XY plane
+#[derive(Debug, Clone)]
+pub struct Path {
+    commands: Vec<PathCommand>,
+    start: Vec2,
+    current: Vec2,
+    /// How far flattened curves may stray from the real ones, in the path's units
+    pub tolerance: f32,
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Self {
+            commands: vec![],
+            start: Vec2::ZERO,
+            current: Vec2::ZERO,
+            tolerance: 0.01,
+        }
+    }
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance.abs().max(f32::EPSILON);
+        self
+    }
+
+    pub fn commands(&self) -> &[PathCommand] {
+        &self.commands
+    }
+
+    /// Starts a new subpath
+    pub fn move_to(mut self, to: Vec2) -> Self {
+        self.commands.push(PathCommand::MoveTo(to));
+        self.start = to;
+        self.current = to;
+        self
+    }
+
+    pub fn line_to(mut self, to: Vec2) -> Self {
+        self.commands.push(PathCommand::LineTo(to));
+        self.current = to;
+        self
+    }
+
+    pub fn quadratic_to(mut self, control: Vec2, to: Vec2) -> Self {
+        self.commands.push(PathCommand::QuadraticTo { control, to });
+        self.current = to;
+        self
+    }
+
+    pub fn cubic_to(mut self, first: Vec2, second: Vec2, to: Vec2) -> Self {
+        self.commands
+            .push(PathCommand::CubicTo { first, second, to });
+        self.current = to;
+        self
+    }
+
+    /// An elliptical arc like SVG's, `rotation` is in radians. Stored as cubic curves.
+    pub fn arc_to(
+        self,
+        radii: Vec2,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: Vec2,
+    ) -> Self {
+        let from = self.current;
+        let mut radii = radii.abs();
+        if from == to {
+            return self;
+        }
+        if radii.x <= f32::EPSILON || radii.y <= f32::EPSILON {
+            return self.line_to(to);
+        }
+
+        // The center parameterization from the SVG spec's implementation notes
+        let (sin, cos) = rotation.sin_cos();
+        let rotate = |v: Vec2| Vec2::new(cos * v.x - sin * v.y, sin * v.x + cos * v.y);
+        let half = (from - to) / 2.0;
+        let p = Vec2::new(cos * half.x + sin * half.y, cos * half.y - sin * half.x);
+        let lambda = (p / radii).length_squared();
+        if lambda > 1.0 {
+            radii *= lambda.sqrt();
+        }
+        let (rx2, ry2) = (radii.x * radii.x, radii.y * radii.y);
+        let numerator = rx2 * ry2 - rx2 * p.y * p.y - ry2 * p.x * p.x;
+        let denominator = rx2 * p.y * p.y + ry2 * p.x * p.x;
+        let mut k = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            k = -k;
+        }
+        let center_prime = Vec2::new(radii.x * p.y / radii.y, -radii.y * p.x / radii.x) * k;
+        let center = rotate(center_prime) + (from + to) / 2.0;
+        let u = (p - center_prime) / radii;
+        let v = (-p - center_prime) / radii;
+        let start = u.y.atan2(u.x);
+        let mut delta = u.perp_dot(v).atan2(u.dot(v));
+        if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        } else if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        }
+
+        let pieces = (delta.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = delta / pieces as f32;
+        let handle = 4.0 / 3.0 * (step / 4.0).tan();
+        let point = |angle: f32| center + rotate(Vec2::new(angle.cos(), angle.sin()) * radii);
+        let tangent = |angle: f32| rotate(Vec2::new(-angle.sin(), angle.cos()) * radii);
+        let mut path = self;
+        for piece in 0..pieces {
+            let a0 = start + step * piece as f32;
+            let a1 = a0 + step;
+            let end = if piece + 1 == pieces { to } else { point(a1) };
+            path = path.cubic_to(
+                point(a0) + tangent(a0) * handle,
+                end - tangent(a1) * handle,
+                end,
+            );
+        }
+        path
+    }
+
+    /// Closes the subpath with a line back to where it started
+    pub fn close(mut self) -> Self {
+        self.commands.push(PathCommand::Close);
+        self.current = self.start;
+        self
+    }
+
+    /// Parses the `d` attribute of an SVG `<path>`, all commands are supported
+    pub fn from_svg(data: &str) -> Result<Self, PathError> {
+        let mut tokens = Tokens { data, position: 0 };
+        let mut path = Path::new();
+        // The last control point for S and T, with the kind of curve it belongs to
+        let mut reflected: Option<(char, Vec2)> = None;
+        loop {
+            tokens.skip_separators();
+            let Some(command) = data[tokens.position..].chars().next() else {
+                break;
+            };
+            let position = tokens.position;
+            if path.commands.is_empty() && !matches!(command, 'M' | 'm') {
+                return Err(PathError::ExpectedMoveTo);
+            }
+            tokens.position += command.len_utf8();
+
+            let relative = command.is_ascii_lowercase();
+            let mut repeated = false;
+            loop {
+                let origin = if relative { path.current } else { Vec2::ZERO };
+                let mut control = None;
+                path = match command.to_ascii_uppercase() {
+                    // Pairs after the first one of a move are lines
+                    'M' if !repeated => path.move_to(origin + tokens.point()?),
+                    'M' | 'L' => path.line_to(origin + tokens.point()?),
+                    'H' => {
+                        let x = origin.x + tokens.number()?;
+                        let y = path.current.y;
+                        path.line_to(Vec2::new(x, y))
+                    }
+                    'V' => {
+                        let y = origin.y + tokens.number()?;
+                        let x = path.current.x;
+                        path.line_to(Vec2::new(x, y))
+                    }
+                    'C' => {
+                        let first = origin + tokens.point()?;
+                        let second = origin + tokens.point()?;
+                        control = Some(('C', second));
+                        path.cubic_to(first, second, origin + tokens.point()?)
+                    }
+                    'S' => {
+                        let first = match reflected {
+                            Some(('C', last)) => path.current * 2.0 - last,
+                            _ => path.current,
+                        };
+                        let second = origin + tokens.point()?;
+                        control = Some(('C', second));
+                        path.cubic_to(first, second, origin + tokens.point()?)
+                    }
+                    'Q' => {
+                        let point = origin + tokens.point()?;
+                        control = Some(('Q', point));
+                        path.quadratic_to(point, origin + tokens.point()?)
+                    }
+                    'T' => {
+                        let point = match reflected {
+                            Some(('Q', last)) => path.current * 2.0 - last,
+                            _ => path.current,
+                        };
+                        control = Some(('Q', point));
+                        path.quadratic_to(point, origin + tokens.point()?)
+                    }
+                    'A' => {
+                        let radii = tokens.point()?;
+                        let rotation = tokens.number()?.to_radians();
+                        let large_arc = tokens.flag()?;
+                        let sweep = tokens.flag()?;
+                        path.arc_to(radii, rotation, large_arc, sweep, origin + tokens.point()?)
+                    }
+                    'Z' => path.close(),
+                    _ => return Err(PathError::UnknownCommand(command, position)),
+                };
+                reflected = control;
+                repeated = true;
+                if command.eq_ignore_ascii_case(&'Z') || !tokens.has_number() {
+                    break;
+                }
+            }
+        }
+        Ok(path)
+    }
+
+    fn curve_segments(&self, length: f32) -> usize {
+        (length / self.tolerance).sqrt().ceil().clamp(1.0, 128.0) as usize
+    }
+
+    /// The subpaths as polylines, with whether they were closed
+    pub fn flatten(&self) -> Vec<(Vec<Vec2>, bool)> {
+        let mut subpaths = vec![];
+        let mut points: Vec<Vec2> = vec![];
+        let finish = |points: &mut Vec<Vec2>, closed: bool, subpaths: &mut Vec<_>| {
+            let mut subpath = std::mem::take(points);
+            subpath.dedup();
+            if closed && subpath.len() > 1 && subpath.first() == subpath.last() {
+                subpath.pop();
+            }
+            if subpath.len() > 1 {
+                subpaths.push((subpath, closed));
+            }
+        };
+
+        let mut current = Vec2::ZERO;
+        for command in self.commands.iter() {
+            if points.is_empty() && !matches!(command, PathCommand::MoveTo(_)) {
+                points.push(current);
+            }
+            match *command {
+                PathCommand::MoveTo(to) => {
+                    finish(&mut points, false, &mut subpaths);
+                    points.push(to);
+                    current = to;
+                }
+                PathCommand::LineTo(to) => {
+                    points.push(to);
+                    current = to;
+                }
+                PathCommand::QuadraticTo { control, to } => {
+                    let segments =
+                        self.curve_segments((current - control * 2.0 + to).length() / 4.0);
+                    for segment in 1..=segments {
+                        let t = segment as f32 / segments as f32;
+                        let s = 1.0 - t;
+                        points.push(current * s * s + control * 2.0 * s * t + to * t * t);
+                    }
+                    current = to;
+                }
+                PathCommand::CubicTo { first, second, to } => {
+                    let bend = (current - first * 2.0 + second)
+                        .length()
+                        .max((first - second * 2.0 + to).length());
+                    let segments = self.curve_segments(bend * 0.75);
+                    for segment in 1..=segments {
+                        let t = segment as f32 / segments as f32;
+                        let s = 1.0 - t;
+                        points.push(
+                            current * s * s * s
+                                + first * 3.0 * s * s * t
+                                + second * 3.0 * s * t * t
+                                + to * t * t * t,
+                        );
+                    }
+                    current = to;
+                }
+                PathCommand::Close => {
+                    current = points[0];
+                    finish(&mut points, true, &mut subpaths);
+                }
+            }
+        }
+        finish(&mut points, false, &mut subpaths);
+        subpaths
+    }
+
+    /// Counter-clockwise triangles covering the inside of the path, every subpath is closed.
+    ///
+    /// The area is cut into horizontal slabs at every vertex and crossing of edges,
+    /// so self-intersecting outlines are filled as the rule says.
+    pub fn fill(&self, rule: FillRule) -> Vec<[Vec2; 3]> {
+        let edges = self
+            .flatten()
+            .iter()
+            .flat_map(|(points, _)| {
+                points
+                    .iter()
+                    .zip(points.iter().cycle().skip(1))
+                    .filter(|(a, b)| a.y != b.y)
+                    .map(|(a, b)| if a.y < b.y { (*a, *b, 1) } else { (*b, *a, -1) })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut stops = edges
+            .iter()
+            .flat_map(|(a, b, _)| [a.y, b.y])
+            .collect::<Vec<_>>();
+        for (i, (a, b, _)) in edges.iter().enumerate() {
+            for (c, d, _) in edges[i + 1..].iter() {
+                stops.extend(crossing(*a, *b, *c, *d).map(|point| point.y));
+            }
+        }
+        stops.sort_by(f32::total_cmp);
+        stops.dedup();
+
+        let mut triangles = vec![];
+        for slab in stops.windows(2) {
+            let (bottom, top) = (slab[0], slab[1]);
+            let middle = (bottom + top) / 2.0;
+            if middle <= bottom || middle >= top {
+                continue;
+            }
+            let mut spans = edges
+                .iter()
+                .filter(|(a, b, _)| a.y < middle && b.y > middle)
+                .map(|(a, b, winding)| {
+                    let x = |y: f32| a.x + (b.x - a.x) * (y - a.y) / (b.y - a.y);
+                    (x(bottom), x(middle), x(top), *winding)
+                })
+                .collect::<Vec<_>>();
+            spans.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            let mut winding = 0;
+            let mut left = None;
+            for (x_bottom, _, x_top, direction) in spans {
+                let was_inside = rule.inside(winding);
+                winding += direction;
+                match (was_inside, rule.inside(winding), left) {
+                    (false, true, _) => left = Some((x_bottom, x_top)),
+                    (true, false, Some((left_bottom, left_top))) => {
+                        let corners = [
+                            Vec2::new(left_bottom, bottom),
+                            Vec2::new(x_bottom, bottom),
+                            Vec2::new(x_top, top),
+                            Vec2::new(left_top, top),
+                        ];
+                        push_triangle(&mut triangles, corners[0], corners[1], corners[2]);
+                        push_triangle(&mut triangles, corners[0], corners[2], corners[3]);
+                        left = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        triangles
+    }
+
+    /// A fan around `center` starting at `center + from`, turning by `angle`
+    fn fan(&self, triangles: &mut Vec<[Vec2; 3]>, center: Vec2, from: Vec2, angle: f32) {
+        let radius = from.length();
+        let step = 2.0 * (1.0 - (self.tolerance / radius).min(1.0)).acos();
+        let segments = (angle.abs() / step.max(0.01)).ceil().clamp(1.0, 64.0) as usize;
+        let mut previous = center + from;
+        for segment in 1..=segments {
+            let (sin, cos) = (angle * segment as f32 / segments as f32).sin_cos();
+            let next = center + Vec2::new(cos * from.x - sin * from.y, sin * from.x + cos * from.y);
+            push_triangle(triangles, center, previous, next);
+            previous = next;
+        }
+    }
+
+    fn join(
+        &self,
+        triangles: &mut Vec<[Vec2; 3]>,
+        point: Vec2,
+        incoming: Vec2,
+        outgoing: Vec2,
+        half: f32,
+        style: &StrokeStyle,
+    ) {
+        let turn = incoming.perp_dot(outgoing);
+        if turn.abs() <= 1e-6 && incoming.dot(outgoing) > 0.0 {
+            return;
+        }
+        // The gap to fill is on the outside of the turn
+        let side = if turn > 0.0 { -half } else { half };
+        let (from, to) = (incoming.perp() * side, outgoing.perp() * side);
+        match style.join {
+            LineJoin::Bevel => push_triangle(triangles, point, point + from, point + to),
+            LineJoin::Miter => {
+                let direction = (from + to).normalize_or_zero();
+                let cos = direction.dot(from / half.abs());
+                if cos > 0.0 && 1.0 / cos <= style.miter_limit {
+                    let tip = point + direction * half.abs() / cos;
+                    push_triangle(triangles, point, point + from, tip);
+                    push_triangle(triangles, point, tip, point + to);
+                } else {
+                    push_triangle(triangles, point, point + from, point + to);
+                }
+            }
+            LineJoin::Round => self.fan(
+                triangles,
+                point,
+                from,
+                from.perp_dot(to).atan2(from.dot(to)),
+            ),
+        }
+    }
+
+    /// Caps the end at `point` of a line going along `direction`
+    fn cap(
+        &self,
+        triangles: &mut Vec<[Vec2; 3]>,
+        point: Vec2,
+        direction: Vec2,
+        half: f32,
+        cap: LineCap,
+    ) {
+        let side = direction.perp() * half;
+        match cap {
+            LineCap::Butt => {}
+            LineCap::Square => {
+                let forward = direction * half;
+                push_triangle(
+                    triangles,
+                    point - side,
+                    point - side + forward,
+                    point + side + forward,
+                );
+                push_triangle(
+                    triangles,
+                    point - side,
+                    point + side + forward,
+                    point + side,
+                );
+            }
+            LineCap::Round => self.fan(triangles, point, side, -PI),
+        }
+    }
+
+    /// Counter-clockwise triangles covering the outline of the path.
+    ///
+    /// Segments, joins and caps overlap, so translucent strokes look darker at joins.
+    pub fn stroke(&self, style: &StrokeStyle) -> Vec<[Vec2; 3]> {
+        let half = style.width.abs() / 2.0;
+        let mut triangles = vec![];
+        if half <= 0.0 {
+            return triangles;
+        }
+        for (points, closed) in self.flatten() {
+            let count = points.len();
+            let next = |i: usize| points[(i + 1) % count];
+            let segments = if closed { count } else { count - 1 };
+            let pairs = points.iter().zip(points.iter().cycle().skip(1));
+            for (a, b) in pairs.take(segments).map(|(a, b)| (*a, *b)) {
+                let side = (b - a).normalize().perp() * half;
+                push_triangle(&mut triangles, a - side, b - side, b + side);
+                push_triangle(&mut triangles, a - side, b + side, a + side);
+            }
+
+            let joins = if closed { 0..count } else { 1..count - 1 };
+            for i in joins {
+                let previous = points[(i + count - 1) % count];
+                let incoming = (points[i] - previous).normalize();
+                let outgoing = (next(i) - points[i]).normalize();
+                self.join(&mut triangles, points[i], incoming, outgoing, half, style);
+            }
+
+            if !closed {
+                let start = (points[0] - points[1]).normalize();
+                let end = (points[count - 1] - points[count - 2]).normalize();
+                self.cap(&mut triangles, points[0], start, half, style.cap);
+                self.cap(&mut triangles, points[count - 1], end, half, style.cap);
+            }
+        }
+        triangles
+    }
+
+    /// Flat triangles facing +Z, texture coordinates spanning their bounds
+    fn into_vertices(triangles: Vec<[Vec2; 3]>, color: (f32, f32, f32, f32)) -> (Vec<Vertex>, u32) {
+        let (min, max) = triangles.iter().flatten().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), point| (min.min(*point), max.max(*point)),
+        );
+        let size = (max - min).max(Vec2::splat(f32::EPSILON));
+        let (r, g, b, a) = color;
+        let vertices = triangles
+            .into_iter()
+            .flatten()
+            .map(|point| {
+                let uv = (point - min) / size;
+                Vertex {
+                    position: point.extend(0.0).extend(1.0).to_array(),
+                    color: [r, g, b, a],
+                    normal: Vec3::Z.to_array(),
+                    uv: [uv.x, 1.0 - uv.y],
+                }
+            })
+            .collect::<Vec<_>>();
+        let count = vertices.len() as u32;
+        (vertices, count)
+    }
+
+    pub fn fill_raw(&self, rule: FillRule, color: (f32, f32, f32, f32)) -> (Vec<Vertex>, u32) {
+        Self::into_vertices(self.fill(rule), color)
+    }
+
+    pub fn stroke_raw(
+        &self,
+        style: &StrokeStyle,
+        color: (f32, f32, f32, f32),
+    ) -> (Vec<Vertex>, u32) {
+        Self::into_vertices(self.stroke(style), color)
+    }
+}
+
+/// Numbers and flags of SVG path data
+struct Tokens<'a> {
+    data: &'a str,
+    position: usize,
+}
+
+impl Tokens<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.data.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_separators(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r' | b',')) {
+            self.position += 1;
+        }
+    }
+
+    fn has_number(&mut self) -> bool {
+        self.skip_separators();
+        matches!(self.peek(), Some(b'0'..=b'9' | b'-' | b'+' | b'.'))
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    fn number(&mut self) -> Result<f32, PathError> {
+        self.skip_separators();
+        let start = self.position;
+        if matches!(self.peek(), Some(b'-' | b'+')) {
+            self.position += 1;
+        }
+        let mut digits = self.digits();
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits += self.digits();
+        }
+        if digits == 0 {
+            self.position = start;
+            return Err(PathError::ExpectedNumber(start));
+        }
+        // An exponent, unless the `e` starts something else
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mantissa = self.position;
+            self.position += 1;
+            if matches!(self.peek(), Some(b'-' | b'+')) {
+                self.position += 1;
+            }
+            if self.digits() == 0 {
+                self.position = mantissa;
+            }
+        }
+        self.data[start..self.position]
+            .parse()
+            .map_err(|_| PathError::ExpectedNumber(start))
+    }
+
+    /// Flags are single digits that don't need separators, like in `a1 1 0 01.5.5`
+    fn flag(&mut self) -> Result<bool, PathError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(PathError::ExpectedNumber(self.position)),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+
+    fn point(&mut self) -> Result<Vec2, PathError> {
+        Ok(Vec2::new(self.number()?, self.number()?))
+    }
+}
@@ -0,0 +1,281 @@
+use glam::{Vec2, Vec3};
+use thiserror::Error;
+
+use crate::shapes::Vertex;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PolygonError {
+    #[error("a polygon needs at least 3 points, got {0}")]
+    TooFewPoints(usize),
+    #[error("the outline or a hole has no area")]
+    NoArea,
+    #[error("couldn't triangulate the polygon, its outline may intersect itself or a hole")]
+    Triangulation,
+}
+
+fn signed_area(points: &[Vec2]) -> f32 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum::<f32>()
+        / 2.0
+}
+
+fn segments_cross(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let side = |p: Vec2, q: Vec2, r: Vec2| (q - p).perp_dot(r - p);
+    let (d1, d2) = (side(c, d, a), side(c, d, b));
+    let (d3, d4) = (side(a, b, c), side(a, b, d));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+fn in_triangle(point: Vec2, a: Vec2, b: Vec2, c: Vec2) -> bool {
+    let epsilon = -1e-6;
+    (b - a).perp_dot(point - a) >= epsilon
+        && (c - b).perp_dot(point - b) >= epsilon
+        && (a - c).perp_dot(point - c) >= epsilon
+}
+
+/// Joins a hole to the outline with a pair of edges, making a single polygon
+fn bridge(outline: &[Vec2], hole: &[Vec2], others: &[Vec<Vec2>]) -> Vec<Vec2> {
+    let (start, from) = hole
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.x.total_cmp(&b.x))
+        .unwrap_or((0, hole[0]));
+
+    let edges = std::iter::once(outline)
+        .chain(std::iter::once(hole))
+        .chain(others.iter().map(Vec::as_slice))
+        .flat_map(|ring| ring.iter().zip(ring.iter().cycle().skip(1)))
+        .map(|(a, b)| (*a, *b))
+        .collect::<Vec<_>>();
+    let visible = |to: Vec2| {
+        edges.iter().all(|(a, b)| {
+            *a == to || *b == to || *a == from || *b == from || !segments_cross(from, to, *a, *b)
+        })
+    };
+    let mut candidates = (0..outline.len()).collect::<Vec<_>>();
+    candidates.sort_by(|a, b| {
+        from.distance_squared(outline[*a])
+            .total_cmp(&from.distance_squared(outline[*b]))
+    });
+    let target = candidates
+        .into_iter()
+        .find(|index| visible(outline[*index]))
+        .unwrap_or(0);
+
+    let mut merged = outline[..=target].to_vec();
+    merged.extend(hole[start..].iter().chain(hole[..start].iter()));
+    merged.push(from);
+    merged.extend_from_slice(&outline[target..]);
+    merged
+}
+
+/// Ear clipping of a counter-clockwise polygon, returns triangles as indices into `points`
+fn clip_ears(points: &[Vec2]) -> Result<Vec<[usize; 3]>, PolygonError> {
+    let mut remaining = (0..points.len()).collect::<Vec<_>>();
+    let mut triangles = vec![];
+    while remaining.len() > 3 {
+        let count = remaining.len();
+        let ear = (0..count).find_map(|i| {
+            let [prev, current, next] =
+                [(i + count - 1) % count, i, (i + 1) % count].map(|j| remaining[j]);
+            let [a, b, c] = [prev, current, next].map(|j| points[j]);
+            let turn = (b - a).perp_dot(c - b);
+            // Collinear points are dropped without a triangle
+            if turn.abs() <= 1e-9 * (b - a).length() * (c - b).length() {
+                return Some((i, None));
+            }
+            if turn < 0.0 {
+                return None;
+            }
+            let blocked = remaining.iter().any(|j| {
+                let point = points[*j];
+                point != a && point != b && point != c && in_triangle(point, a, b, c)
+            });
+            (!blocked).then_some((i, Some([prev, current, next])))
+        });
+        let Some((i, triangle)) = ear else {
+            return Err(PolygonError::Triangulation);
+        };
+        triangles.extend(triangle);
+        remaining.remove(i);
+    }
+    if let [a, b, c] = remaining[..] {
+        if (points[b] - points[a]).perp_dot(points[c] - points[a]) > 0.0 {
+            triangles.push([a, b, c]);
+        }
+    }
+    Ok(triangles)
+}
+
+/// A 2D outline on the XY plane with optional holes
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    pub outline: Vec<Vec2>,
+    pub holes: Vec<Vec<Vec2>>,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Polygon {
+    /// The outline can be wound either way
+    pub fn new(outline: Vec<Vec2>) -> Self {
+        Self {
+            outline,
+            holes: vec![],
+            color: (1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    /// A regular polygon centered on the origin with its first corner on +X
+    pub fn regular(sides: u32, radius: f32) -> Self {
+        let sides = sides.max(3);
+        Self::new(
+            (0..sides)
+                .map(|side| {
+                    let angle = side as f32 / sides as f32 * std::f32::consts::TAU;
+                    Vec2::new(angle.cos(), angle.sin()) * radius
+                })
+                .collect(),
+        )
+    }
+
+    pub fn with_hole(mut self, hole: Vec<Vec2>) -> Self {
+        self.holes.push(hole);
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// The outline counter-clockwise and the holes clockwise
+    fn rings(&self) -> Result<(Vec<Vec2>, Vec<Vec<Vec2>>), PolygonError> {
+        let orient = |ring: &[Vec2], counter_clockwise: bool| {
+            if ring.len() < 3 {
+                return Err(PolygonError::TooFewPoints(ring.len()));
+            }
+            let area = signed_area(ring);
+            if area.abs() <= f32::EPSILON {
+                return Err(PolygonError::NoArea);
+            }
+            let mut ring = ring.to_vec();
+            if (area > 0.0) != counter_clockwise {
+                ring.reverse();
+            }
+            Ok(ring)
+        };
+        let outline = orient(&self.outline, true)?;
+        let holes = self
+            .holes
+            .iter()
+            .map(|hole| orient(hole, false))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((outline, holes))
+    }
+
+    /// Counter-clockwise triangles covering the polygon but not its holes
+    pub fn triangulate(&self) -> Result<Vec<[Vec2; 3]>, PolygonError> {
+        let (mut outline, mut holes) = self.rings()?;
+        // Holes further right are bridged first so later bridges can't cross them
+        holes.sort_by(|a, b| {
+            let right =
+                |ring: &Vec<Vec2>| ring.iter().map(|point| point.x).fold(f32::MIN, f32::max);
+            right(b).total_cmp(&right(a))
+        });
+        while !holes.is_empty() {
+            let hole = holes.remove(0);
+            outline = bridge(&outline, &hole, &holes);
+        }
+        Ok(clip_ears(&outline)?
+            .into_iter()
+            .map(|triangle| triangle.map(|index| outline[index]))
+            .collect())
+    }
+
+    fn bounds(&self) -> (Vec2, Vec2) {
+        self.outline.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), point| (min.min(*point), max.max(*point)),
+        )
+    }
+
+    /// Texture coordinates spanning the bounds of the outline
+    fn uv(&self, point: Vec2) -> [f32; 2] {
+        let (min, max) = self.bounds();
+        let size = (max - min).max(Vec2::splat(f32::EPSILON));
+        let uv = (point - min) / size;
+        [uv.x, 1.0 - uv.y]
+    }
+
+    fn vertex(&self, position: Vec3, normal: Vec3, uv: [f32; 2]) -> Vertex {
+        let (r, g, b, a) = self.color;
+        Vertex {
+            position: position.extend(1.0).to_array(),
+            color: [r, g, b, a],
+            normal: normal.to_array(),
+            uv,
+        }
+    }
+
+    /// A flat shape facing +Z
+    pub fn into_raw(self) -> Result<(Vec<Vertex>, u32), PolygonError> {
+        let vertices = self
+            .triangulate()?
+            .into_iter()
+            .flatten()
+            .map(|point| self.vertex(point.extend(0.0), Vec3::Z, self.uv(point)))
+            .collect::<Vec<_>>();
+        let count = vertices.len() as u32;
+        Ok((vertices, count))
+    }
+
+    /// A prism from `-depth / 2` to `depth / 2` along Z, with caps and flat sides
+    pub fn extrude(self, depth: f32) -> Result<(Vec<Vertex>, u32), PolygonError> {
+        let half = depth.abs() / 2.0;
+        let triangles = self.triangulate()?;
+        let mut vertices = vec![];
+        for [a, b, c] in triangles {
+            for point in [a, b, c] {
+                vertices.push(self.vertex(point.extend(half), Vec3::Z, self.uv(point)));
+            }
+            for point in [a, c, b] {
+                let [u, v] = self.uv(point);
+                vertices.push(self.vertex(point.extend(-half), Vec3::NEG_Z, [1.0 - u, v]));
+            }
+        }
+
+        // Outline counter-clockwise and holes clockwise both have the solid on their left
+        let (outline, holes) = self.rings()?;
+        for ring in std::iter::once(&outline).chain(holes.iter()) {
+            let mut distance = 0.0;
+            let perimeter = ring
+                .iter()
+                .zip(ring.iter().cycle().skip(1))
+                .map(|(a, b)| a.distance(*b))
+                .sum::<f32>()
+                .max(f32::EPSILON);
+            for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                let edge = *b - *a;
+                let normal = Vec3::new(edge.y, -edge.x, 0.0).normalize_or_zero();
+                let (u0, u1) = (distance / perimeter, (distance + edge.length()) / perimeter);
+                distance += edge.length();
+                let corners = [
+                    (a.extend(-half), [u0, 1.0]),
+                    (b.extend(-half), [u1, 1.0]),
+                    (b.extend(half), [u1, 0.0]),
+                    (a.extend(half), [u0, 0.0]),
+                ];
+                for corner in [0, 1, 2, 0, 2, 3] {
+                    let (position, uv) = corners[corner];
+                    vertices.push(self.vertex(position, normal, uv));
+                }
+            }
+        }
+        let count = vertices.len() as u32;
+        Ok((vertices, count))
+    }
+}
@@ -0,0 +1,557 @@
+use std::collections::HashMap;
+use std::f32::consts::{PI, TAU};
+
+use glam::{Vec2, Vec3};
+
+use crate::shapes::Vertex;
+
+const WHITE: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 1.0);
+
+/// Indexed triangles with normals and texture coordinates
+#[derive(Debug, Clone, Default)]
+struct Geometry {
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+    indices: Vec<u32>,
+}
+
+impl Geometry {
+    fn vertex(&mut self, position: Vec3, normal: Vec3, uv: Vec2) -> u32 {
+        self.positions.push(position);
+        self.normals.push(normal.normalize_or_zero());
+        self.uvs.push(uv);
+        self.positions.len() as u32 - 1
+    }
+
+    /// Adds a triangle facing the way its normals point, skipping degenerate ones
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        let [pa, pb, pc] = [a, b, c].map(|index| self.positions[index as usize]);
+        let (ab, ac) = (pb - pa, pc - pa);
+        let face = ab.cross(ac);
+        // Collapsed triangles, like the ones touching the poles of a sphere
+        if face.length_squared() <= 1e-10 * ab.length_squared() * ac.length_squared() {
+            return;
+        }
+        let normal = [a, b, c]
+            .map(|index| self.normals[index as usize])
+            .iter()
+            .sum::<Vec3>();
+        if face.dot(normal) < 0.0 {
+            self.indices.extend([a, c, b]);
+        } else {
+            self.indices.extend([a, b, c]);
+        }
+    }
+
+    /// Triangulates a grid of `columns + 1` by `rows + 1` vertices starting at `first`
+    fn grid(&mut self, first: u32, columns: u32, rows: u32) {
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = first + row * (columns + 1) + column;
+                let b = a + columns + 1;
+                self.triangle(a, b, a + 1);
+                self.triangle(a + 1, b, b + 1);
+            }
+        }
+    }
+
+    /// Spins a profile of `(radius, height, normal in the radius/height plane, v)` around Y
+    fn revolve(&mut self, profile: &[(f32, f32, Vec2, f32)], segments: u32) {
+        let first = self.positions.len() as u32;
+        for &(radius, height, normal, v) in profile {
+            for segment in 0..=segments {
+                let u = segment as f32 / segments as f32;
+                let (sin, cos) = (u * TAU).sin_cos();
+                self.vertex(
+                    Vec3::new(radius * cos, height, -radius * sin),
+                    Vec3::new(normal.x * cos, normal.y, -normal.x * sin),
+                    Vec2::new(u, v),
+                );
+            }
+        }
+        self.grid(first, segments, profile.len() as u32 - 1);
+    }
+
+    /// A flat disc at `height` facing up or down
+    fn disc(&mut self, radius: f32, height: f32, segments: u32, up: bool) {
+        let normal = if up { Vec3::Y } else { Vec3::NEG_Y };
+        let center = self.vertex(Vec3::Y * height, normal, Vec2::splat(0.5));
+        let first = self.positions.len() as u32;
+        for segment in 0..=segments {
+            let (sin, cos) = (segment as f32 / segments as f32 * TAU).sin_cos();
+            self.vertex(
+                Vec3::new(radius * cos, height, -radius * sin),
+                normal,
+                Vec2::new(0.5 + cos * 0.5, 0.5 - sin * 0.5),
+            );
+        }
+        for segment in 0..segments {
+            self.triangle(center, first + segment, first + segment + 1);
+        }
+    }
+
+    /// Unrolls the triangles into the vertex list meshes are drawn from
+    fn into_raw(self, (r, g, b, a): (f32, f32, f32, f32)) -> (Vec<Vertex>, u32) {
+        let vertices = self
+            .indices
+            .iter()
+            .map(|index| {
+                let index = *index as usize;
+                Vertex {
+                    position: self.positions[index].extend(1.0).to_array(),
+                    color: [r, g, b, a],
+                    normal: self.normals[index].to_array(),
+                    uv: self.uvs[index].to_array(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let count = vertices.len() as u32;
+        (vertices, count)
+    }
+}
+
+/// Points of an arc between two angles measured from the top, as `(radius, height, normal)`
+fn arc(radius: f32, center: f32, from: f32, to: f32, steps: u32) -> Vec<(f32, f32, Vec2)> {
+    (0..=steps)
+        .map(|step| {
+            let angle = from + (to - from) * step as f32 / steps as f32;
+            let normal = Vec2::new(angle.sin(), angle.cos());
+            (radius * normal.x, center + radius * normal.y, normal)
+        })
+        .collect()
+}
+
+/// A sphere made of rings of latitude and longitude
+#[derive(Debug, Clone)]
+pub struct UvSphere {
+    pub radius: f32,
+    /// Slices around the Y axis
+    pub sectors: u32,
+    /// Rings from pole to pole
+    pub stacks: u32,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl UvSphere {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            sectors: 32,
+            stacks: 16,
+            color: WHITE,
+        }
+    }
+
+    pub fn with_segments(mut self, sectors: u32, stacks: u32) -> Self {
+        self.sectors = sectors.max(3);
+        self.stacks = stacks.max(2);
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn into_raw(self) -> (Vec<Vertex>, u32) {
+        let profile = arc(self.radius, 0.0, 0.0, PI, self.stacks)
+            .into_iter()
+            .enumerate()
+            .map(|(stack, (radius, height, normal))| {
+                (radius, height, normal, stack as f32 / self.stacks as f32)
+            })
+            .collect::<Vec<_>>();
+        let mut geometry = Geometry::default();
+        geometry.revolve(&profile, self.sectors);
+        geometry.into_raw(self.color)
+    }
+}
+
+/// A sphere made of evenly sized triangles, by subdividing an icosahedron
+#[derive(Debug, Clone)]
+pub struct IcoSphere {
+    pub radius: f32,
+    /// Each one splits every triangle in four
+    pub subdivisions: u32,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl IcoSphere {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            subdivisions: 2,
+            color: WHITE,
+        }
+    }
+
+    pub fn with_subdivisions(mut self, subdivisions: u32) -> Self {
+        self.subdivisions = subdivisions;
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn into_raw(self) -> (Vec<Vertex>, u32) {
+        let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+        let mut points = [
+            (-1.0, t, 0.0),
+            (1.0, t, 0.0),
+            (-1.0, -t, 0.0),
+            (1.0, -t, 0.0),
+            (0.0, -1.0, t),
+            (0.0, 1.0, t),
+            (0.0, -1.0, -t),
+            (0.0, 1.0, -t),
+            (t, 0.0, -1.0),
+            (t, 0.0, 1.0),
+            (-t, 0.0, -1.0),
+            (-t, 0.0, 1.0),
+        ]
+        .map(|(x, y, z)| Vec3::new(x, y, z).normalize())
+        .to_vec();
+        let mut faces = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..self.subdivisions {
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: usize, b: usize| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    points.push((points[a] + points[b]).normalize());
+                    points.len() - 1
+                })
+            };
+            faces = faces
+                .into_iter()
+                .flat_map(|[a, b, c]| {
+                    let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        // Vertices aren't shared so the texture coordinates don't wrap around at the seam
+        let mut geometry = Geometry::default();
+        for face in faces {
+            let normals = face.map(|index| points[index]);
+            let mut uvs = normals.map(|normal| {
+                Vec2::new(
+                    0.5 + normal.z.atan2(normal.x) / TAU,
+                    normal.y.clamp(-1.0, 1.0).acos() / PI,
+                )
+            });
+            let max_u = uvs.iter().map(|uv| uv.x).fold(0.0, f32::max);
+            for uv in uvs.iter_mut() {
+                if max_u - uv.x > 0.5 {
+                    uv.x += 1.0;
+                }
+            }
+            let [a, b, c] =
+                [0, 1, 2].map(|i| geometry.vertex(normals[i] * self.radius, normals[i], uvs[i]));
+            geometry.triangle(a, b, c);
+        }
+        geometry.into_raw(self.color)
+    }
+}
+
+/// A cylinder along Y, centered on the origin
+#[derive(Debug, Clone)]
+pub struct Cylinder {
+    pub radius: f32,
+    pub height: f32,
+    pub segments: u32,
+    /// Rings along the height
+    pub rings: u32,
+    pub caps: bool,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Cylinder {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Self {
+            radius,
+            height,
+            segments: 32,
+            rings: 1,
+            caps: true,
+            color: WHITE,
+        }
+    }
+
+    pub fn with_segments(mut self, segments: u32) -> Self {
+        self.segments = segments.max(3);
+        self
+    }
+
+    pub fn with_rings(mut self, rings: u32) -> Self {
+        self.rings = rings.max(1);
+        self
+    }
+
+    pub fn with_caps(mut self, caps: bool) -> Self {
+        self.caps = caps;
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn into_raw(self) -> (Vec<Vertex>, u32) {
+        let half = self.height / 2.0;
+        let profile = (0..=self.rings)
+            .map(|ring| {
+                let v = ring as f32 / self.rings as f32;
+                (self.radius, half - v * self.height, Vec2::X, v)
+            })
+            .collect::<Vec<_>>();
+        let mut geometry = Geometry::default();
+        geometry.revolve(&profile, self.segments);
+        if self.caps {
+            geometry.disc(self.radius, half, self.segments, true);
+            geometry.disc(self.radius, -half, self.segments, false);
+        }
+        geometry.into_raw(self.color)
+    }
+}
+
+/// A cone along Y with its tip up, centered on the origin
+#[derive(Debug, Clone)]
+pub struct Cone {
+    pub radius: f32,
+    pub height: f32,
+    pub segments: u32,
+    pub cap: bool,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Cone {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Self {
+            radius,
+            height,
+            segments: 32,
+            cap: true,
+            color: WHITE,
+        }
+    }
+
+    pub fn with_segments(mut self, segments: u32) -> Self {
+        self.segments = segments.max(3);
+        self
+    }
+
+    pub fn with_cap(mut self, cap: bool) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn into_raw(self) -> (Vec<Vertex>, u32) {
+        let half = self.height / 2.0;
+        // Perpendicular to the slope, shared by the tip and the base
+        let normal = Vec2::new(self.height, self.radius).normalize_or_zero();
+        let profile = [(0.0, half, normal, 0.0), (self.radius, -half, normal, 1.0)];
+        let mut geometry = Geometry::default();
+        geometry.revolve(&profile, self.segments);
+        if self.cap {
+            geometry.disc(self.radius, -half, self.segments, false);
+        }
+        geometry.into_raw(self.color)
+    }
+}
+
+/// A ring lying on the XZ plane
+#[derive(Debug, Clone)]
+pub struct Torus {
+    /// From the center to the middle of the tube
+    pub major_radius: f32,
+    /// Of the tube
+    pub minor_radius: f32,
+    pub major_segments: u32,
+    pub minor_segments: u32,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Torus {
+    pub fn new(major_radius: f32, minor_radius: f32) -> Self {
+        Self {
+            major_radius,
+            minor_radius,
+            major_segments: 32,
+            minor_segments: 16,
+            color: WHITE,
+        }
+    }
+
+    pub fn with_segments(mut self, major_segments: u32, minor_segments: u32) -> Self {
+        self.major_segments = major_segments.max(3);
+        self.minor_segments = minor_segments.max(3);
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn into_raw(self) -> (Vec<Vertex>, u32) {
+        let profile = (0..=self.minor_segments)
+            .map(|segment| {
+                let v = segment as f32 / self.minor_segments as f32;
+                let (sin, cos) = (v * TAU).sin_cos();
+                let normal = Vec2::new(cos, sin);
+                (
+                    self.major_radius + self.minor_radius * cos,
+                    self.minor_radius * sin,
+                    normal,
+                    v,
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut geometry = Geometry::default();
+        geometry.revolve(&profile, self.major_segments);
+        geometry.into_raw(self.color)
+    }
+}
+
+/// A cylinder along Y with half spheres at both ends, centered on the origin
+#[derive(Debug, Clone)]
+pub struct Capsule {
+    pub radius: f32,
+    /// Of the cylinder between the half spheres
+    pub height: f32,
+    pub segments: u32,
+    /// Rings of each half sphere
+    pub rings: u32,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Capsule {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Self {
+            radius,
+            height,
+            segments: 32,
+            rings: 8,
+            color: WHITE,
+        }
+    }
+
+    pub fn with_segments(mut self, segments: u32, rings: u32) -> Self {
+        self.segments = segments.max(3);
+        self.rings = rings.max(1);
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn into_raw(self) -> (Vec<Vertex>, u32) {
+        let half = self.height / 2.0;
+        let mut points = arc(self.radius, half, 0.0, PI / 2.0, self.rings);
+        points.extend(arc(self.radius, -half, PI / 2.0, PI, self.rings));
+        // v follows the length of the profile so the texture isn't stretched
+        let total = self.height + PI * self.radius;
+        let mut length = 0.0;
+        let profile = points
+            .iter()
+            .enumerate()
+            .map(|(index, &(radius, height, normal))| {
+                if index > 0 {
+                    let (previous_radius, previous_height, _) = points[index - 1];
+                    length +=
+                        Vec2::new(radius - previous_radius, height - previous_height).length();
+                }
+                let v = if total > 0.0 { length / total } else { 0.0 };
+                (radius, height, normal, v)
+            })
+            .collect::<Vec<_>>();
+        let mut geometry = Geometry::default();
+        geometry.revolve(&profile, self.segments);
+        geometry.into_raw(self.color)
+    }
+}
+
+/// A subdivided plane on XZ facing up, centered on the origin
+#[derive(Debug, Clone)]
+pub struct Plane {
+    pub width: f32,
+    pub depth: f32,
+    pub subdivisions_x: u32,
+    pub subdivisions_z: u32,
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Plane {
+    pub fn new(width: f32, depth: f32) -> Self {
+        Self {
+            width,
+            depth,
+            subdivisions_x: 1,
+            subdivisions_z: 1,
+            color: WHITE,
+        }
+    }
+
+    pub fn with_subdivisions(mut self, subdivisions_x: u32, subdivisions_z: u32) -> Self {
+        self.subdivisions_x = subdivisions_x.max(1);
+        self.subdivisions_z = subdivisions_z.max(1);
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32, f32)) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn into_raw(self) -> (Vec<Vertex>, u32) {
+        let mut geometry = Geometry::default();
+        for z in 0..=self.subdivisions_z {
+            for x in 0..=self.subdivisions_x {
+                let uv = Vec2::new(
+                    x as f32 / self.subdivisions_x as f32,
+                    z as f32 / self.subdivisions_z as f32,
+                );
+                geometry.vertex(
+                    Vec3::new((uv.x - 0.5) * self.width, 0.0, (uv.y - 0.5) * self.depth),
+                    Vec3::Y,
+                    uv,
+                );
+            }
+        }
+        geometry.grid(0, self.subdivisions_x, self.subdivisions_z);
+        geometry.into_raw(self.color)
+    }
+}
@@ -5,8 +5,21 @@ use std::ops::{Add, Div, Mul, Sub};
 pub struct Vertex {
     pub position: [f32; 4],
     pub color: [f32; 4],
+    /// Zero for the flat normal of the triangle
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
 }
 impl Vertex {
+    /// A vertex without a normal or texture coordinates
+    pub fn new(position: [f32; 4], color: [f32; 4]) -> Self {
+        Self {
+            position,
+            color,
+            normal: [0.0; 3],
+            uv: [0.0; 2],
+        }
+    }
+
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
@@ -22,6 +35,16 @@ impl Vertex {
                     shader_location: 1,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 8]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 11]>() as wgpu::BufferAddress,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
             ],
         }
     }
@@ -77,6 +100,13 @@ impl Vertex {
         self.position[1] = ayx * px + ayy * py + ayz * pz;
         self.position[2] = azx * px + azy * py + azz * pz;
 
+        let [nx, ny, nz] = self.normal;
+        self.normal = [
+            axx * nx + axy * ny + axz * nz,
+            ayx * nx + ayy * ny + ayz * nz,
+            azx * nx + azy * ny + azz * nz,
+        ];
+
         self
     }
 }
@@ -92,7 +122,7 @@ impl Add<f32> for Vertex {
                 self.position[2] + rhs,
                 self.position[3],
             ],
-            color: self.color,
+            ..self
         }
     }
 }
@@ -107,7 +137,7 @@ impl Sub<f32> for Vertex {
                 self.position[2] - rhs,
                 self.position[3],
             ],
-            color: self.color,
+            ..self
         }
     }
 }
@@ -122,7 +152,7 @@ impl Mul<f32> for Vertex {
                 self.position[2] * rhs,
                 self.position[3],
             ],
-            color: self.color,
+            ..self
         }
     }
 }
@@ -137,7 +167,7 @@ impl Div<f32> for Vertex {
                 self.position[2] / rhs,
                 self.position[3],
             ],
-            color: self.color,
+            ..self
         }
     }
 }
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use starry_ecs::resources::Resource;
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::{SystemType, World};
+
+use crate::plugin::EngineOrdering;
+use crate::StarstruckEngine;
+
+/// Anything that can be used as the states of a `State<S>`
+pub trait StateData: Debug + Clone + Eq + Hash + 'static {}
+
+impl<T: Debug + Clone + Eq + Hash + 'static> StateData for T {}
+
+/// The current state of type `S`.
+///
+/// Transitions requested with `set` are applied at the start of the next frame,
+/// running the exit systems of the old state and then the enter systems of the new one.
+#[derive(Debug, Clone)]
+pub struct State<S> {
+    current: S,
+    previous: Option<S>,
+    next: Option<S>,
+    entered: bool,
+}
+
+impl<S: StateData> State<S> {
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: initial,
+            previous: None,
+            next: None,
+            entered: false,
+        }
+    }
+
+    pub fn get(&self) -> &S {
+        &self.current
+    }
+
+    /// The state before the last transition
+    pub fn previous(&self) -> Option<&S> {
+        self.previous.as_ref()
+    }
+
+    /// The transition queued for the next frame
+    pub fn next(&self) -> Option<&S> {
+        self.next.as_ref()
+    }
+
+    pub fn is_in(&self, state: &S) -> bool {
+        &self.current == state
+    }
+
+    /// Queues a transition, replacing any transition queued earlier in the frame
+    pub fn set(&mut self, next: S) {
+        self.next = Some(next);
+    }
+}
+
+impl<S: StateData> Resource for State<S> {}
+
+/// The systems registered for each state of type `S`
+#[derive(Debug)]
+pub struct StateSystems<S> {
+    on_enter: HashMap<S, Vec<SystemType>>,
+    on_exit: HashMap<S, Vec<SystemType>>,
+    on_update: HashMap<(i32, S), Vec<SystemType>>,
+}
+
+impl<S> Default for StateSystems<S> {
+    fn default() -> Self {
+        Self {
+            on_enter: HashMap::new(),
+            on_exit: HashMap::new(),
+            on_update: HashMap::new(),
+        }
+    }
+}
+
+impl<S: StateData> StateSystems<S> {
+    fn enter_systems(&self, state: &S) -> Vec<SystemType> {
+        self.on_enter.get(state).cloned().unwrap_or_default()
+    }
+
+    fn exit_systems(&self, state: &S) -> Vec<SystemType> {
+        self.on_exit.get(state).cloned().unwrap_or_default()
+    }
+
+    fn update_systems(&self, ordering: i32, state: &S) -> Vec<SystemType> {
+        self.on_update
+            .get(&(ordering, state.clone()))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl<S: StateData> Resource for StateSystems<S> {}
+
+fn run_systems(world: &World, systems: Vec<SystemType>) {
+    for system in systems {
+        system(world);
+    }
+}
+
+/// Enters the initial state and applies queued transitions
+pub fn apply_state_transition<S: StateData>(world: &World) {
+    // Neither guard may be held while the systems run, they are free to use the state
+    let (exited, entered) = {
+        let mut state = world.get_resource_mut::<State<S>>();
+        if !state.entered {
+            state.entered = true;
+            state.next = None;
+            (None, state.current.clone())
+        } else {
+            match state.next.take() {
+                Some(next) if next != state.current => {
+                    let previous = std::mem::replace(&mut state.current, next);
+                    state.previous = Some(previous.clone());
+                    (Some(previous), state.current.clone())
+                }
+                _ => return,
+            }
+        }
+    };
+
+    if let Some(exited) = exited {
+        let systems = world
+            .get_resource::<StateSystems<S>>()
+            .exit_systems(&exited);
+        run_systems(world, systems);
+    }
+    let systems = world
+        .get_resource::<StateSystems<S>>()
+        .enter_systems(&entered);
+    run_systems(world, systems);
+}
+
+/// Runs the systems of the active state registered for `ORDERING`
+pub fn run_state_systems<S: StateData, const ORDERING: i32>(world: &World) {
+    let current = world.get_resource::<State<S>>().current.clone();
+    let systems = world
+        .get_resource::<StateSystems<S>>()
+        .update_systems(ORDERING, &current);
+    run_systems(world, systems);
+}
+
+impl StarstruckEngine {
+    /// Adds a `State<S>` resource starting in `initial`.
+    ///
+    /// The systems of a state run one after another inside the stage they were added to.
+    ///
+    /// # Panics
+    /// Panics if a state of the same type was already added
+    pub fn add_state<S: StateData>(self, initial: S) -> Self {
+        if self.world().try_get_resource::<State<S>>().is_ok() {
+            panic!(
+                "`add_state::<{}>` was called twice",
+                std::any::type_name::<S>()
+            );
+        }
+        self.add_resource(State::new(initial))
+            .add_resource(StateSystems::<S>::default())
+            .add_system(EngineOrdering::First, apply_state_transition::<S>)
+            .add_system(
+                DefaultOrdering::PreRun,
+                run_state_systems::<S, { DefaultOrdering::PreRun as i32 }>,
+            )
+            .add_system(
+                DefaultOrdering::Run,
+                run_state_systems::<S, { DefaultOrdering::Run as i32 }>,
+            )
+            .add_system(
+                DefaultOrdering::PostRun,
+                run_state_systems::<S, { DefaultOrdering::PostRun as i32 }>,
+            )
+    }
+
+    /// Adds a system which runs once every time `state` is entered
+    pub fn add_system_on_enter<S: StateData>(self, state: S, system: SystemType) -> Self {
+        self.state_systems::<S>()
+            .on_enter
+            .entry(state)
+            .or_default()
+            .push(system);
+        self
+    }
+
+    /// Adds a system which runs once every time `state` is left
+    pub fn add_system_on_exit<S: StateData>(self, state: S, system: SystemType) -> Self {
+        self.state_systems::<S>()
+            .on_exit
+            .entry(state)
+            .or_default()
+            .push(system);
+        self
+    }
+
+    /// Adds a system which runs every frame while `state` is active
+    pub fn add_system_in_state<S: StateData>(
+        self,
+        ordering: DefaultOrdering,
+        state: S,
+        system: SystemType,
+    ) -> Self {
+        self.state_systems::<S>()
+            .on_update
+            .entry((ordering as i32, state))
+            .or_default()
+            .push(system);
+        self
+    }
+
+    fn state_systems<S: StateData>(&self) -> starry_ecs::ResourceWriteGuard<'_, StateSystems<S>> {
+        self.world()
+            .try_get_resource_mut::<StateSystems<S>>()
+            .unwrap_or_else(|_| {
+                panic!(
+                    "`add_state::<{}>` has to be called before adding its systems",
+                    std::any::type_name::<S>()
+                )
+            })
+    }
+}
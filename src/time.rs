@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+use starry_ecs::resources::Resource;
+use starry_ecs::World;
+
+use crate::plugin::{EngineOrdering, Plugin};
+use crate::StarstruckEngine;
+
+#[derive(Debug, Clone)]
+pub struct Time {
+    startup: Instant,
+    last_update: Option<Instant>,
+    delta: Duration,
+    elapsed: Duration,
+    frame_count: u64,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self {
+            startup: Instant::now(),
+            last_update: None,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            frame_count: 0,
+        }
+    }
+}
+
+impl Time {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time between the start of the previous frame and the current one
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// Time since the engine started up
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn update(&mut self) {
+        self.update_with_instant(Instant::now());
+    }
+
+    pub fn update_with_instant(&mut self, now: Instant) {
+        self.delta = match self.last_update {
+            Some(last_update) => now.saturating_duration_since(last_update),
+            None => Duration::ZERO,
+        };
+        self.elapsed = now.saturating_duration_since(self.startup);
+        self.last_update = Some(now);
+        self.frame_count += 1;
+    }
+}
+
+impl Resource for Time {}
+
+pub fn update_time_system(world: &World) {
+    world.get_resource_mut::<Time>().update();
+}
+
+/// Adds the `Time` resource and keeps it updated at the start of every frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimePlugin;
+
+impl Plugin for TimePlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        engine
+            .add_resource(Time::new())
+            .add_system(EngineOrdering::First, update_time_system)
+    }
+}
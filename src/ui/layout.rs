@@ -0,0 +1,163 @@
+use glam::Vec2;
+
+use crate::renderer::sprite::Rect;
+use crate::ui::{AlignItems, FlexDirection, JustifyContent, UiNode, UiRoot};
+
+/// Splits a size into its length along `direction` and across it
+fn split(size: Vec2, direction: FlexDirection) -> (f32, f32) {
+    match direction {
+        FlexDirection::Row => (size.x, size.y),
+        FlexDirection::Column => (size.y, size.x),
+    }
+}
+
+fn join(main: f32, cross: f32, direction: FlexDirection) -> Vec2 {
+    match direction {
+        FlexDirection::Row => Vec2::new(main, cross),
+        FlexDirection::Column => Vec2::new(cross, main),
+    }
+}
+
+/// Size a node wants when nothing constrains it, padding included
+fn content_size(node: &UiNode, measure: &dyn Fn(&UiNode) -> Vec2) -> Vec2 {
+    let style = &node.style;
+    if node.children.is_empty() {
+        return measure(node) + style.padding.size();
+    }
+
+    let (mut main, mut cross) = (0.0, 0.0f32);
+    for child in node.children.iter() {
+        let size = fixed_size(child, Vec2::ZERO, measure) + child.style.margin.size();
+        let (child_main, child_cross) = split(size, style.direction);
+        main += child_main;
+        cross = cross.max(child_cross);
+    }
+    main += style.gap * (node.children.len() - 1) as f32;
+    join(main, cross, style.direction) + style.padding.size()
+}
+
+/// The size of a node from its style, falling back to its content
+fn fixed_size(node: &UiNode, parent: Vec2, measure: &dyn Fn(&UiNode) -> Vec2) -> Vec2 {
+    let width = node.style.width.resolve(parent.x);
+    let height = node.style.height.resolve(parent.y);
+    match (width, height) {
+        (Some(width), Some(height)) => Vec2::new(width, height),
+        _ => {
+            let content = content_size(node, measure);
+            Vec2::new(width.unwrap_or(content.x), height.unwrap_or(content.y))
+        }
+    }
+}
+
+fn layout_children(node: &mut UiNode, measure: &dyn Fn(&UiNode) -> Vec2) {
+    if node.children.is_empty() {
+        return;
+    }
+    let style = node.style.clone();
+    let direction = style.direction;
+    let inner_origin = node.layout.min + Vec2::new(style.padding.left, style.padding.top);
+    let inner = (node.layout.size() - style.padding.size()).max(Vec2::ZERO);
+    let (inner_main, inner_cross) = split(inner, direction);
+
+    let mut sizes = node
+        .children
+        .iter()
+        .map(|child| {
+            let (mut main, mut cross) = split(fixed_size(child, inner, measure), direction);
+            let (_, margin_cross) = split(child.style.margin.size(), direction);
+            let cross_is_auto = match direction {
+                FlexDirection::Row => child.style.height.resolve(0.0).is_none(),
+                FlexDirection::Column => child.style.width.resolve(0.0).is_none(),
+            };
+            if style.align_items == AlignItems::Stretch && cross_is_auto {
+                cross = (inner_cross - margin_cross).max(0.0);
+            }
+            main = main.max(0.0);
+            (main, cross)
+        })
+        .collect::<Vec<_>>();
+
+    let used = node
+        .children
+        .iter()
+        .zip(sizes.iter())
+        .map(|(child, (main, _))| main + split(child.style.margin.size(), direction).0)
+        .sum::<f32>()
+        + style.gap * (node.children.len() - 1) as f32;
+    let mut free = inner_main - used;
+
+    let total_grow = node
+        .children
+        .iter()
+        .map(|child| child.style.grow.max(0.0))
+        .sum::<f32>();
+    if free > 0.0 && total_grow > 0.0 {
+        for (child, (main, _)) in node.children.iter().zip(sizes.iter_mut()) {
+            *main += free * child.style.grow.max(0.0) / total_grow;
+        }
+        free = 0.0;
+    }
+
+    let count = node.children.len() as f32;
+    let free = free.max(0.0);
+    let (mut cursor, spacing) = match style.justify_content {
+        JustifyContent::Start => (0.0, 0.0),
+        JustifyContent::Center => (free / 2.0, 0.0),
+        JustifyContent::End => (free, 0.0),
+        JustifyContent::SpaceBetween if count > 1.0 => (0.0, free / (count - 1.0)),
+        JustifyContent::SpaceBetween => (0.0, 0.0),
+        JustifyContent::SpaceAround => (free / count / 2.0, free / count),
+    };
+
+    for (child, (main, cross)) in node.children.iter_mut().zip(sizes) {
+        let margin = child.style.margin;
+        let (margin_before, margin_cross_before) =
+            split(Vec2::new(margin.left, margin.top), direction);
+        let (margin_main, margin_cross) = split(margin.size(), direction);
+        let cross_offset = match style.align_items {
+            AlignItems::Start | AlignItems::Stretch => margin_cross_before,
+            AlignItems::Center => (inner_cross - cross - margin_cross) / 2.0 + margin_cross_before,
+            AlignItems::End => inner_cross - cross - margin_cross + margin_cross_before,
+        };
+
+        let min = inner_origin + join(cursor + margin_before, cross_offset, direction);
+        child.layout = Rect {
+            min,
+            max: min + join(main, cross, direction),
+        };
+        layout_children(child, measure);
+        cursor += main + margin_main + style.gap + spacing;
+    }
+}
+
+fn scale_layout(node: &mut UiNode, factor: f32) {
+    node.layout = Rect {
+        min: node.layout.min * factor,
+        max: node.layout.max * factor,
+    };
+    for child in node.children.iter_mut() {
+        scale_layout(child, factor);
+    }
+}
+
+/// Places the root on the window and lays out its tree.
+///
+/// `window` is in physical pixels and every length of the tree is multiplied by `factor`,
+/// `measure` gives the size of leaves like texts and images in logical pixels.
+pub fn layout_root(
+    root: &mut UiRoot,
+    window: Vec2,
+    factor: f32,
+    measure: &dyn Fn(&UiNode) -> Vec2,
+) {
+    let factor = factor.max(f32::EPSILON);
+    let window = window / factor;
+    let size = fixed_size(&root.node, window, measure);
+    let min = root.anchor.fraction() * (window - size) + root.offset;
+    root.node.layout = Rect {
+        min,
+        max: min + size,
+    };
+    layout_children(&mut root.node, measure);
+    scale_layout(&mut root.node, factor);
+}
@@ -0,0 +1,585 @@
+pub mod layout;
+pub mod pass;
+
+use glam::Vec2;
+use starry_ecs::component::Component;
+use starry_ecs::resources::Resource;
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::World;
+use wgpu::Color;
+use winit::event::{MouseButton, WindowEvent};
+
+use crate::commands::WorldExt;
+use crate::events::Events;
+use crate::input::Input;
+use crate::plugin::Plugin;
+use crate::renderer::sprite::Rect;
+use crate::renderer::text::layout_text;
+use crate::renderer::{Fonts, ImageId, Images, TextStyle};
+use crate::ui::layout::layout_root;
+use crate::ui::pass::UiPass;
+use crate::StarstruckEngine;
+
+/// A length in logical pixels or relative to the parent's content
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Val {
+    /// Fits the content
+    #[default]
+    Auto,
+    Px(f32),
+    Percent(f32),
+}
+
+impl Val {
+    pub fn resolve(&self, parent: f32) -> Option<f32> {
+        match *self {
+            Val::Auto => None,
+            Val::Px(pixels) => Some(pixels),
+            Val::Percent(percent) => Some(parent * percent / 100.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlexDirection {
+    #[default]
+    Row,
+    Column,
+}
+
+/// How children are spread along the direction of their parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JustifyContent {
+    #[default]
+    Start,
+    Center,
+    End,
+    SpaceBetween,
+    SpaceAround,
+}
+
+/// How children are placed across the direction of their parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlignItems {
+    Start,
+    Center,
+    End,
+    /// Children without a size fill their parent
+    #[default]
+    Stretch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Edges {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl Edges {
+    pub fn all(value: f32) -> Self {
+        Self::axes(value, value)
+    }
+
+    pub fn axes(horizontal: f32, vertical: f32) -> Self {
+        Self {
+            left: horizontal,
+            right: horizontal,
+            top: vertical,
+            bottom: vertical,
+        }
+    }
+
+    /// Total of the left and right edges in x, top and bottom in y
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.left + self.right, self.top + self.bottom)
+    }
+}
+
+/// The edge or corner of the window a `UiRoot` sticks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UiAnchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl UiAnchor {
+    /// Where the anchor is on the window, from (0, 0) at the top left to (1, 1)
+    pub fn fraction(&self) -> Vec2 {
+        match self {
+            UiAnchor::TopLeft => Vec2::new(0.0, 0.0),
+            UiAnchor::Top => Vec2::new(0.5, 0.0),
+            UiAnchor::TopRight => Vec2::new(1.0, 0.0),
+            UiAnchor::Left => Vec2::new(0.0, 0.5),
+            UiAnchor::Center => Vec2::new(0.5, 0.5),
+            UiAnchor::Right => Vec2::new(1.0, 0.5),
+            UiAnchor::BottomLeft => Vec2::new(0.0, 1.0),
+            UiAnchor::Bottom => Vec2::new(0.5, 1.0),
+            UiAnchor::BottomRight => Vec2::new(1.0, 1.0),
+        }
+    }
+}
+
+/// Flexbox-style layout of a node and its children, in logical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    pub width: Val,
+    pub height: Val,
+    pub direction: FlexDirection,
+    pub justify_content: JustifyContent,
+    pub align_items: AlignItems,
+    pub padding: Edges,
+    pub margin: Edges,
+    /// Space between children
+    pub gap: f32,
+    /// Share of the parent's free space the node grows by
+    pub grow: f32,
+    pub background: Option<Color>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            width: Val::Auto,
+            height: Val::Auto,
+            direction: FlexDirection::Row,
+            justify_content: JustifyContent::Start,
+            align_items: AlignItems::Stretch,
+            padding: Edges::default(),
+            margin: Edges::default(),
+            gap: 0.0,
+            grow: 0.0,
+            background: None,
+        }
+    }
+}
+
+impl Style {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_size(mut self, width: Val, height: Val) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_direction(mut self, direction: FlexDirection) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_justify_content(mut self, justify_content: JustifyContent) -> Self {
+        self.justify_content = justify_content;
+        self
+    }
+
+    pub fn with_align_items(mut self, align_items: AlignItems) -> Self {
+        self.align_items = align_items;
+        self
+    }
+
+    pub fn with_padding(mut self, padding: Edges) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn with_margin(mut self, margin: Edges) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn with_gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn with_grow(mut self, grow: f32) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    pub fn with_background(mut self, background: Color) -> Self {
+        self.background = Some(background);
+        self
+    }
+}
+
+/// Backgrounds of a button for each `Interaction`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ButtonColors {
+    pub normal: Color,
+    pub hovered: Color,
+    pub pressed: Color,
+}
+
+impl Default for ButtonColors {
+    fn default() -> Self {
+        let gray = |value: f64| Color {
+            r: value,
+            g: value,
+            b: value,
+            a: 1.0,
+        };
+        Self {
+            normal: gray(0.2),
+            hovered: gray(0.3),
+            pressed: gray(0.1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Container,
+    /// `TextStyle::size` is in logical pixels
+    Text {
+        text: String,
+        style: TextStyle,
+    },
+    Image {
+        image: ImageId,
+        tint: Color,
+    },
+    Button(ButtonColors),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interaction {
+    #[default]
+    None,
+    Hovered,
+    /// The left mouse button went down over the node and is still held
+    Pressed,
+}
+
+/// An element of the UI, laid out with its children
+#[derive(Debug, Clone, PartialEq)]
+pub struct UiNode {
+    /// Used to find the node and sent with its `ButtonClicked` events
+    pub name: Option<String>,
+    pub kind: NodeKind,
+    pub style: Style,
+    pub children: Vec<UiNode>,
+    layout: Rect,
+    interaction: Interaction,
+    clicked: bool,
+}
+
+impl UiNode {
+    pub fn new(kind: NodeKind) -> Self {
+        Self {
+            name: None,
+            kind,
+            style: Style::default(),
+            children: vec![],
+            layout: Rect::new(0.0, 0.0, 0.0, 0.0),
+            interaction: Interaction::None,
+            clicked: false,
+        }
+    }
+
+    pub fn container() -> Self {
+        Self::new(NodeKind::Container)
+    }
+
+    pub fn text(text: &str, style: TextStyle) -> Self {
+        Self::new(NodeKind::Text {
+            text: String::from(text),
+            style,
+        })
+    }
+
+    pub fn image(image: ImageId) -> Self {
+        Self::new(NodeKind::Image {
+            image,
+            tint: Color::WHITE,
+        })
+    }
+
+    pub fn button() -> Self {
+        Self::new(NodeKind::Button(ButtonColors::default()))
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(String::from(name));
+        self
+    }
+
+    pub fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn with_child(mut self, child: UiNode) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn with_children(mut self, children: impl IntoIterator<Item = UiNode>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    /// Where the node was laid out, in physical pixels from the top left of the window
+    pub fn layout(&self) -> Rect {
+        self.layout
+    }
+
+    pub fn interaction(&self) -> Interaction {
+        self.interaction
+    }
+
+    /// Whether the button was clicked this frame
+    pub fn clicked(&self) -> bool {
+        self.clicked
+    }
+
+    /// The first node named `name`, depth first
+    pub fn find(&self, name: &str) -> Option<&UiNode> {
+        if self.name.as_deref() == Some(name) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut UiNode> {
+        if self.name.as_deref() == Some(name) {
+            return Some(self);
+        }
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(name))
+    }
+
+    /// Calls `visit` with the node and every descendant, parents before children
+    pub fn visit(&self, visit: &mut impl FnMut(&UiNode)) {
+        visit(self);
+        for child in self.children.iter() {
+            child.visit(visit);
+        }
+    }
+}
+
+/// A tree of UI nodes placed on the window
+#[derive(Debug, Clone)]
+pub struct UiRoot {
+    pub node: UiNode,
+    pub anchor: UiAnchor,
+    /// Moves the root from its anchor, in logical pixels
+    pub offset: Vec2,
+}
+
+impl UiRoot {
+    pub fn new(node: UiNode) -> Self {
+        Self {
+            node,
+            anchor: UiAnchor::TopLeft,
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_anchor(mut self, anchor: UiAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+impl Component for UiRoot {}
+
+/// Size of the window and how many physical pixels a logical UI pixel covers
+#[derive(Debug, Clone)]
+pub struct UiScale {
+    /// Multiplies the scale factor of the window
+    pub scale: f32,
+    scale_factor: f64,
+    window_size: (u32, u32),
+}
+
+impl Default for UiScale {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            scale_factor: 1.0,
+            window_size: (0, 0),
+        }
+    }
+}
+
+impl UiScale {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn factor(&self) -> f32 {
+        self.scale * self.scale_factor as f32
+    }
+
+    /// Size of the window in physical pixels
+    pub fn window_size(&self) -> (u32, u32) {
+        self.window_size
+    }
+
+    /// Called by the renderer at the start of every frame, so the size is known before any event
+    pub fn set_window(&mut self, window_size: (u32, u32), scale_factor: f64) {
+        self.window_size = window_size;
+        self.scale_factor = scale_factor;
+    }
+
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Resized(size) => self.window_size = (size.width, size.height),
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                new_inner_size,
+            } => {
+                self.scale_factor = *scale_factor;
+                self.window_size = (new_inner_size.width, new_inner_size.height);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Resource for UiScale {}
+
+/// Sent when a button is clicked, with the button's name
+#[derive(Debug, Clone, PartialEq)]
+pub struct ButtonClicked {
+    pub name: Option<String>,
+}
+
+pub fn ui_event_handler(world: &World, event: &WindowEvent) {
+    if let Ok(mut scale) = world.try_get_resource_mut::<UiScale>() {
+        scale.handle_event(event);
+    }
+}
+
+/// Size of the content of a leaf node, in logical pixels
+pub fn measure_node(node: &UiNode, fonts: Option<&Fonts>, images: Option<&Images>) -> Vec2 {
+    match &node.kind {
+        NodeKind::Text { text, style } => fonts
+            .and_then(|fonts| fonts.get(style.font))
+            .map(|font| {
+                layout_text(
+                    font,
+                    text,
+                    style.size,
+                    style.line_height,
+                    style.alignment,
+                    style.max_width,
+                )
+                .size
+            })
+            .unwrap_or_default(),
+        NodeKind::Image { image, .. } => images
+            .and_then(|images| images.get(*image))
+            .map(|image| Vec2::new(image.width as f32, image.height as f32))
+            .unwrap_or_default(),
+        NodeKind::Container | NodeKind::Button(_) => Vec2::ZERO,
+    }
+}
+
+pub fn ui_layout_system(world: &World) {
+    let (Ok(scale), Ok(mut roots)) = (
+        world.try_get_resource::<UiScale>(),
+        world.try_get_spawned_mut::<UiRoot>(),
+    ) else {
+        return;
+    };
+    let fonts = world.try_get_resource::<Fonts>().ok();
+    let images = world.try_get_resource::<Images>().ok();
+    let measure = |node: &UiNode| measure_node(node, fonts.as_deref(), images.as_deref());
+
+    let (width, height) = scale.window_size();
+    for root in roots.iter_mut() {
+        layout_root(
+            root,
+            Vec2::new(width as f32, height as f32),
+            scale.factor(),
+            &measure,
+        );
+    }
+}
+
+fn interact(
+    node: &mut UiNode,
+    cursor: Option<Vec2>,
+    input: &Input,
+    clicked: &mut Vec<ButtonClicked>,
+) {
+    node.clicked = false;
+    if let NodeKind::Button(_) = node.kind {
+        let hovered = cursor.is_some_and(|cursor| node.layout.contains(cursor));
+        let was_pressed = node.interaction == Interaction::Pressed;
+        node.interaction = match (hovered, was_pressed) {
+            (true, _) if input.mouse_just_pressed(MouseButton::Left) => Interaction::Pressed,
+            (true, true) if input.mouse_pressed(MouseButton::Left) => Interaction::Pressed,
+            (true, _) => Interaction::Hovered,
+            (false, _) => Interaction::None,
+        };
+        if hovered && was_pressed && input.mouse_just_released(MouseButton::Left) {
+            node.clicked = true;
+            clicked.push(ButtonClicked {
+                name: node.name.clone(),
+            });
+        }
+    }
+    for child in node.children.iter_mut() {
+        interact(child, cursor, input, clicked);
+    }
+}
+
+/// Updates the interaction of every button from the cursor and the left mouse button
+pub fn ui_interaction_system(world: &World) {
+    let (Ok(input), Ok(mut roots)) = (
+        world.try_get_resource::<Input>(),
+        world.try_get_spawned_mut::<UiRoot>(),
+    ) else {
+        return;
+    };
+    let cursor = input
+        .cursor_position()
+        .map(|(x, y)| Vec2::new(x as f32, y as f32));
+
+    let mut clicked = vec![];
+    for root in roots.iter_mut() {
+        interact(&mut root.node, cursor, &input, &mut clicked);
+    }
+    if let Ok(mut events) = world.try_get_resource_mut::<Events<ButtonClicked>>() {
+        for event in clicked {
+            events.send(event);
+        }
+    }
+}
+
+/// Lays out, updates and draws every `UiRoot` over the window
+#[derive(Debug, Clone, Default)]
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        engine
+            .add_resource(UiScale::new())
+            .add_event::<ButtonClicked>()
+            .add_window_event_handler(ui_event_handler)
+            .add_system(DefaultOrdering::PreRun, ui_interaction_system)
+            .add_system(DefaultOrdering::PostRun, ui_layout_system)
+            .add_render_pass(UiPass::new())
+    }
+}
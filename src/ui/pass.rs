@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use glam::{Mat4, Vec2};
+use starry_ecs::World;
+use wgpu::{
+    include_wgsl, BindGroup, BindGroupLayout, Buffer, Color, CommandEncoder, Device, LoadOp,
+    Operations, RenderPassColorAttachment, RenderPipeline, TextureFormat,
+};
+
+use crate::commands::WorldExt;
+use crate::renderer::camera::CameraUniform;
+use crate::renderer::graph::{PassBuilder, RenderResources, SWAPCHAIN};
+use crate::renderer::image::{GpuImage, Image};
+use crate::renderer::pass::{write_buffer, RenderContext, RenderPass};
+use crate::renderer::sprite::{Rect, SpriteVertex};
+use crate::renderer::text::{TextPass, TextSpace};
+use crate::renderer::{Fonts, ImageId, TextStyle};
+use crate::ui::{Interaction, NodeKind, UiNode, UiRoot, UiScale};
+
+/// What fills a quad, backgrounds use a white texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum QuadImage {
+    White,
+    Image(ImageId),
+}
+
+struct UiRenderer {
+    pipeline: RenderPipeline,
+    camera_buffer: Buffer,
+    camera_bind_group: BindGroup,
+    image_bind_group_layout: BindGroupLayout,
+    white: GpuImage,
+    // Keyed by the version of the image they were created for
+    image_bind_groups: HashMap<QuadImage, (u32, BindGroup)>,
+    vertex_buffer: Option<Buffer>,
+    index_buffer: Option<Buffer>,
+    batches: Vec<(QuadImage, Range<u32>)>,
+    text: TextPass,
+}
+
+impl UiRenderer {
+    fn new(device: &Device, queue: &wgpu::Queue, format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(include_wgsl!("../renderer/sprite.wgsl"));
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Ui Camera Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let image_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Ui Image Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Ui Camera Buffer"),
+            size: std::mem::size_of::<CameraUniform>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Ui Camera Bind Group"),
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Ui Pipeline Layout"),
+            bind_group_layouts: &[&camera_bind_group_layout, &image_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Ui Pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_sprite",
+                buffers: &[SpriteVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_sprite",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        Self {
+            pipeline,
+            camera_buffer,
+            camera_bind_group,
+            image_bind_group_layout,
+            white: GpuImage::new(device, queue, &Image::filled(1, 1, [255; 4]), 0),
+            image_bind_groups: HashMap::new(),
+            vertex_buffer: None,
+            index_buffer: None,
+            batches: vec![],
+            text: TextPass::new(device, TextSpace::Screen, format),
+        }
+    }
+
+    fn bind_image(&mut self, context: &RenderContext<'_>, image: QuadImage) {
+        let gpu_image = match image {
+            QuadImage::White => &self.white,
+            QuadImage::Image(id) => match context.image(id) {
+                Some(gpu_image) => gpu_image,
+                None => return,
+            },
+        };
+        let current = self
+            .image_bind_groups
+            .get(&image)
+            .is_some_and(|(version, _)| *version == gpu_image.version);
+        if current {
+            return;
+        }
+        let bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Ui Image Bind Group"),
+                layout: &self.image_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(gpu_image.view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(gpu_image.sampler()),
+                    },
+                ],
+            });
+        self.image_bind_groups
+            .insert(image, (gpu_image.version, bind_group));
+    }
+}
+
+fn color_array(color: Color) -> [f32; 4] {
+    [
+        color.r as f32,
+        color.g as f32,
+        color.b as f32,
+        color.a as f32,
+    ]
+}
+
+fn quad(rect: Rect, color: Color) -> [SpriteVertex; 4] {
+    let color = color_array(color);
+    [
+        (Vec2::new(rect.min.x, rect.max.y), Vec2::new(0.0, 1.0)),
+        (rect.max, Vec2::new(1.0, 1.0)),
+        (Vec2::new(rect.max.x, rect.min.y), Vec2::new(1.0, 0.0)),
+        (rect.min, Vec2::new(0.0, 0.0)),
+    ]
+    .map(|(position, uv)| SpriteVertex {
+        position: position.extend(0.0).to_array(),
+        uv: uv.to_array(),
+        color,
+    })
+}
+
+/// The quads and texts of a node and its children, parents first
+fn collect_node(
+    node: &UiNode,
+    factor: f32,
+    quads: &mut Vec<(QuadImage, [SpriteVertex; 4])>,
+    texts: &mut Vec<(String, TextStyle, Mat4)>,
+) {
+    let background = match &node.kind {
+        NodeKind::Button(colors) => Some(match node.interaction() {
+            Interaction::None => node.style.background.unwrap_or(colors.normal),
+            Interaction::Hovered => colors.hovered,
+            Interaction::Pressed => colors.pressed,
+        }),
+        _ => node.style.background,
+    };
+    if let Some(background) = background {
+        quads.push((QuadImage::White, quad(node.layout(), background)));
+    }
+
+    let padding = node.style.padding;
+    let content = Rect {
+        min: node.layout().min + Vec2::new(padding.left, padding.top) * factor,
+        max: node.layout().max - Vec2::new(padding.right, padding.bottom) * factor,
+    };
+    match &node.kind {
+        NodeKind::Image { image, tint } => {
+            quads.push((QuadImage::Image(*image), quad(content, *tint)));
+        }
+        NodeKind::Text { text, style } => {
+            let mut style = style.clone();
+            style.size *= factor;
+            // Aligned within the node when it is wider than the text
+            style.max_width = Some(content.width());
+            texts.push((
+                text.clone(),
+                style,
+                Mat4::from_translation(content.min.extend(0.0)),
+            ));
+        }
+        NodeKind::Container | NodeKind::Button(_) => {}
+    }
+
+    for child in node.children.iter() {
+        collect_node(child, factor, quads, texts);
+    }
+}
+
+/// Draws every `UiRoot` on the window, after the scene and its post-processing
+#[derive(Default)]
+pub struct UiPass {
+    renderer: Option<UiRenderer>,
+}
+
+impl UiPass {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RenderPass for UiPass {
+    fn name(&self) -> &str {
+        "ui"
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        builder.read(SWAPCHAIN).write(SWAPCHAIN);
+    }
+
+    fn init(&mut self, context: &RenderContext<'_>) {
+        self.renderer = Some(UiRenderer::new(
+            context.device,
+            context.queue,
+            context.format,
+        ));
+    }
+
+    fn prepare(&mut self, context: &RenderContext<'_>, world: &World) {
+        let Some(renderer) = self.renderer.as_mut() else {
+            return;
+        };
+        renderer.batches.clear();
+        let factor = world
+            .try_get_resource::<UiScale>()
+            .map_or(1.0, |scale| scale.factor());
+        let mut quads = vec![];
+        let mut texts = vec![];
+        if let Ok(roots) = world.try_get_spawned::<UiRoot>() {
+            for root in roots.iter() {
+                collect_node(&root.node, factor, &mut quads, &mut texts);
+            }
+        }
+
+        // The same size the layout used, so nodes land where they were placed
+        let (width, height) = world
+            .try_get_resource::<UiScale>()
+            .map_or((context.size.width, context.size.height), |scale| {
+                scale.window_size()
+            });
+        let (width, height) = (width as f32, height as f32);
+        let view_projection = Mat4::orthographic_rh(0.0, width, height, 0.0, -1.0, 1.0);
+        context.queue.write_buffer(
+            &renderer.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[CameraUniform::new(view_projection)]),
+        );
+        match world.try_get_resource::<Fonts>() {
+            Ok(fonts) => renderer
+                .text
+                .prepare_texts(context, &fonts, &texts, view_projection),
+            Err(_) => renderer
+                .text
+                .prepare_texts(context, &Fonts::new(), &[], view_projection),
+        }
+
+        // Images that aren't uploaded yet are skipped
+        quads.retain(|(image, _)| match image {
+            QuadImage::White => true,
+            QuadImage::Image(id) => context.image(*id).is_some(),
+        });
+        if quads.is_empty() {
+            return;
+        }
+        for (index, (image, _)) in quads.iter().enumerate() {
+            let index = index as u32;
+            match renderer.batches.last_mut() {
+                Some((batch_image, range)) if batch_image == image => range.end = index + 1,
+                _ => renderer.batches.push((*image, index..index + 1)),
+            }
+        }
+        renderer.image_bind_groups.retain(|image, _| match image {
+            QuadImage::White => true,
+            QuadImage::Image(id) => context.image(*id).is_some(),
+        });
+        let images = renderer
+            .batches
+            .iter()
+            .map(|(image, _)| *image)
+            .collect::<Vec<_>>();
+        for image in images {
+            renderer.bind_image(context, image);
+        }
+
+        let vertices = quads
+            .iter()
+            .flat_map(|(_, vertices)| *vertices)
+            .collect::<Vec<_>>();
+        let indices = (0..quads.len() as u32)
+            .flat_map(|quad| [0, 1, 2, 0, 2, 3].map(|corner| quad * 4 + corner))
+            .collect::<Vec<_>>();
+        write_buffer(
+            context.device,
+            context.queue,
+            &mut renderer.vertex_buffer,
+            "Ui Vertex Buffer",
+            wgpu::BufferUsages::VERTEX,
+            bytemuck::cast_slice(&vertices),
+        );
+        write_buffer(
+            context.device,
+            context.queue,
+            &mut renderer.index_buffer,
+            "Ui Index Buffer",
+            wgpu::BufferUsages::INDEX,
+            bytemuck::cast_slice(&indices),
+        );
+    }
+
+    fn render(
+        &mut self,
+        _context: &RenderContext<'_>,
+        encoder: &mut CommandEncoder,
+        resources: &RenderResources<'_>,
+    ) {
+        let (Some(renderer), Some(view)) =
+            (self.renderer.as_ref(), resources.texture_view(SWAPCHAIN))
+        else {
+            return;
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Ui Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        if let (Some(vertex_buffer), Some(index_buffer)) = (
+            renderer.vertex_buffer.as_ref(),
+            renderer.index_buffer.as_ref(),
+        ) {
+            render_pass.set_pipeline(&renderer.pipeline);
+            render_pass.set_bind_group(0, &renderer.camera_bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            for (image, range) in renderer.batches.iter() {
+                let Some((_, bind_group)) = renderer.image_bind_groups.get(image) else {
+                    continue;
+                };
+                render_pass.set_bind_group(1, bind_group, &[]);
+                render_pass.draw_indexed(range.start * 6..range.end * 6, 0, 0..1);
+            }
+        }
+        renderer.text.draw(&mut render_pass);
+    }
+}
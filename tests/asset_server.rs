@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+use starstruck::assets::{AssetError, AssetServer, LoadState, ObjModel};
+use starstruck::renderer::{Image, Images, Mesh};
+
+/// A temporary directory, removed with everything in it once the test ends
+fn directory() -> tempfile::TempDir {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(directory.path().join("models")).unwrap();
+    directory
+}
+
+fn wait(server: &AssetServer) {
+    let start = Instant::now();
+    while !server.is_idle() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "loading timed out"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn assets_load_in_the_background() {
+    let temp = directory();
+    let directory = temp.path();
+    let mut png = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut png, 1, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[10, 20, 30, 255]).unwrap();
+    }
+    std::fs::write(directory.join("pixel.png"), &png).unwrap();
+    std::fs::write(directory.join("models/quad.png"), &png).unwrap();
+    std::fs::write(
+        directory.join("models/quad.obj"),
+        "mtllib quad.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nusemtl red\nf 1 2 3\n",
+    )
+    .unwrap();
+    std::fs::write(
+        directory.join("models/quad.mtl"),
+        "newmtl red\nKd 1 0 0\nmap_Kd quad.png\n",
+    )
+    .unwrap();
+    std::fs::write(directory.join("models/quad.stl"), b"not an stl").unwrap();
+
+    let server = AssetServer::new(directory);
+    let image = server.load::<Image>("pixel.png");
+    // Requesting a file again gives the same handle
+    assert_eq!(server.load::<Image>("./pixel.png"), image);
+    assert_eq!(server.load::<Image>("models/../pixel.png"), image);
+    let model = server.load::<ObjModel>("models/quad.obj");
+    let broken = server.load::<Mesh>("models/quad.stl");
+    let missing = server.load::<Image>("missing.png");
+    let unknown = server.load::<Image>("models/quad.obj");
+    wait(&server);
+
+    assert!(matches!(server.state(image), LoadState::Loaded));
+    assert_eq!(server.get(image).unwrap().data, [10, 20, 30, 255]);
+    let model = server.get(model).unwrap();
+    assert_eq!(model.materials[0].diffuse, [1.0, 0.0, 0.0, 1.0]);
+    // Textures are relative to the asset directory, ready to be loaded too
+    assert_eq!(
+        model.materials[0].texture.as_deref(),
+        Some(std::path::Path::new("models/quad.png"))
+    );
+    // and are read from the server's source, wherever the process runs
+    let mut images = Images::new();
+    let meshes = model.meshes_from(&mut images, server.source()).unwrap();
+    let texture = images.get(meshes[0].texture.unwrap()).unwrap();
+    assert_eq!(texture.data, [10, 20, 30, 255]);
+
+    // Reloads find the asset however its path is written
+    server.reload("models/./../pixel.png");
+    wait(&server);
+    assert_eq!(server.version(image), 2);
+
+    assert!(server.get(broken).is_none());
+    let LoadState::Failed(error) = server.state(broken) else {
+        panic!("a broken file should fail");
+    };
+    assert!(matches!(*error, AssetError::Load { .. }));
+    let LoadState::Failed(error) = server.state(missing) else {
+        panic!("a missing file should fail");
+    };
+    assert!(matches!(*error, AssetError::Io { .. }));
+    let LoadState::Failed(error) = server.state(unknown) else {
+        panic!("an OBJ isn't an image");
+    };
+    assert!(matches!(*error, AssetError::NoLoader { .. }));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn custom_loaders_run_on_the_current_runtime() {
+    let temp = directory();
+    let directory = temp.path();
+    std::fs::write(directory.join("greeting.TXT"), "hello").unwrap();
+
+    let server = AssetServer::new(directory)
+        .with_loader(&["txt"], |bytes, _| String::from_utf8(bytes.to_vec()));
+    let greeting = server.load::<String>("greeting.TXT");
+    assert_eq!(
+        server.path(greeting).unwrap(),
+        std::path::Path::new("greeting.TXT")
+    );
+    while !server.is_idle() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    assert_eq!(server.get(greeting).unwrap().as_str(), "hello");
+}
@@ -0,0 +1,160 @@
+use glam::Vec2;
+use starstruck::renderer::{
+    AnimationEventKind, AnimationFrame, Image, PlaybackMode, Rect, SpriteAnimation, TextureAtlas,
+    TextureAtlasBuilder,
+};
+
+#[test]
+fn packed_images_keep_their_pixels_and_dont_overlap() {
+    let mut builder = TextureAtlasBuilder::new().with_padding(1);
+    let red = builder.add(Image::filled(10, 20, [255, 0, 0, 255]));
+    let green = builder.add_named("green", Image::filled(30, 5, [0, 255, 0, 255]));
+    let blue = builder.add(Image::filled(7, 7, [0, 0, 255, 255]));
+    let (image, atlas) = builder.build().unwrap();
+
+    assert_eq!(atlas.len(), 3);
+    assert_eq!(atlas.size, image.size());
+    assert!(image.width.is_power_of_two());
+    assert_eq!(atlas.index_of("green"), Some(green));
+
+    let regions = [red, green, blue].map(|index| atlas.region(index).unwrap());
+    assert_eq!(regions[0].size(), Vec2::new(10.0, 20.0));
+    for (index, region) in regions.iter().enumerate() {
+        for other in regions[index + 1..].iter() {
+            let overlap = region.min.cmplt(other.max).all() && other.min.cmplt(region.max).all();
+            assert!(!overlap, "{region:?} overlaps {other:?}");
+        }
+    }
+
+    let pixel = |x: f32, y: f32| {
+        let start = (y as usize * image.width as usize + x as usize) * 4;
+        image.data[start..start + 4].to_vec()
+    };
+    assert_eq!(
+        pixel(regions[1].min.x, regions[1].min.y),
+        vec![0, 255, 0, 255]
+    );
+    assert_eq!(
+        pixel(regions[2].max.x - 1.0, regions[2].max.y - 1.0),
+        vec![0, 0, 255, 255]
+    );
+
+    let mut too_small = TextureAtlasBuilder::new().with_max_size(16);
+    too_small.add(Image::filled(32, 32, [0; 4]));
+    assert!(too_small.build().is_err());
+}
+
+#[test]
+fn grids_and_sprite_sheets_describe_regions() {
+    let grid = TextureAtlas::from_grid((64, 32), (16, 16), 3, 2, (2, 0), (1, 0));
+    assert_eq!(grid.len(), 6);
+    assert_eq!(grid.region(1), Some(Rect::new(19.0, 0.0, 16.0, 16.0)));
+    assert_eq!(grid.region(3), Some(Rect::new(1.0, 16.0, 16.0, 16.0)));
+
+    let sheet = TextureAtlas::from_json(
+        r#"{
+            "frames": {
+                "walk 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 8, "h": 8 }, "duration": 100 },
+                "walk 1.aseprite": { "frame": { "x": 8, "y": 0, "w": 8, "h": 8 }, "duration": 250 }
+            },
+            "meta": {
+                "size": { "w": 16, "h": 8 },
+                "frameTags": [{ "name": "walk", "from": 0, "to": 1, "direction": "pingpong" }]
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(sheet.size, (16, 8));
+    assert_eq!(
+        sheet.named_region("walk 1.aseprite"),
+        Some(Rect::new(8.0, 0.0, 8.0, 8.0))
+    );
+    assert_eq!(sheet.duration(1), Some(0.25));
+
+    let walk = SpriteAnimation::from_tag(&sheet, "walk", 0.1).unwrap();
+    assert_eq!(walk.mode, PlaybackMode::PingPong);
+    assert_eq!(walk.frames.len(), 2);
+    assert!(TextureAtlas::from_json(r#"{ "meta": {} }"#).is_err());
+}
+
+#[test]
+fn sprite_sheet_frames_keep_their_document_order() {
+    let frames = (0..12)
+        .map(|index| {
+            format!(
+                r#""walk {index}.png": {{ "frame": {{ "x": {}, "y": 0, "w": 8, "h": 8 }} }}"#,
+                index * 8
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let sheet = TextureAtlas::from_json(&format!(
+        r#"{{
+            "frames": {{ {frames} }},
+            "meta": {{ "frameTags": [{{ "name": "walk", "from": 2, "to": 10 }}] }}
+        }}"#
+    ))
+    .unwrap();
+    assert_eq!(sheet.len(), 12);
+    assert_eq!(sheet.index_of("walk 2.png"), Some(2));
+    assert_eq!(sheet.index_of("walk 10.png"), Some(10));
+
+    let walk = SpriteAnimation::from_tag(&sheet, "walk", 0.1).unwrap();
+    let starts = walk
+        .frames
+        .iter()
+        .map(|frame| frame.region.min.x)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        starts,
+        (2..=10).map(|index| index as f32 * 8.0).collect::<Vec<_>>()
+    );
+}
+
+fn frames(count: usize) -> Vec<AnimationFrame> {
+    (0..count)
+        .map(|index| AnimationFrame {
+            region: Rect::new(index as f32, 0.0, 1.0, 1.0),
+            duration: 0.1,
+            event: None,
+        })
+        .collect()
+}
+
+#[test]
+fn animations_advance_loop_and_send_events() {
+    let mut looping = SpriteAnimation::new("idle", frames(3)).with_event(2, "step");
+    assert!(looping.advance(0.05).is_empty());
+    assert_eq!(looping.frame(), 0);
+
+    let events = looping.advance(0.2);
+    assert_eq!(looping.frame(), 2);
+    assert_eq!(
+        events[0].kind,
+        AnimationEventKind::Frame {
+            name: String::from("step"),
+            frame: 2
+        }
+    );
+    let events = looping.advance(0.1);
+    assert_eq!(looping.frame(), 0);
+    assert_eq!(events[0].kind, AnimationEventKind::Looped);
+
+    let mut once = SpriteAnimation::new("attack", frames(2))
+        .with_mode(PlaybackMode::Once)
+        .with_speed(2.0);
+    let events = once.advance(0.5);
+    assert!(once.is_finished());
+    assert_eq!(once.frame(), 1);
+    assert_eq!(events.last().unwrap().kind, AnimationEventKind::Finished);
+    assert!(once.advance(1.0).is_empty());
+
+    let mut ping_pong = SpriteAnimation::new("walk", frames(3)).with_mode(PlaybackMode::PingPong);
+    let visited = (0..5)
+        .map(|_| {
+            ping_pong.advance(0.1);
+            ping_pong.frame()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(visited, vec![1, 2, 1, 0, 1]);
+}
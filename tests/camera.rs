@@ -0,0 +1,105 @@
+use glam::{Vec3, Vec4Swizzles};
+use starstruck::renderer::scene::{PipelineKey, ScenePass};
+use starstruck::renderer::{
+    Camera, CameraTarget, DebugRenderSettings, Projection, RenderTargetDescriptor, RenderTargets,
+};
+
+fn assert_close(actual: Vec3, expected: Vec3) {
+    assert!(
+        actual.abs_diff_eq(expected, 1e-5),
+        "{actual} isn't {expected}"
+    );
+}
+
+#[test]
+fn projections_scale_the_width_by_the_aspect_ratio() {
+    let perspective = Projection::Perspective {
+        fov_y_degrees: 90.0,
+        near: 0.1,
+        far: 100.0,
+    };
+    // A 90 degree field of view sees as far up as it sees ahead
+    let corner = perspective.matrix(2.0) * Vec3::new(1.0, 1.0, -1.0).extend(1.0);
+    assert_close(
+        corner.xyz() / corner.w,
+        Vec3::new(0.5, 1.0, corner.z / corner.w),
+    );
+    let square = perspective.matrix(1.0) * Vec3::new(1.0, 1.0, -1.0).extend(1.0);
+    assert_close(
+        square.xyz() / square.w,
+        Vec3::new(1.0, 1.0, square.z / square.w),
+    );
+
+    let orthographic = Projection::Orthographic {
+        height: 10.0,
+        near: 0.0,
+        far: 100.0,
+    };
+    let matrix = orthographic.matrix(2.0);
+    assert_close(
+        matrix.project_point3(Vec3::new(10.0, 5.0, 0.0)),
+        Vec3::new(1.0, 1.0, 0.0),
+    );
+    assert_close(
+        matrix.project_point3(Vec3::new(-10.0, -5.0, -100.0)),
+        Vec3::new(-1.0, -1.0, 1.0),
+    );
+}
+
+#[test]
+fn cameras_project_through_their_view() {
+    let camera = Camera::default();
+    let center = camera
+        .view_projection(16.0 / 9.0)
+        .project_point3(Vec3::ZERO);
+    assert_close(center, Vec3::new(0.0, 0.0, center.z));
+    assert!((0.0..1.0).contains(&center.z));
+
+    let camera = Camera {
+        position: Vec3::new(3.0, 4.0, 500.0),
+        look_at: Vec3::new(3.0, 4.0, 0.0),
+        ..Camera::orthographic(100.0)
+    };
+    let top_right =
+        camera
+            .view_projection(1.5)
+            .project_point3(Vec3::new(3.0 + 75.0, 4.0 + 50.0, 0.0));
+    assert_close(top_right, Vec3::new(1.0, 1.0, 0.5));
+}
+
+#[test]
+fn render_targets_are_resized_through_their_descriptor() {
+    let mut targets = RenderTargets::new();
+    let minimap = targets.create(RenderTargetDescriptor::new(256, 256).with_label("minimap"));
+    let mirror = targets.create(RenderTargetDescriptor::new(640, 480).with_depth_format(None));
+    assert_ne!(minimap, mirror);
+
+    let descriptor = targets.get_mut(minimap).unwrap();
+    descriptor.width = 512;
+    descriptor.height = 128;
+    let descriptor = targets.get(minimap).unwrap();
+    assert_eq!((descriptor.width, descriptor.height), (512, 128));
+    assert_eq!(descriptor.label.as_deref(), Some("minimap"));
+
+    assert_eq!(targets.remove(mirror).unwrap().depth_format, None);
+    assert!(targets.get(mirror).is_none());
+    // Ids aren't reused, so a removed target can't be mistaken for a new one
+    let reflection = targets.create(RenderTargetDescriptor::new(64, 64));
+    assert_ne!(reflection, mirror);
+    assert_eq!(targets.iter().count(), 2);
+}
+
+#[test]
+fn window_cameras_draw_with_depth() {
+    let debug = DebugRenderSettings::default();
+    let window = PipelineKey::for_target(CameraTarget::Window, |_| None, &debug).unwrap();
+    assert_eq!(window.depth_format, Some(ScenePass::DEPTH_FORMAT));
+
+    let mut targets = RenderTargets::new();
+    let flat = targets.create(RenderTargetDescriptor::new(64, 64).with_depth_format(None));
+    let key = PipelineKey::for_target(CameraTarget::Texture(flat), |id| targets.get(id), &debug);
+    assert_eq!(key.unwrap().depth_format, None);
+    targets.remove(flat);
+    let key = PipelineKey::for_target(CameraTarget::Texture(flat), |id| targets.get(id), &debug);
+    assert!(key.is_none());
+}
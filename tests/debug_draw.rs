@@ -0,0 +1,93 @@
+use glam::{Mat4, Vec3};
+use starry_ecs::World;
+use starstruck::renderer::debug::{draw_normals_system, face_normal};
+use starstruck::renderer::{DebugDraw, Mesh, RendererSettings};
+use starstruck::shapes::Triangle;
+use wgpu::Color;
+
+#[test]
+fn shapes_are_queued_as_lines() {
+    let mut debug = DebugDraw::new();
+    debug.aabb(Vec3::ZERO, Vec3::ONE, Color::WHITE);
+    assert_eq!(debug.lines().len(), 12);
+    assert!(debug
+        .lines()
+        .iter()
+        .all(|line| (line.end - line.start).length() == 1.0));
+
+    debug.clear();
+    debug.sphere(Vec3::new(1.0, 2.0, 3.0), 2.0, Color::RED);
+    assert_eq!(debug.lines().len(), 96);
+    assert!(debug
+        .lines()
+        .iter()
+        .all(|line| ((line.start - Vec3::new(1.0, 2.0, 3.0)).length() - 2.0).abs() < 1e-4));
+
+    debug.clear();
+    debug.axes(Mat4::from_translation(Vec3::X), 2.0);
+    assert_eq!(debug.lines().len(), 15);
+    assert_eq!(debug.lines()[0].start, Vec3::X);
+    assert_eq!(debug.lines()[0].end, Vec3::new(3.0, 0.0, 0.0));
+    assert_eq!(debug.lines()[5].color, Color::GREEN);
+}
+
+#[test]
+fn shapes_only_last_one_frame() {
+    let world = World::new().add_resource(DebugDraw::new());
+    {
+        let mut debug = world.get_resource_mut::<DebugDraw>();
+        debug.line(Vec3::ZERO, Vec3::Y, Color::BLUE);
+        debug.text(Vec3::Y, "up", Color::BLUE);
+    }
+    assert_eq!(world.get_resource::<DebugDraw>().labels()[0].text, "up");
+
+    DebugDraw::update_system(&world);
+    let debug = world.get_resource::<DebugDraw>();
+    assert!(debug.lines().is_empty());
+    assert!(debug.labels().is_empty());
+}
+
+#[test]
+fn normals_follow_the_winding_of_mesh_triangles() {
+    let (vertices, _) = Triangle::new(
+        (0.0, 0.0, 0.0, 1.0),
+        (1.0, 0.0, 0.0, 1.0),
+        (0.0, 1.0, 0.0, 1.0),
+        (1.0, 1.0, 1.0, 1.0),
+    )
+    .into_raw();
+    let mut settings = RendererSettings::default();
+    settings.debug.show_normals = true;
+    settings.debug.normal_length = 2.0;
+    let world = World::new()
+        .add_resource(settings)
+        .add_resource(DebugDraw::new())
+        .add_component(Mesh::new(vertices).with_transform(Mat4::from_translation(Vec3::Z)));
+
+    draw_normals_system(&world);
+    let debug = world.get_resource::<DebugDraw>();
+    assert_eq!(debug.lines().len(), 1);
+    let line = debug.lines()[0];
+    assert!((line.start - Vec3::new(1.0 / 3.0, 1.0 / 3.0, 1.0)).length() < 1e-5);
+    assert!((line.end - line.start - Vec3::new(0.0, 0.0, 2.0)).length() < 1e-5);
+
+    assert_eq!(face_normal(Vec3::ZERO, Vec3::Y, Vec3::X), -Vec3::Z);
+}
+
+#[test]
+fn normals_stay_perpendicular_under_uneven_scale() {
+    let mesh = Mesh::new(vec![]).with_transform(Mat4::from_scale(Vec3::new(2.0, 1.0, 1.0)));
+    let normal = Vec3::new(1.0, 1.0, 0.0).normalize();
+    let tangent = Vec3::new(1.0, -1.0, 0.0);
+
+    let moved_tangent = mesh.transform.transform_vector3(tangent);
+    assert!(
+        mesh.transform
+            .transform_vector3(normal)
+            .dot(moved_tangent)
+            .abs()
+            > 0.1
+    );
+    let moved_normal = mesh.normal_matrix().transform_vector3(normal);
+    assert!(moved_normal.dot(moved_tangent).abs() < 1e-5);
+}
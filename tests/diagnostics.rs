@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+use starstruck::diagnostics::{Profiler, Track};
+use starstruck::StarstruckEngine;
+
+#[test]
+fn frames_are_kept_up_to_the_capacity() {
+    let mut profiler = Profiler::new().with_capacity(2);
+    let start = Instant::now();
+    let at = |millis: u64| start + Duration::from_millis(millis);
+
+    profiler.record("ignored", at(0), at(1));
+    for frame in 0..4 {
+        profiler.begin_frame(at(frame * 20));
+        profiler.record("systems", at(frame * 20), at(frame * 20 + 5));
+        profiler.record_gpu("scene", at(frame * 20 + 6), Duration::from_millis(3));
+    }
+    profiler.begin_frame(at(80));
+
+    let frames = profiler.frames().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0].index, 2);
+    assert_eq!(frames[1].duration, Duration::from_millis(20));
+    assert_eq!(frames[1].time("systems"), Duration::from_millis(5));
+    assert_eq!(frames[1].spans[1].track, Track::Gpu);
+    assert!((profiler.fps() - 50.0).abs() < 0.01);
+}
+
+#[test]
+fn updates_are_exported_as_a_chrome_trace() {
+    let mut engine = StarstruckEngine::new_empty("Diagnostics").add_resource(Profiler::new());
+    engine.update();
+    engine.update();
+
+    let profiler = engine.world().get_resource::<Profiler>();
+    assert_eq!(profiler.frames().count(), 1);
+    let trace: serde_json::Value = serde_json::from_str(&profiler.chrome_trace()).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| event["name"] == "systems" && event["ph"] == "X"));
+    assert!(events.iter().any(|event| event["name"] == "frame 0"));
+}
@@ -0,0 +1,217 @@
+use base64::Engine;
+use glam::{Mat4, Vec3};
+use starry_ecs::World;
+use starstruck::assets::{GltfError, GltfScene, GltfWarning, SceneLight, SceneNode};
+use starstruck::renderer::{Camera, DirectionalLight, Images, Mesh, Projection};
+
+/// A triangle's positions followed by its indices, padded to 4 bytes
+fn triangle_buffer() -> Vec<u8> {
+    let positions: [f32; 9] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+    let indices: [u16; 4] = [0, 1, 2, 0];
+    let mut buffer = bytemuck::cast_slice::<f32, u8>(&positions).to_vec();
+    buffer.extend_from_slice(bytemuck::cast_slice(&indices));
+    buffer
+}
+
+fn png() -> Vec<u8> {
+    let mut png = vec![];
+    let mut encoder = png::Encoder::new(&mut png, 1, 1);
+    encoder.set_color(png::ColorType::Rgba);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[255, 0, 0, 255]).unwrap();
+    writer.finish().unwrap();
+    png
+}
+
+/// A parent node moved up by 2 with a child holding the triangle, a camera and two lights
+fn document(buffer: &str, image: &str) -> String {
+    format!(
+        r#"{{
+  "asset": {{ "version": "2.0" }},
+  "extensionsUsed": ["KHR_lights_punctual", "KHR_materials_clearcoat"],
+  "extensions": {{ "KHR_lights_punctual": {{ "lights": [
+    {{ "type": "directional", "color": [1, 0.5, 0], "intensity": 3 }},
+    {{ "type": "point" }}
+  ] }} }},
+  "scene": 0,
+  "scenes": [{{ "nodes": [0, 2, 3] }}],
+  "nodes": [
+    {{ "name": "parent", "translation": [0, 2, 0], "children": [1] }},
+    {{ "name": "triangle", "mesh": 0, "scale": [2, 2, 2] }},
+    {{ "name": "camera", "camera": 0, "translation": [0, 0, 5] }},
+    {{ "name": "sun", "rotation": [-0.7071068, 0, 0, 0.7071068],
+       "children": [4], "extensions": {{ "KHR_lights_punctual": {{ "light": 0 }} }} }},
+    {{ "name": "bulb", "extensions": {{ "KHR_lights_punctual": {{ "light": 1 }} }} }}
+  ],
+  "cameras": [{{ "type": "perspective", "perspective": {{ "yfov": 1.0, "znear": 0.1 }} }}],
+  "meshes": [{{ "name": "triangle", "primitives": [{{
+    "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0
+  }}] }}],
+  "materials": [{{
+    "name": "red",
+    "pbrMetallicRoughness": {{
+      "baseColorFactor": [1, 1, 1, 0.5], "baseColorTexture": {{ "index": 0 }},
+      "metallicFactor": 0.25, "roughnessFactor": 0.75
+    }},
+    "emissiveFactor": [1, 1, 1],
+    "alphaMode": "BLEND"
+  }}],
+  "textures": [{{ "source": 0 }}],
+  "images": [{{ {image} }}],
+  "buffers": [{{ "byteLength": 44{buffer} }}],
+  "bufferViews": [
+    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+  ],
+  "accessors": [
+    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+       "min": [0, 0, 0], "max": [1, 1, 0] }},
+    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+  ]
+}}"#
+    )
+}
+
+#[test]
+fn scenes_are_imported_with_their_hierarchy() {
+    let base64 = base64::engine::general_purpose::STANDARD;
+    let json = document(
+        &format!(
+            r#", "uri": "data:application/octet-stream;base64,{}""#,
+            base64.encode(triangle_buffer())
+        ),
+        &format!(r#""uri": "data:image/png;base64,{}""#, base64.encode(png())),
+    );
+    let mut images = Images::new();
+    let scene = GltfScene::from_slice(json.as_bytes(), None, &mut images).unwrap();
+
+    let names = scene
+        .nodes
+        .iter()
+        .map(|node| node.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["parent", "triangle", "camera", "sun", "bulb"]);
+    assert_eq!(scene.nodes[0].children, [1]);
+    assert_eq!(scene.nodes[1].parent, Some(0));
+    assert_eq!(
+        scene.nodes[1].global_transform,
+        Mat4::from_translation(Vec3::Y * 2.0) * Mat4::from_scale(Vec3::splat(2.0))
+    );
+
+    let material = &scene.materials[0];
+    assert_eq!((material.metallic, material.roughness), (0.25, 0.75));
+    assert_eq!(material.emissive, [1.0; 3]);
+    let texture = material.base_color_texture.unwrap();
+    assert_eq!(images.get(texture).unwrap().data, [255, 0, 0, 255]);
+
+    let meshes = scene.world_meshes();
+    assert_eq!(meshes.len(), 1);
+    assert_eq!(meshes[0].vertices.len(), 3);
+    assert_eq!(meshes[0].vertices[1].position, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(meshes[0].vertices[0].color, [1.0, 1.0, 1.0, 0.5]);
+    assert_eq!(meshes[0].texture, Some(texture));
+    assert_eq!(meshes[0].transform, scene.nodes[1].global_transform);
+
+    let camera = scene.nodes[2].camera.clone().unwrap();
+    assert_eq!(camera.position, Vec3::new(0.0, 0.0, 5.0));
+    assert!((camera.look_at - Vec3::new(0.0, 0.0, 4.0)).length() < 1e-5);
+    assert!(matches!(camera.projection, Projection::Perspective { far, .. } if far > 100.0));
+    // Rotated to point down
+    let Some(SceneLight::Directional(sun)) = scene.nodes[3].light.clone() else {
+        panic!("expected a directional light");
+    };
+    assert!((sun.direction - Vec3::NEG_Y).length() < 1e-5);
+    assert_eq!((sun.color.g, sun.illuminance), (0.5, 3.0));
+
+    assert_eq!(
+        scene.warnings,
+        [
+            GltfWarning::UnsupportedExtension {
+                name: String::from("KHR_materials_clearcoat"),
+                required: false
+            },
+            GltfWarning::UnsupportedLight {
+                node: String::from("bulb"),
+                kind: String::from("point")
+            },
+        ]
+    );
+}
+
+#[test]
+fn binary_scenes_are_spawned() {
+    // The BIN chunk holds the buffer and the image
+    let mut bin = triangle_buffer();
+    let png = png();
+    let image = r#""bufferView": 2, "mimeType": "image/png""#;
+    let json = document("", image).replace(
+        r#"{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }"#,
+        &format!(
+            r#"{{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }},
+    {{ "buffer": 0, "byteOffset": 44, "byteLength": {} }}"#,
+            png.len()
+        ),
+    );
+    let json = json.replace(
+        r#""byteLength": 44"#,
+        &format!(r#""byteLength": {}"#, 44 + png.len()),
+    );
+    bin.extend_from_slice(&png);
+
+    let pad = |mut chunk: Vec<u8>, with: u8| {
+        chunk.resize(chunk.len().div_ceil(4) * 4, with);
+        chunk
+    };
+    let json = pad(json.into_bytes(), b' ');
+    let bin = pad(bin, 0);
+    let mut glb = vec![];
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(12 + 8 + json.len() as u32 + 8 + bin.len() as u32).to_le_bytes());
+    for (kind, chunk) in [(b"JSON", &json), (b"BIN\0", &bin)] {
+        glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        glb.extend_from_slice(kind);
+        glb.extend_from_slice(chunk);
+    }
+
+    let mut images = Images::new();
+    let scene = GltfScene::from_slice(&glb, None, &mut images).unwrap();
+    assert_eq!(images.iter().count(), 1);
+
+    let world = scene.spawn(World::new());
+    assert_eq!(world.try_get_components::<Mesh>().unwrap().len(), 1);
+    assert_eq!(world.try_get_components::<Camera>().unwrap().len(), 1);
+    assert_eq!(
+        world
+            .try_get_components::<DirectionalLight>()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(world.try_get_components::<SceneNode>().unwrap().len(), 5);
+}
+
+#[test]
+fn images_past_the_end_of_their_buffer_are_errors() {
+    let base64 = base64::engine::general_purpose::STANDARD;
+    // The file says the buffer holds an image after the triangle, but it stops before
+    let json = document(
+        &format!(
+            r#", "uri": "data:application/octet-stream;base64,{}""#,
+            base64.encode(triangle_buffer())
+        ),
+        r#""bufferView": 2, "mimeType": "image/png""#,
+    )
+    .replace(
+        r#"{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }"#,
+        r#"{ "buffer": 0, "byteOffset": 36, "byteLength": 6 },
+    { "buffer": 0, "byteOffset": 44, "byteLength": 100 }"#,
+    )
+    .replace(r#""byteLength": 44"#, r#""byteLength": 144"#);
+
+    let error = GltfScene::from_slice(json.as_bytes(), None, &mut Images::new()).unwrap_err();
+    assert!(matches!(
+        error,
+        GltfError::BufferView { view: 2, buffer: 0 }
+    ));
+}
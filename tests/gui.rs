@@ -0,0 +1,31 @@
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::World;
+use starstruck::renderer::{Gui, GuiPlugin};
+use starstruck::StarstruckEngine;
+
+fn panel(world: &World) {
+    let gui = world.get_resource::<Gui>();
+    egui::Window::new("Stats").show(gui.context(), |ui| {
+        ui.label("fps");
+    });
+}
+
+#[test]
+fn systems_build_panels_through_the_gui_resource() {
+    let mut engine = StarstruckEngine::new_empty("Gui")
+        .add_plugin(GuiPlugin)
+        .add_system(DefaultOrdering::Run, panel);
+
+    // The renderer starts frames with the window's input, a default one does without it
+    engine
+        .world()
+        .get_resource::<Gui>()
+        .context()
+        .begin_frame(egui::RawInput::default());
+    engine.update();
+
+    let gui = engine.world().get_resource::<Gui>();
+    assert!(!gui.wants_keyboard_input());
+    let output = gui.context().end_frame();
+    assert!(!output.shapes.is_empty());
+}
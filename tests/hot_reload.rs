@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use starry_ecs::World;
+use starstruck::assets::{sync_assets_system, AssetServer};
+use starstruck::renderer::{Image, Images, Mesh, PostEffect, PostProcessing};
+
+const INVERT: &str = "@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0) - textureSample(input_texture, input_sampler, in.uv);
+}
+";
+
+fn png(rgba: [u8; 4]) -> Vec<u8> {
+    let mut png = vec![];
+    let mut encoder = png::Encoder::new(&mut png, 1, 1);
+    encoder.set_color(png::ColorType::Rgba);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&rgba).unwrap();
+    writer.finish().unwrap();
+    png
+}
+
+fn stl(triangles: u32) -> Vec<u8> {
+    let (vertices, _) = starstruck::shapes::Plane::new(1.0, 1.0).into_raw();
+    let mesh = Mesh::new(vertices[..triangles as usize * 3].to_vec());
+    mesh.to_stl(Default::default())
+}
+
+/// Waits for the server to finish loading, then copies the new versions into the world
+fn sync(world: &World) {
+    // Queued files are reloaded first, then the new versions are copied once they're loaded
+    sync_assets_system(world);
+    wait(&world.get_resource::<AssetServer>());
+    sync_assets_system(world);
+}
+
+/// Writes the file and queues it like the watcher would once it saw the change
+fn write(world: &World, directory: &Path, file: &str, contents: impl AsRef<[u8]>) {
+    std::fs::write(directory.join(file), contents).unwrap();
+    world.get_resource::<AssetServer>().mark_changed(file);
+}
+
+fn wait(server: &AssetServer) {
+    let start = Instant::now();
+    while !server.is_idle() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "reloading timed out"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn changed_files_are_reloaded_behind_their_handles() {
+    let temp = tempfile::tempdir().unwrap();
+    let directory = temp.path();
+    std::fs::write(directory.join("pixel.png"), png([255, 0, 0, 255])).unwrap();
+    std::fs::write(directory.join("plane.stl"), stl(1)).unwrap();
+    std::fs::write(directory.join("invert.wgsl"), INVERT).unwrap();
+
+    let server = AssetServer::new(directory);
+    let image = server.load::<Image>("pixel.png");
+    let plane = server.load::<Mesh>("plane.stl");
+    let shader = server.load("invert.wgsl");
+    let world = World::new()
+        .add_resource(server)
+        .add_resource(Images::new())
+        .add_resource(PostProcessing::new().with(PostEffect::custom_shader("invert", shader)))
+        .add_component(Mesh::from_asset(plane));
+    sync(&world);
+
+    let image_id = world.get_resource::<AssetServer>().image(image).unwrap();
+    assert_eq!(
+        world.get_resource::<Images>().get(image_id).unwrap().data,
+        [255, 0, 0, 255]
+    );
+    assert_eq!(world.get_components::<Mesh>()[0].vertices.len(), 3);
+    let source = |world: &World| match &world.get_resource::<PostProcessing>().effects[0] {
+        PostEffect::Custom { source, .. } => source.clone(),
+        effect => panic!("unexpected effect {:?}", effect),
+    };
+    assert_eq!(source(&world), INVERT);
+
+    write(&world, directory, "pixel.png", png([0, 0, 255, 255]));
+    write(&world, directory, "plane.stl", stl(2));
+    sync(&world);
+    assert_eq!(
+        world.get_resource::<Images>().get(image_id).unwrap().data,
+        [0, 0, 255, 255]
+    );
+    assert_eq!(world.get_components::<Mesh>()[0].vertices.len(), 6);
+    // The image keeps its id, so whatever draws it doesn't change
+    assert_eq!(
+        world.get_resource::<AssetServer>().image(image),
+        Some(image_id)
+    );
+
+    write(&world, directory, "invert.wgsl", "fn fs_main( {");
+    sync(&world);
+    // A shader that doesn't compile is loaded but not used
+    let loaded = world.get_resource::<AssetServer>().get(shader).unwrap();
+    assert!(loaded.source.starts_with("fn"));
+    assert_eq!(source(&world), INVERT);
+    let edited = INVERT.replace("1.0", "0.5");
+    write(&world, directory, "invert.wgsl", &edited);
+    sync(&world);
+    assert_eq!(source(&world), edited);
+}
+
+#[test]
+fn changed_paths_are_matched_to_their_handles() {
+    let temp = tempfile::tempdir().unwrap();
+    let directory = temp.path();
+    std::fs::create_dir_all(directory.join("models")).unwrap();
+    std::fs::write(directory.join("models/pixel.png"), png([255; 4])).unwrap();
+
+    let server = AssetServer::new(directory);
+    let image = server.load::<Image>("models/pixel.png");
+    wait(&server);
+    assert_eq!(server.version(image), 1);
+
+    // Watchers report paths with the platform's separators, not always normalized
+    let changed = ["models", "..", "models", "pixel.png"]
+        .iter()
+        .collect::<PathBuf>();
+    server.mark_changed(changed);
+    server.reload_changed();
+    wait(&server);
+    assert_eq!(server.version(image), 2);
+
+    // Each change is only reloaded once
+    server.reload_changed();
+    wait(&server);
+    assert_eq!(server.version(image), 2);
+}
@@ -0,0 +1,137 @@
+use starry_ecs::World;
+use starstruck::commands::{Commands, Despawned, WorldExt};
+use starstruck::inspector::{inspector_system, Field, Inspector, InspectorPlugin, Reflect};
+use starstruck::renderer::{DirectionalLight, Gui, RendererSettings};
+use starstruck::StarstruckEngine;
+
+#[test]
+fn reflected_fields_edit_the_value() {
+    let mut light = DirectionalLight::default();
+    for (name, field) in light.fields() {
+        match (name, field) {
+            ("illuminance", Field::F32(value)) => *value = 3.0,
+            ("cast_shadows", Field::Bool(value)) => *value = false,
+            _ => {}
+        }
+    }
+    assert_eq!(light.illuminance, 3.0);
+    assert!(!light.cast_shadows);
+
+    let mut settings = RendererSettings::default();
+    let mut fields = settings.fields();
+    let Some((_, Field::Struct(debug))) = fields.iter_mut().find(|(name, _)| *name == "debug")
+    else {
+        panic!("debug settings aren't reflected");
+    };
+    for (name, field) in debug.fields() {
+        if let ("wireframe", Field::Bool(value)) = (name, field) {
+            *value = true;
+        }
+    }
+    drop(fields);
+    assert!(settings.debug.wireframe);
+}
+
+#[test]
+fn spawned_components_are_added_after_the_update() {
+    let mut engine = StarstruckEngine::new_empty("Inspector").add_plugin(InspectorPlugin);
+    {
+        let inspector = engine.world().get_resource::<Inspector>();
+        assert!(inspector
+            .components()
+            .any(|name| name == "DirectionalLight"));
+        assert!(inspector.resources().any(|name| name == "RendererSettings"));
+
+        let mut commands = engine.world().get_resource_mut::<Commands>();
+        assert!(inspector.spawn("DirectionalLight", &mut commands));
+        assert!(!inspector.spawn("Sprite", &mut commands));
+        assert_eq!(commands.len(), 1);
+    }
+    assert!(engine
+        .world()
+        .try_get_components::<DirectionalLight>()
+        .is_err());
+
+    engine.update();
+    assert_eq!(engine.world().get_components::<DirectionalLight>().len(), 1);
+    assert!(engine.world().get_resource::<Commands>().is_empty());
+}
+
+#[test]
+fn despawned_components_are_left_out_of_queries() {
+    let mut engine = StarstruckEngine::new_empty("Inspector")
+        .add_plugin(InspectorPlugin)
+        .add_component(DirectionalLight::default())
+        .add_component(DirectionalLight {
+            illuminance: 2.0,
+            ..Default::default()
+        });
+    {
+        let inspector = engine.world().get_resource::<Inspector>();
+        let mut commands = engine.world().get_resource_mut::<Commands>();
+        assert!(inspector.despawn("DirectionalLight", 0, &mut commands));
+        assert!(!inspector.despawn("Missing", 0, &mut commands));
+    }
+    engine.update();
+
+    let world = engine.world();
+    assert!(world
+        .get_resource::<Despawned>()
+        .contains::<DirectionalLight>(0));
+    let lights = world.try_get_spawned::<DirectionalLight>().unwrap();
+    assert_eq!(lights.len(), 1);
+    assert_eq!(lights[0].illuminance, 2.0);
+    drop(lights);
+
+    world
+        .get_resource_mut::<Commands>()
+        .despawn::<DirectionalLight>(1);
+    engine.update();
+    assert!(engine
+        .world()
+        .try_get_spawned::<DirectionalLight>()
+        .is_err());
+
+    // New components take the place of despawned ones instead of growing the world
+    engine
+        .world()
+        .get_resource_mut::<Commands>()
+        .spawn(DirectionalLight {
+            illuminance: 3.0,
+            ..Default::default()
+        });
+    engine.update();
+    let world = engine.world();
+    assert_eq!(world.get_components::<DirectionalLight>().len(), 2);
+    let lights = world.try_get_spawned::<DirectionalLight>().unwrap();
+    assert_eq!(lights.len(), 1);
+    assert_eq!(lights[0].illuminance, 3.0);
+    assert!(world
+        .get_resource::<Despawned>()
+        .contains::<DirectionalLight>(1));
+}
+
+fn panel_shapes(world: &World, open: bool) -> usize {
+    world.get_resource_mut::<Inspector>().open = open;
+    let gui = world.get_resource::<Gui>();
+    gui.context().begin_frame(egui::RawInput::default());
+    drop(gui);
+    inspector_system(world);
+    world
+        .get_resource::<Gui>()
+        .context()
+        .end_frame()
+        .shapes
+        .len()
+}
+
+#[test]
+fn panel_is_only_shown_when_open() {
+    let engine = StarstruckEngine::new_empty("Inspector")
+        .add_plugin(InspectorPlugin)
+        .add_resource(RendererSettings::default())
+        .add_component(DirectionalLight::default());
+
+    assert_eq!(panel_shapes(engine.world(), false), 0);
+    assert!(panel_shapes(engine.world(), true) > 0);
+}
@@ -0,0 +1,31 @@
+use starstruck::logging::{LogSettings, LoggingError};
+
+#[test]
+fn events_are_filtered_and_written_to_the_file() {
+    std::env::remove_var("RUST_LOG");
+    assert!(matches!(
+        LogSettings::new().with_filter("info,=[").init(),
+        Err(LoggingError::Filter(_))
+    ));
+
+    let path = std::env::temp_dir().join(format!("starstruck-{}.log", std::process::id()));
+    LogSettings::new()
+        .with_filter("warn,logging=debug")
+        .with_file(&path)
+        .with_ansi(false)
+        .init()
+        .unwrap();
+    tracing::debug!("shown because of the target's directive");
+    tracing::trace!("hidden");
+    tracing::info!(target: "other", "hidden too");
+
+    assert!(matches!(
+        LogSettings::new().init(),
+        Err(LoggingError::AlreadyInstalled(_))
+    ));
+    let log = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(log.contains("DEBUG"));
+    assert!(log.contains("shown because of the target's directive"));
+    assert!(!log.contains("hidden"));
+}
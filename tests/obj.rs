@@ -0,0 +1,132 @@
+use starstruck::assets::{ObjError, ObjMaterial, ObjModel};
+use starstruck::renderer::Images;
+use starstruck::shapes::UvSphere;
+
+const CUBE: &str = "
+mtllib cube.mtl
+mtllib missing.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1 1 0 0
+vt 0 0
+vt 1 0
+vt 1 1
+vn 0 0 -1
+o front
+usemtl red
+f 1//1 4//1 3//1 2//1
+o top
+usemtl textured
+f -5/-3 -4/-2 -1/-1 # the last vertex is colored
+";
+
+fn directory(name: &str) -> std::path::PathBuf {
+    let directory =
+        std::env::temp_dir().join(format!("starstruck-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+#[test]
+fn models_load_with_their_materials() {
+    let directory = directory("obj");
+    std::fs::write(directory.join("cube.obj"), CUBE).unwrap();
+    std::fs::write(
+        directory.join("cube.mtl"),
+        "newmtl red\nKd 1 0 0\nd 0.5\n\nnewmtl textured\nKd 0.5 0.5 0.5\nmap_Kd -s 2 2 1 checker.png\n",
+    )
+    .unwrap();
+    let mut png = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut png, 2, 1);
+        encoder.set_color(png::ColorType::Rgba);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[255; 8]).unwrap();
+    }
+    std::fs::write(directory.join("checker.png"), png).unwrap();
+
+    let model = ObjModel::load(directory.join("cube.obj")).unwrap();
+    assert_eq!(model.materials.len(), 2);
+    assert_eq!(model.materials[0].diffuse, [1.0, 0.0, 0.0, 0.5]);
+    assert_eq!(
+        model.materials[1].texture.as_deref(),
+        Some(directory.join("checker.png").as_path())
+    );
+    let [front, top] = &model.meshes[..] else {
+        panic!("expected 2 meshes, got {}", model.meshes.len());
+    };
+    // The quad is split in two triangles
+    assert_eq!((front.name.as_str(), front.vertices.len()), ("front", 6));
+    assert_eq!(front.vertices[0].normal, [0.0, 0.0, -1.0]);
+    assert_eq!(top.vertices[2].color, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(top.vertices[2].uv, [1.0, 0.0]);
+
+    let mut images = Images::new();
+    let meshes = model.meshes(&mut images).unwrap();
+    assert_eq!(meshes[0].vertices[0].color, [1.0, 0.0, 0.0, 0.5]);
+    assert_eq!(meshes[0].texture, None);
+    assert_eq!(meshes[1].vertices[2].color, [0.5, 0.0, 0.0, 1.0]);
+    let texture = meshes[1].texture.unwrap();
+    assert_eq!(images.get(texture).unwrap().size(), (2, 1));
+
+    assert!(matches!(
+        ObjModel::parse("v 0 0 0\nf 1 2 3"),
+        Err(ObjError::Parse { line: 2, .. })
+    ));
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn shapes_are_exported_and_load_back() {
+    let directory = directory("obj-export");
+    let (vertices, _) = UvSphere::new(1.5)
+        .with_segments(6, 4)
+        .with_color((0.2, 0.4, 0.6, 1.0))
+        .into_raw();
+    let mut model = ObjModel::from_vertices("sphere", vertices.clone());
+    model.materials.push(ObjMaterial::new("plain"));
+    model.meshes[0].material = Some(0);
+    model.save(directory.join("sphere.obj")).unwrap();
+
+    let obj = std::fs::read_to_string(directory.join("sphere.obj")).unwrap();
+    assert!(obj.starts_with("mtllib sphere.mtl\n"));
+    // Shared positions are only written once
+    assert!(obj.lines().filter(|line| line.starts_with("v ")).count() < vertices.len());
+
+    let loaded = ObjModel::load(directory.join("sphere.obj")).unwrap();
+    assert_eq!(loaded.materials[0].name, "plain");
+    assert_eq!(loaded.meshes[0].name, "sphere");
+    let reloaded = &loaded.meshes[0].vertices;
+    assert_eq!(reloaded.len(), vertices.len());
+    for (a, b) in vertices.iter().zip(reloaded.iter()) {
+        assert_eq!(a.position, b.position);
+        assert_eq!(a.normal, b.normal);
+        assert_eq!(a.color, b.color);
+        assert!((a.uv[0] - b.uv[0]).abs() < 1e-6 && (a.uv[1] - b.uv[1]).abs() < 1e-6);
+    }
+    std::fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn textures_keep_their_path_when_saved_next_to_the_model() {
+    let directory = directory("obj-textured");
+    let models = directory.join("models");
+    std::fs::create_dir_all(&models).unwrap();
+    std::fs::write(
+        models.join("quad.obj"),
+        "mtllib quad.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nusemtl tiles\nf 1 2 3\n",
+    )
+    .unwrap();
+    std::fs::write(models.join("quad.mtl"), "newmtl tiles\nmap_Kd tiles.png\n").unwrap();
+
+    let model = ObjModel::load(models.join("quad.obj")).unwrap();
+    model.save(models.join("copy.obj")).unwrap();
+    let mtl = std::fs::read_to_string(models.join("copy.mtl")).unwrap();
+    assert!(mtl.contains("map_Kd tiles.png\n"));
+
+    let copy = ObjModel::load(models.join("copy.obj")).unwrap();
+    assert_eq!(copy.materials[0].texture, Some(models.join("tiles.png")));
+    std::fs::remove_dir_all(directory).unwrap();
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use starstruck::assets::{
+    AssetServer, AssetSource, Compression, LoadState, ObjModel, Packer, Pak, PakError,
+};
+
+/// A temporary directory, removed with everything in it once the test ends
+fn directory() -> tempfile::TempDir {
+    let directory = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(directory.path().join("assets/models")).unwrap();
+    directory
+}
+
+#[test]
+fn directories_are_packed_and_read_back() {
+    let temp = directory();
+    let directory = temp.path();
+    let assets = directory.join("assets");
+    let repeated = "v 0 0 0\n".repeat(200);
+    std::fs::write(assets.join("models/points.obj"), &repeated).unwrap();
+    std::fs::write(assets.join("tiny.txt"), "hi").unwrap();
+
+    for compression in [Compression::Deflate, Compression::None] {
+        let archive = directory.join("assets.pak");
+        let packer = Packer::new()
+            .with_compression(compression)
+            .with_directory(&assets)
+            .unwrap();
+        assert_eq!(packer.len(), 2);
+        packer.save(&archive).unwrap();
+
+        let pak = Pak::open(&archive).unwrap();
+        assert_eq!(
+            pak.files().collect::<Vec<_>>(),
+            ["models/points.obj", "tiny.txt"]
+        );
+        assert_eq!(pak.read("models/points.obj").unwrap(), repeated.as_bytes());
+        // Paths are resolved the same way on every platform
+        assert_eq!(pak.read("models/../tiny.txt").unwrap(), b"hi");
+        let (size, stored) = pak.size("models/points.obj").unwrap();
+        assert_eq!(size, repeated.len() as u64);
+        assert_eq!(stored < size, compression == Compression::Deflate);
+        // Compressing two bytes would make them bigger
+        assert_eq!(pak.size("tiny.txt"), Some((2, 2)));
+        assert!(matches!(
+            pak.read("missing.txt"),
+            Err(PakError::NotFound(_))
+        ));
+    }
+
+    std::fs::write(directory.join("fake.pak"), "not an archive").unwrap();
+    assert!(matches!(
+        Pak::open(directory.join("fake.pak")),
+        Err(PakError::NotAPak)
+    ));
+    let mut packer = Packer::new();
+    assert!(matches!(
+        packer.add_file("/absolute.txt", vec![]),
+        Err(PakError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn truncated_archives_are_rejected() {
+    let temp = directory();
+    let directory = temp.path();
+    let mut packer = Packer::new().with_compression(Compression::None);
+    packer.add_file("data.bin", vec![7; 1000]).unwrap();
+    let mut bytes = vec![];
+    packer.write(&mut bytes).unwrap();
+
+    let archive = directory.join("truncated.pak");
+    std::fs::write(&archive, &bytes[..bytes.len() - 10]).unwrap();
+    assert!(matches!(Pak::open(&archive), Err(PakError::Index)));
+
+    // An entry claiming more data than the file holds
+    let stored_size = bytes.len() - 1000 - 17;
+    bytes[stored_size..stored_size + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    std::fs::write(&archive, &bytes).unwrap();
+    assert!(matches!(Pak::open(&archive), Err(PakError::Index)));
+}
+
+#[test]
+fn the_asset_server_reads_from_archives() {
+    let temp = directory();
+    let directory = temp.path();
+    let assets = directory.join("assets");
+    std::fs::write(
+        assets.join("models/quad.obj"),
+        "mtllib quad.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nusemtl red\nf 1 2 3\n",
+    )
+    .unwrap();
+    std::fs::write(assets.join("models/quad.mtl"), "newmtl red\nKd 1 0 0\n").unwrap();
+    let archive = directory.join("assets.pak");
+    Packer::new()
+        .with_directory(&assets)
+        .unwrap()
+        .save(&archive)
+        .unwrap();
+    // Only the archive is read from now on
+    std::fs::remove_dir_all(&assets).unwrap();
+
+    let mut server =
+        AssetServer::from_source(AssetSource::Pak(Arc::new(Pak::open(&archive).unwrap())));
+    assert!(server.watch().is_err());
+    let model = server.load::<ObjModel>("models/quad.obj");
+    let missing = server.load::<ObjModel>("models/missing.obj");
+    let start = Instant::now();
+    while !server.is_idle() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "loading timed out"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    // The material library next to the model comes from the archive too
+    let model = server.get(model).unwrap();
+    assert_eq!(model.materials[0].diffuse, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(model.meshes[0].vertices.len(), 3);
+    assert!(matches!(server.state(missing), LoadState::Failed(_)));
+}
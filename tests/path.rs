@@ -0,0 +1,115 @@
+use glam::Vec2;
+use starstruck::shapes::{FillRule, LineCap, LineJoin, Path, PathCommand, PathError, StrokeStyle};
+
+fn area(triangles: &[[Vec2; 3]]) -> f32 {
+    triangles
+        .iter()
+        .map(|[a, b, c]| (*b - *a).perp_dot(*c - *a) / 2.0)
+        .sum()
+}
+
+fn covers(triangles: &[[Vec2; 3]], point: Vec2) -> bool {
+    triangles.iter().any(|[a, b, c]| {
+        (*b - *a).perp_dot(point - *a) >= 0.0
+            && (*c - *b).perp_dot(point - *b) >= 0.0
+            && (*a - *c).perp_dot(point - *c) >= 0.0
+    })
+}
+
+#[test]
+fn fills_follow_the_fill_rule() {
+    // Two squares wound the same way, the inner one is a hole only with even-odd
+    let nested = Path::from_svg("M-2-2H2V2H-2Z m1 1h2v2h-2z").unwrap();
+    let non_zero = nested.fill(FillRule::NonZero);
+    let even_odd = nested.fill(FillRule::EvenOdd);
+    assert!((area(&non_zero) - 16.0).abs() < 1e-4);
+    assert!((area(&even_odd) - 12.0).abs() < 1e-4);
+    assert!(covers(&non_zero, Vec2::ZERO));
+    assert!(!covers(&even_odd, Vec2::ZERO));
+    assert!(non_zero
+        .iter()
+        .chain(even_odd.iter())
+        .all(|[a, b, c]| (*b - *a).perp_dot(*c - *a) > 0.0));
+
+    // A self-intersecting star, its center is enclosed twice
+    let star = (0..5).fold(Path::new(), |path, i| {
+        let angle = (i * 2 % 5) as f32 / 5.0 * std::f32::consts::TAU;
+        let point = Vec2::new(angle.sin(), angle.cos());
+        if i == 0 {
+            path.move_to(point)
+        } else {
+            path.line_to(point)
+        }
+    });
+    let star = star.close();
+    assert!(covers(&star.fill(FillRule::NonZero), Vec2::ZERO));
+    assert!(!covers(&star.fill(FillRule::EvenOdd), Vec2::ZERO));
+
+    let circle = Path::from_svg("M1 0A1 1 0 0 1-1 0a1 1 0 0 1 2 0z").unwrap();
+    let circle = area(&circle.fill(FillRule::NonZero));
+    assert!((circle - std::f32::consts::PI).abs() < 0.05, "{}", circle);
+}
+
+#[test]
+fn strokes_have_joins_and_caps() {
+    let line = Path::new()
+        .move_to(Vec2::ZERO)
+        .line_to(Vec2::new(10.0, 0.0));
+    let butt = line.stroke(&StrokeStyle::new(2.0));
+    let square = line.stroke(&StrokeStyle::new(2.0).with_cap(LineCap::Square));
+    let round = line.stroke(&StrokeStyle::new(2.0).with_cap(LineCap::Round));
+    assert!((area(&butt) - 20.0).abs() < 1e-4);
+    assert!((area(&square) - 24.0).abs() < 1e-4);
+    assert!((area(&round) - 20.0 - std::f32::consts::PI).abs() < 0.05);
+
+    let outline = Path::from_svg("M0 0h4v4h-4z").unwrap();
+    let corner = Vec2::new(4.45, 4.45);
+    let stroke = |join| outline.stroke(&StrokeStyle::new(1.0).with_join(join));
+    assert!(covers(&stroke(LineJoin::Miter), corner));
+    assert!(!covers(&stroke(LineJoin::Round), corner));
+    assert!(covers(&stroke(LineJoin::Round), Vec2::new(4.3, 4.3)));
+    assert!(!covers(&stroke(LineJoin::Bevel), Vec2::new(4.3, 4.3)));
+    // Too sharp for the miter limit
+    let spike = Path::from_svg("M0 0L10 0.5L0 1").unwrap();
+    assert!(!covers(
+        &spike.stroke(&StrokeStyle::new(0.2)),
+        Vec2::new(10.5, 0.5)
+    ));
+}
+
+#[test]
+fn svg_paths_are_parsed() {
+    let path = Path::from_svg("m1,1 2 0 l0-2e0 Q4 0 4 1 T4 3 c1 0 1 1 1 1 S5 5 4 5z").unwrap();
+    let expected = Path::new()
+        .move_to(Vec2::new(1.0, 1.0))
+        .line_to(Vec2::new(3.0, 1.0))
+        .line_to(Vec2::new(3.0, -1.0))
+        .quadratic_to(Vec2::new(4.0, 0.0), Vec2::new(4.0, 1.0))
+        .quadratic_to(Vec2::new(4.0, 2.0), Vec2::new(4.0, 3.0))
+        .cubic_to(
+            Vec2::new(5.0, 3.0),
+            Vec2::new(5.0, 4.0),
+            Vec2::new(5.0, 4.0),
+        )
+        .cubic_to(
+            Vec2::new(5.0, 4.0),
+            Vec2::new(5.0, 5.0),
+            Vec2::new(4.0, 5.0),
+        )
+        .close();
+    assert_eq!(path.commands(), expected.commands());
+    assert_eq!(path.commands().last(), Some(&PathCommand::Close));
+
+    assert_eq!(
+        Path::from_svg("L1 1").unwrap_err(),
+        PathError::ExpectedMoveTo
+    );
+    assert_eq!(
+        Path::from_svg("M0 0 L1").unwrap_err(),
+        PathError::ExpectedNumber(7)
+    );
+    assert_eq!(
+        Path::from_svg("M0 0 X1 1").unwrap_err(),
+        PathError::UnknownCommand('X', 5)
+    );
+}
@@ -0,0 +1,81 @@
+use starry_ecs::resources::Resource;
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::World;
+use starstruck::events::Events;
+use starstruck::input::{Input, InputPlugin};
+use starstruck::plugin::{DefaultPlugins, Plugin};
+use starstruck::renderer::RendererPlugin;
+use starstruck::time::{Time, TimePlugin};
+use starstruck::StarstruckEngine;
+
+#[derive(Debug)]
+struct Counter {
+    runs: usize,
+    seen: usize,
+}
+impl Resource for Counter {}
+
+#[derive(Debug)]
+struct Ping;
+
+fn count(world: &World) {
+    let mut counter = world.get_resource_mut::<Counter>();
+    counter.runs += 1;
+    counter.seen += world.get_resource::<Events<Ping>>().len();
+    world.get_resource_mut::<Events<Ping>>().send(Ping);
+}
+
+struct CounterPlugin;
+
+impl Plugin for CounterPlugin {
+    fn build(&self, engine: StarstruckEngine) -> StarstruckEngine {
+        engine
+            .add_event::<Ping>()
+            .add_resource(Counter { runs: 0, seen: 0 })
+            .add_system(DefaultOrdering::Run, count)
+    }
+}
+
+#[test]
+fn plugin_registers_systems_resources_and_events() {
+    let mut engine = StarstruckEngine::new_empty("Plugin Test").add_plugin(CounterPlugin);
+    engine.update();
+    engine.update();
+    engine.update();
+
+    let counter = engine.world().get_resource::<Counter>();
+    assert_eq!(counter.runs, 3);
+    // Each ping is seen exactly once, on the update after it was sent
+    assert_eq!(counter.seen, 2);
+}
+
+#[test]
+fn engines_start_with_the_default_plugins() {
+    let engine = StarstruckEngine::new("Plugin Test");
+    assert!(engine.has_plugin::<DefaultPlugins>());
+    assert!(engine.has_plugin::<RendererPlugin>());
+    assert!(!StarstruckEngine::new_empty("Plugin Test").has_plugin::<TimePlugin>());
+}
+
+#[test]
+fn default_plugins_can_be_disabled() {
+    let plugins = DefaultPlugins::default().disable::<RendererPlugin>();
+    assert!(!plugins.contains::<RendererPlugin>());
+
+    let mut engine =
+        StarstruckEngine::new_empty("Plugin Test").add_plugin(plugins.disable::<InputPlugin>());
+    engine.update();
+
+    assert!(engine.has_plugin::<TimePlugin>());
+    assert!(!engine.has_plugin::<InputPlugin>());
+    assert!(engine.world().try_get_resource::<Input>().is_err());
+    assert_eq!(engine.world().get_resource::<Time>().frame_count(), 1);
+}
+
+#[test]
+#[should_panic]
+fn plugins_cannot_be_added_twice() {
+    StarstruckEngine::new_empty("Plugin Test")
+        .add_plugin(TimePlugin)
+        .add_plugin(TimePlugin);
+}
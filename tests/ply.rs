@@ -0,0 +1,94 @@
+use glam::{Mat4, Vec3};
+use starstruck::assets::{PlyError, PlyFormat};
+use starstruck::renderer::Mesh;
+use starstruck::shapes::UvSphere;
+
+const QUAD: &str = "ply
+format ascii 1.0
+comment a colored quad
+element vertex 4
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+element edge 1
+property int vertex1
+property int vertex2
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+1 1 0 0 0 255
+0 1 0 255 255 255
+4 0 1 2 3
+0 2
+";
+
+#[test]
+fn colored_polygons_are_triangulated() {
+    let mesh = Mesh::from_ply(QUAD.as_bytes()).unwrap();
+    // The quad is split into a fan of 2 triangles
+    assert_eq!(mesh.vertices.len(), 6);
+    let colors = mesh
+        .vertices
+        .iter()
+        .map(|vertex| vertex.color)
+        .collect::<Vec<_>>();
+    assert_eq!(colors[0], [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(colors[1], [0.0, 1.0, 0.0, 1.0]);
+    assert_eq!(colors[5], [1.0, 1.0, 1.0, 1.0]);
+    assert_eq!(mesh.vertices[4].position, [1.0, 1.0, 0.0, 1.0]);
+
+    let missing = QUAD.replace("4 0 1 2 3", "4 0 1 2 7");
+    assert!(matches!(
+        Mesh::from_ply(missing.as_bytes()),
+        Err(PlyError::Parse { ref element, index: 0, .. }) if element == "face"
+    ));
+    // Indices are read as numbers, so fractions and negatives have to be rejected
+    let fractional = QUAD.replace("4 0 1 2 3", "4 0 1 2.5 3");
+    assert!(Mesh::from_ply(fractional.as_bytes()).is_err());
+    let negative = QUAD.replace("4 0 1 2 3", "4 0 -1 2 3");
+    assert!(Mesh::from_ply(negative.as_bytes()).is_err());
+    assert!(matches!(
+        Mesh::from_ply(QUAD.replace("uchar red", "colour red").as_bytes()),
+        Err(PlyError::Header(_))
+    ));
+}
+
+#[test]
+fn meshes_round_trip_in_every_format() {
+    let (vertices, _) = UvSphere::new(1.0)
+        .with_segments(8, 4)
+        .with_color((0.2, 0.4, 0.6, 1.0))
+        .into_raw();
+    let mesh = Mesh::new(vertices.clone()).with_transform(Mat4::from_scale(Vec3::splat(2.0)));
+    for format in [
+        PlyFormat::Ascii,
+        PlyFormat::BinaryLittleEndian,
+        PlyFormat::BinaryBigEndian,
+    ] {
+        let ply = mesh.to_ply(format);
+        let header = String::from_utf8_lossy(&ply[..200]);
+        let count = header
+            .lines()
+            .find_map(|line| line.strip_prefix("element vertex "))
+            .unwrap();
+        // Corners shared between triangles are written once
+        assert!(count.parse::<usize>().unwrap() < vertices.len());
+
+        let loaded = Mesh::from_ply(&ply).unwrap();
+        assert_eq!(loaded.vertices.len(), vertices.len());
+        for (loaded, original) in loaded.vertices.iter().zip(&vertices) {
+            let scaled = Vec3::from_slice(&original.position) * 2.0;
+            assert!(Vec3::from_slice(&loaded.position).distance(scaled) < 1e-5);
+            assert!(Vec3::from(loaded.normal).distance(Vec3::from(original.normal)) < 1e-5);
+            assert_eq!(loaded.uv, original.uv);
+            for (loaded, original) in loaded.color.iter().zip(original.color) {
+                assert!((loaded - original).abs() <= 0.5 / 255.0);
+            }
+        }
+    }
+}
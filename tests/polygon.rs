@@ -0,0 +1,92 @@
+use glam::{Vec2, Vec3};
+use starstruck::shapes::{Polygon, PolygonError};
+
+fn square(center: Vec2, size: f32) -> Vec<Vec2> {
+    [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+        .map(|(x, y)| center + Vec2::new(x, y) * size / 2.0)
+        .to_vec()
+}
+
+fn area([a, b, c]: [Vec2; 3]) -> f32 {
+    (b - a).perp_dot(c - a) / 2.0
+}
+
+#[test]
+fn concave_outlines_and_holes_are_triangulated() {
+    // An L wound clockwise
+    let l = Polygon::new(
+        [
+            (0.0, 0.0),
+            (0.0, 2.0),
+            (1.0, 2.0),
+            (1.0, 1.0),
+            (2.0, 1.0),
+            (2.0, 0.0),
+        ]
+        .map(|(x, y)| Vec2::new(x, y))
+        .to_vec(),
+    );
+    let triangles = l.triangulate().unwrap();
+    assert_eq!(triangles.len(), 4);
+    assert!(triangles.iter().all(|triangle| area(*triangle) > 0.0));
+    assert!(
+        (triangles
+            .iter()
+            .map(|triangle| area(*triangle))
+            .sum::<f32>()
+            - 3.0)
+            .abs()
+            < 1e-5
+    );
+
+    let framed = Polygon::new(square(Vec2::ZERO, 4.0))
+        .with_hole(square(Vec2::new(-1.0, 0.0), 1.0))
+        .with_hole(square(Vec2::new(1.0, 0.5), 1.0));
+    let triangles = framed.triangulate().unwrap();
+    assert!(triangles.iter().all(|triangle| area(*triangle) > 0.0));
+    assert!(
+        (triangles
+            .iter()
+            .map(|triangle| area(*triangle))
+            .sum::<f32>()
+            - 14.0)
+            .abs()
+            < 1e-4
+    );
+    for triangle in triangles {
+        let centroid = (triangle[0] + triangle[1] + triangle[2]) / 3.0;
+        for hole in [Vec2::new(-1.0, 0.0), Vec2::new(1.0, 0.5)] {
+            let offset = (centroid - hole).abs();
+            assert!(offset.x >= 0.5 || offset.y >= 0.5, "{:?}", triangle);
+        }
+    }
+
+    assert_eq!(
+        Polygon::new(vec![Vec2::ZERO, Vec2::X]).triangulate(),
+        Err(PolygonError::TooFewPoints(2))
+    );
+}
+
+#[test]
+fn extrusions_have_caps_and_outward_sides() {
+    let polygon = Polygon::new(square(Vec2::ZERO, 2.0)).with_hole(square(Vec2::ZERO, 1.0));
+    let triangles = polygon.triangulate().unwrap().len();
+    let (vertices, count) = polygon.extrude(0.5).unwrap();
+    // Both caps and a quad for each of the 8 edges
+    assert_eq!(vertices.len(), triangles * 6 + 8 * 6);
+    assert_eq!(count as usize, vertices.len());
+
+    for triangle in vertices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| Vec3::from_slice(&triangle[i].position[..3]));
+        let normal = Vec3::from(triangle[0].normal);
+        assert!((b - a).cross(c - a).dot(normal) > 0.0);
+        assert!(a.z.abs() <= 0.25);
+
+        if normal.z == 0.0 {
+            // Sides of the outline point away from the center, the ones of the hole towards it
+            let center = (a + b + c) / 3.0;
+            let outside = center.x.abs().max(center.y.abs()) > 0.75;
+            assert_eq!(center.dot(normal) > 0.0, outside);
+        }
+    }
+}
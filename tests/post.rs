@@ -0,0 +1,47 @@
+use starstruck::renderer::{PostEffect, PostProcessing, Tonemapper};
+
+const INVERT: &str = r#"
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.uv);
+    return vec4<f32>(1.0 - color.rgb, color.a);
+}
+"#;
+
+#[test]
+fn effects_run_in_order_and_pending_ones_are_skipped() {
+    let post_processing = PostProcessing::new()
+        .with(PostEffect::bloom())
+        .with(PostEffect::custom("waiting", ""))
+        .with(PostEffect::custom("invert", INVERT))
+        .with(PostEffect::tonemapping(Tonemapper::Aces))
+        .with(PostEffect::gamma());
+
+    let ready = post_processing.ready().cloned().collect::<Vec<_>>();
+    assert_eq!(
+        ready,
+        vec![
+            PostEffect::bloom(),
+            PostEffect::custom("invert", INVERT),
+            PostEffect::tonemapping(Tonemapper::Aces),
+            PostEffect::gamma(),
+        ]
+    );
+}
+
+#[test]
+fn custom_effects_are_validated_without_a_gpu() {
+    assert!(PostEffect::custom("invert", INVERT).validate().is_ok());
+    assert!(PostEffect::custom("waiting", "").validate().is_ok());
+    assert!(PostEffect::vignette().validate().is_ok());
+
+    let error = PostEffect::custom("broken", "fn fs_main( {")
+        .validate()
+        .unwrap_err();
+    assert_eq!(error.label, "broken");
+
+    let no_entry_point = INVERT.replace("fs_main", "fs_invert");
+    assert!(PostEffect::custom("renamed", &no_entry_point)
+        .validate()
+        .is_err());
+}
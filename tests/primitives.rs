@@ -0,0 +1,92 @@
+use glam::{Vec2, Vec3};
+use starstruck::shapes::{Capsule, Cone, Cylinder, IcoSphere, Plane, Torus, UvSphere, Vertex};
+
+fn triangles(vertices: &[Vertex]) -> Vec<[Vertex; 3]> {
+    assert_eq!(vertices.len() % 3, 0);
+    vertices
+        .chunks_exact(3)
+        .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+        .collect()
+}
+
+/// Every triangle is wound counter-clockwise when seen from the side its normals point to
+fn assert_faces_follow_normals(vertices: &[Vertex]) {
+    for triangle in triangles(vertices) {
+        let [a, b, c] = triangle.map(|vertex| Vec3::from_slice(&vertex.position[..3]));
+        let face = (b - a).cross(c - a);
+        for vertex in triangle {
+            let normal = Vec3::from(vertex.normal);
+            assert!((normal.length() - 1.0).abs() < 1e-4);
+            assert!(face.dot(normal) > 0.0, "{:?}", triangle);
+        }
+    }
+}
+
+#[test]
+fn spheres_are_closed_and_wound_outwards() {
+    let (vertices, count) = UvSphere::new(2.0).with_segments(8, 4).into_raw();
+    // The triangles touching the poles are single ones
+    assert_eq!(count as usize, vertices.len());
+    assert_eq!(vertices.len(), 8 * (4 * 2 - 2) * 3);
+    assert_faces_follow_normals(&vertices);
+    for vertex in vertices.iter() {
+        let position = Vec3::from_slice(&vertex.position[..3]);
+        assert!((position.length() - 2.0).abs() < 1e-4);
+        assert!(position.normalize().dot(Vec3::from(vertex.normal)) > 0.999);
+        assert!(vertex.uv.iter().all(|value| (0.0..=1.0).contains(value)));
+    }
+
+    let (vertices, _) = IcoSphere::new(1.0).with_subdivisions(2).into_raw();
+    assert_eq!(vertices.len(), 20 * 16 * 3);
+    assert_faces_follow_normals(&vertices);
+}
+
+#[test]
+fn solids_of_revolution_face_outwards() {
+    for vertices in [
+        Cylinder::new(0.5, 2.0)
+            .with_segments(12)
+            .with_rings(3)
+            .into_raw()
+            .0,
+        Cone::new(0.5, 1.0).with_segments(12).into_raw().0,
+        Torus::new(1.0, 0.25).with_segments(16, 8).into_raw().0,
+        Capsule::new(0.5, 1.0).with_segments(12, 4).into_raw().0,
+    ] {
+        assert!(!vertices.is_empty());
+        assert_faces_follow_normals(&vertices);
+    }
+
+    // Side, top and bottom
+    let (vertices, _) = Cylinder::new(0.5, 2.0).with_segments(12).into_raw();
+    assert_eq!(vertices.len(), (12 * 2 + 12 + 12) * 3);
+    let (top, bottom) = vertices
+        .iter()
+        .fold((0.0f32, 0.0f32), |(top, bottom), vertex| {
+            (top.max(vertex.position[1]), bottom.min(vertex.position[1]))
+        });
+    assert_eq!((top, bottom), (1.0, -1.0));
+
+    let (vertices, _) = Capsule::new(0.5, 1.0).into_raw();
+    let top = vertices
+        .iter()
+        .map(|vertex| vertex.position[1])
+        .fold(0.0, f32::max);
+    assert!((top - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn planes_are_subdivided_grids() {
+    let (vertices, _) = Plane::new(4.0, 2.0).with_subdivisions(2, 3).into_raw();
+    assert_eq!(vertices.len(), 2 * 3 * 2 * 3);
+    assert_faces_follow_normals(&vertices);
+    assert!(vertices
+        .iter()
+        .all(|vertex| vertex.normal == [0.0, 1.0, 0.0]));
+
+    let corner = vertices
+        .iter()
+        .find(|vertex| vertex.position[0] == 2.0 && vertex.position[2] == 1.0)
+        .unwrap();
+    assert_eq!(Vec2::from(corner.uv), Vec2::ONE);
+}
@@ -0,0 +1,144 @@
+use starstruck::renderer::graph::{PassBuilder, SlotSize, TextureSlot, SCENE_COLOR, SWAPCHAIN};
+use starstruck::renderer::{
+    RenderContext, RenderGraph, RenderGraphError, RenderPass, RenderResources,
+};
+use wgpu::{CommandEncoder, TextureFormat, TextureUsages};
+
+struct TestPass {
+    name: &'static str,
+    reads: Vec<&'static str>,
+    writes: Vec<&'static str>,
+    creates: Vec<&'static str>,
+}
+
+impl TestPass {
+    fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            reads: vec![],
+            writes: vec![],
+            creates: vec![],
+        }
+    }
+
+    fn reads(mut self, slot: &'static str) -> Self {
+        self.reads.push(slot);
+        self
+    }
+
+    fn writes(mut self, slot: &'static str) -> Self {
+        self.writes.push(slot);
+        self
+    }
+
+    fn creates(mut self, slot: &'static str) -> Self {
+        self.creates.push(slot);
+        self
+    }
+}
+
+impl RenderPass for TestPass {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn setup(&self, builder: &mut PassBuilder) {
+        for slot in self.reads.iter() {
+            builder.read(slot);
+        }
+        for slot in self.writes.iter() {
+            builder.write(slot);
+        }
+        for slot in self.creates.iter() {
+            builder.create_texture(
+                slot,
+                TextureSlot {
+                    size: SlotSize::Window,
+                    format: TextureFormat::Rgba16Float,
+                    usage: TextureUsages::RENDER_ATTACHMENT,
+                    layers: 1,
+                },
+            );
+        }
+    }
+
+    fn render(
+        &mut self,
+        _context: &RenderContext<'_>,
+        _encoder: &mut CommandEncoder,
+        _resources: &RenderResources<'_>,
+    ) {
+    }
+}
+
+fn compile(passes: Vec<TestPass>) -> Result<Vec<String>, RenderGraphError> {
+    let mut graph = RenderGraph::new();
+    for pass in passes {
+        graph.add_pass(Box::new(pass));
+    }
+    graph.compile()?;
+    Ok(graph.order().into_iter().map(String::from).collect())
+}
+
+#[test]
+fn passes_run_after_the_passes_writing_what_they_read() {
+    let order = compile(vec![
+        TestPass::new("post").reads(SCENE_COLOR).writes(SWAPCHAIN),
+        TestPass::new("ui").reads(SWAPCHAIN).writes(SWAPCHAIN),
+        TestPass::new("bloom")
+            .reads("shadows")
+            .reads(SCENE_COLOR)
+            .writes(SCENE_COLOR),
+        TestPass::new("scene").reads("shadows").creates(SCENE_COLOR),
+        TestPass::new("shadows").creates("shadows"),
+    ])
+    .unwrap();
+
+    assert_eq!(order, vec!["shadows", "scene", "bloom", "post", "ui"]);
+}
+
+#[test]
+fn reading_a_slot_nothing_writes_is_an_error() {
+    let error = compile(vec![TestPass::new("post").reads(SCENE_COLOR)]).unwrap_err();
+    assert_eq!(
+        error,
+        RenderGraphError::MissingInput {
+            pass: String::from("post"),
+            slot: String::from(SCENE_COLOR)
+        }
+    );
+}
+
+#[test]
+fn cycles_and_duplicate_slots_are_errors() {
+    let error = compile(vec![
+        TestPass::new("a").reads("x").writes("y"),
+        TestPass::new("b").reads("y").writes("x"),
+    ])
+    .unwrap_err();
+    assert_eq!(
+        error,
+        RenderGraphError::Cycle(vec![String::from("a"), String::from("b")])
+    );
+
+    let error = compile(vec![
+        TestPass::new("a").creates("x"),
+        TestPass::new("b").creates("x"),
+    ])
+    .unwrap_err();
+    assert_eq!(error, RenderGraphError::DuplicateSlot(String::from("x")));
+}
+
+#[test]
+fn a_failed_compile_keeps_the_last_valid_order() {
+    let mut graph = RenderGraph::new();
+    graph.add_pass(Box::new(TestPass::new("scene").creates(SCENE_COLOR)));
+    graph.add_pass(Box::new(
+        TestPass::new("post").reads(SCENE_COLOR).writes(SWAPCHAIN),
+    ));
+    graph.compile().unwrap();
+
+    graph.add_pass(Box::new(TestPass::new("outline").reads("edges")));
+    assert!(graph.compile().is_err());
+    assert_eq!(graph.order(), vec!["scene", "post"]);
+}
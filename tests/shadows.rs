@@ -0,0 +1,69 @@
+use glam::{Mat4, Vec3};
+use starstruck::renderer::shadow::{cascade_splits, cascade_view_projections};
+use starstruck::renderer::SpotLight;
+
+fn inside_clip_space(view_projection: Mat4, point: Vec3) -> bool {
+    let ndc = view_projection.project_point3(point);
+    ndc.x.abs() <= 1.0 + 1e-4 && ndc.y.abs() <= 1.0 + 1e-4 && (-1e-4..=1.0 + 1e-4).contains(&ndc.z)
+}
+
+#[test]
+fn cascade_splits_grow_towards_the_far_plane() {
+    let splits = cascade_splits(0.1, 50.0, 4, 0.75);
+
+    assert_eq!(splits.len(), 4);
+    assert!(splits.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!((splits[3] - 50.0).abs() < 1e-3);
+    // Logarithmic splits keep the first cascade small
+    assert!(splits[0] < 50.0 / 4.0);
+
+    let uniform = cascade_splits(0.0, 1.0, 2, 0.75);
+    assert_eq!(uniform, vec![0.5, 1.0]);
+}
+
+#[test]
+fn cascades_contain_their_slice_of_the_camera_frustum() {
+    let (near, far) = (0.1, 40.0);
+    let camera = Mat4::perspective_rh(60f32.to_radians(), 16.0 / 9.0, near, far)
+        * Mat4::look_at_rh(Vec3::new(0.0, 2.0, 5.0), Vec3::ZERO, Vec3::Y);
+    let splits = cascade_splits(near, far, 3, 0.5);
+    let cascades =
+        cascade_view_projections(camera, near, far, &splits, Vec3::new(-0.3, -1.0, 0.2), 1024);
+    assert_eq!(cascades.len(), 3);
+
+    let inverse = camera.inverse();
+    let mut start = near;
+    for (cascade, end) in cascades.iter().zip(splits.iter()) {
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+            let near_corner = inverse.project_point3(Vec3::new(x, y, 0.0));
+            let far_corner = inverse.project_point3(Vec3::new(x, y, 1.0));
+            for distance in [start, *end] {
+                let corner = near_corner.lerp(far_corner, (distance - near) / (far - near));
+                assert!(inside_clip_space(*cascade, corner), "{corner} outside");
+            }
+        }
+        start = *end;
+    }
+}
+
+#[test]
+fn spot_lights_see_along_their_direction() {
+    let light = SpotLight {
+        position: Vec3::new(0.0, 4.0, 0.0),
+        direction: Vec3::NEG_Y,
+        range: 10.0,
+        ..Default::default()
+    };
+    let view_projection = light.view_projection();
+
+    assert!(inside_clip_space(view_projection, Vec3::ZERO));
+    assert!(!inside_clip_space(
+        view_projection,
+        Vec3::new(0.0, 8.0, 0.0)
+    ));
+    // Outside of the cone
+    assert!(!inside_clip_space(
+        view_projection,
+        Vec3::new(8.0, 0.0, 0.0)
+    ));
+}
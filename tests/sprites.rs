@@ -0,0 +1,98 @@
+use glam::{Vec2, Vec3};
+use starstruck::renderer::sprite::batch_sprites;
+use starstruck::renderer::{Anchor, Camera, Image, Images, Rect, Sprite, SpriteSettings};
+
+#[test]
+fn sprites_are_sized_and_mapped_from_their_region() {
+    let mut images = Images::new();
+    let sheet = images.add(Image::filled(64, 32, [255, 255, 255, 255]));
+
+    let sprite = Sprite::new(sheet)
+        .with_region(Rect::new(16.0, 0.0, 16.0, 32.0))
+        .with_position(Vec2::new(100.0, 50.0))
+        .with_anchor(Anchor::BottomLeft);
+    let vertices = sprite.vertices((64, 32));
+
+    assert_eq!(vertices[0].position, [100.0, 50.0, 0.0]);
+    assert_eq!(vertices[2].position, [116.0, 82.0, 0.0]);
+    // The bottom of the sprite samples the bottom row of the region
+    assert_eq!(vertices[0].uv, [0.25, 1.0]);
+    assert_eq!(vertices[2].uv, [0.5, 0.0]);
+
+    let flipped = sprite.with_flip(true, false).vertices((64, 32));
+    assert_eq!(flipped[0].uv, [0.5, 1.0]);
+    assert_eq!(flipped[0].position, [100.0, 50.0, 0.0]);
+}
+
+#[test]
+fn sprites_sharing_an_image_are_batched_back_to_front() {
+    let mut images = Images::new();
+    let a = images.add(Image::filled(1, 1, [255, 0, 0, 255]));
+    let b = images.add(Image::filled(1, 1, [0, 255, 0, 255]));
+
+    let mut sprites = vec![
+        Sprite::new(b).with_z(1.0),
+        Sprite::new(a),
+        Sprite::new(b),
+        Sprite::new(a).with_z(2.0),
+        Sprite::new(a),
+    ];
+    let batches = batch_sprites(&mut sprites);
+
+    let batches = batches
+        .iter()
+        .map(|batch| (batch.image, batch.sprites.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(batches, vec![(a, 0..2), (b, 2..4), (a, 4..5)]);
+    assert_eq!(sprites[4].z, 2.0);
+}
+
+#[test]
+fn png_images_are_decoded_to_rgba() {
+    let mut bytes = vec![];
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[255, 0, 0, 0, 0, 255]).unwrap();
+    }
+
+    let image = Image::from_png(&bytes).unwrap();
+    assert_eq!(image.size(), (2, 1));
+    assert_eq!(image.data, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+    assert!(Image::new(2, 2, vec![0; 4]).is_err());
+}
+
+#[test]
+fn sprites_stay_flat_under_perspective_cameras_unless_asked_not_to() {
+    let camera = Camera {
+        position: Vec3::new(100.0, 0.0, 2.0),
+        look_at: Vec3::new(100.0, 0.0, 0.0),
+        ..Default::default()
+    };
+    let settings = SpriteSettings::default();
+    // Sprites follow the camera but stay in pixels
+    let corner = settings
+        .view_projection(Some(&camera), 800.0, 600.0)
+        .project_point3(Vec3::new(500.0, 300.0, 0.0));
+    assert!(corner.truncate().abs_diff_eq(Vec2::ONE, 1e-5));
+    assert_eq!(
+        settings.view_projection(None, 800.0, 600.0),
+        settings.view_projection(Some(&Camera::default()), 800.0, 600.0)
+    );
+
+    let settings = SpriteSettings {
+        use_camera_projection: true,
+    };
+    assert_eq!(
+        settings.view_projection(Some(&camera), 800.0, 600.0),
+        camera.view_projection(800.0 / 600.0)
+    );
+    // Orthographic cameras keep their zoom either way
+    let camera = Camera::orthographic(60.0);
+    assert_eq!(
+        SpriteSettings::default().view_projection(Some(&camera), 800.0, 600.0),
+        camera.view_projection(800.0 / 600.0)
+    );
+}
@@ -0,0 +1,82 @@
+use starry_ecs::resources::Resource;
+use starry_ecs::systems::DefaultOrdering;
+use starry_ecs::World;
+use starstruck::state::State;
+use starstruck::StarstruckEngine;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+}
+
+#[derive(Debug, Default)]
+struct Log {
+    entries: Vec<&'static str>,
+}
+impl Resource for Log {}
+
+fn enter_menu(world: &World) {
+    world.get_resource_mut::<Log>().entries.push("enter menu");
+}
+
+fn exit_menu(world: &World) {
+    world.get_resource_mut::<Log>().entries.push("exit menu");
+}
+
+fn enter_playing(world: &World) {
+    world
+        .get_resource_mut::<Log>()
+        .entries
+        .push("enter playing");
+}
+
+fn menu(world: &World) {
+    world.get_resource_mut::<Log>().entries.push("menu");
+    world
+        .get_resource_mut::<State<GameState>>()
+        .set(GameState::Playing);
+}
+
+fn playing(world: &World) {
+    world.get_resource_mut::<Log>().entries.push("playing");
+}
+
+#[test]
+fn state_transitions_run_enter_exit_and_update_systems() {
+    let mut engine = StarstruckEngine::new_empty("State Test")
+        .add_resource(Log::default())
+        .add_state(GameState::Menu)
+        .add_system_on_enter(GameState::Menu, enter_menu)
+        .add_system_on_exit(GameState::Menu, exit_menu)
+        .add_system_on_enter(GameState::Playing, enter_playing)
+        .add_system_in_state(DefaultOrdering::Run, GameState::Menu, menu)
+        .add_system_in_state(DefaultOrdering::Run, GameState::Playing, playing);
+
+    engine.update();
+    engine.update();
+    engine.update();
+
+    let state = engine.world().get_resource::<State<GameState>>();
+    assert!(state.is_in(&GameState::Playing));
+    assert_eq!(state.previous(), Some(&GameState::Menu));
+    assert_eq!(
+        engine.world().get_resource::<Log>().entries,
+        vec![
+            "enter menu",
+            "menu",
+            "exit menu",
+            "enter playing",
+            "playing",
+            "playing"
+        ]
+    );
+}
+
+#[test]
+#[should_panic]
+fn states_cannot_be_added_twice() {
+    StarstruckEngine::new_empty("State Test")
+        .add_state(GameState::Menu)
+        .add_state(GameState::Playing);
+}
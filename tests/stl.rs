@@ -0,0 +1,78 @@
+use glam::{Mat4, Vec3};
+use starstruck::assets::{StlError, StlFormat};
+use starstruck::renderer::Mesh;
+use starstruck::shapes::IcoSphere;
+
+#[test]
+fn meshes_round_trip_through_both_formats() {
+    let (vertices, _) = IcoSphere::new(1.0).with_subdivisions(1).into_raw();
+    let mesh = Mesh::new(vertices.clone()).with_transform(Mat4::from_translation(Vec3::X));
+    for format in [StlFormat::Ascii, StlFormat::Binary] {
+        let stl = mesh.to_stl(format);
+        assert_eq!(stl.starts_with(b"solid"), format == StlFormat::Ascii);
+        let loaded = Mesh::from_stl(&stl).unwrap();
+        assert_eq!(loaded.vertices.len(), vertices.len());
+        for (loaded, original) in loaded.vertices.iter().zip(&vertices) {
+            let moved = Vec3::from_slice(&original.position) + Vec3::X;
+            assert!(Vec3::from_slice(&loaded.position).distance(moved) < 1e-5);
+            // Each corner gets the facet normal, pointing away from the center
+            assert!(Vec3::from(loaded.normal).dot(moved - Vec3::X) > 0.0);
+        }
+    }
+}
+
+#[test]
+fn ascii_files_are_parsed_and_checked() {
+    // A binary header starting with `solid` is still read as binary
+    let mut binary = b"solid but binary".to_vec();
+    binary.resize(80, 0);
+    binary.extend_from_slice(&1u32.to_le_bytes());
+    for value in [
+        0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+    ] {
+        binary.extend_from_slice(&value.to_le_bytes());
+    }
+    binary.extend_from_slice(&[0, 0]);
+    let mesh = Mesh::from_stl(&binary).unwrap();
+    assert_eq!(mesh.vertices[2].position, [0.0, 1.0, 0.0, 1.0]);
+    assert_eq!(mesh.vertices[0].normal, [0.0, 0.0, 1.0]);
+
+    let ascii = "solid part\n\
+                 facet normal 0 0 0\n outer loop\n\
+                 vertex 0 0 0\n vertex 1 0 0\n vertex 0 1 0\n\
+                 endloop\n endfacet\nendsolid part\n";
+    let mesh = Mesh::from_stl(ascii.as_bytes()).unwrap();
+    assert_eq!(mesh.vertices.len(), 3);
+    // No normal in the file means flat shading
+    assert_eq!(mesh.vertices[1].normal, [0.0; 3]);
+
+    let broken = ascii.replace("vertex 1 0 0", "vertex 1 zero 0");
+    assert!(matches!(
+        Mesh::from_stl(broken.as_bytes()),
+        Err(StlError::Parse { line: 5, .. })
+    ));
+    // A facet missing a vertex is reported where it ends, not at the end of the file
+    let short = "solid part\n\
+                 facet normal 0 0 1\n outer loop\n vertex 0 0 0\n vertex 1 0 0\n\
+                 endloop\n endfacet\n\
+                 facet normal 0 0 1\n outer loop\n\
+                 vertex 0 0 0\n vertex 1 0 0\n vertex 0 1 0\n\
+                 endloop\n endfacet\nendsolid part\n";
+    assert!(matches!(
+        Mesh::from_stl(short.as_bytes()),
+        Err(StlError::Parse { line: 7, .. })
+    ));
+    let no_end = short.replacen(" endfacet\n", "", 1);
+    assert!(matches!(
+        Mesh::from_stl(no_end.as_bytes()),
+        Err(StlError::Parse { line: 7, .. })
+    ));
+    assert!(matches!(
+        Mesh::from_stl(&binary[..100]),
+        Err(StlError::Size {
+            triangles: 1,
+            expected: 134,
+            actual: 100
+        })
+    ));
+}
@@ -0,0 +1,70 @@
+use starstruck::renderer::text::{layout_text, GlyphCache};
+use starstruck::renderer::{Font, Fonts, TextAlignment};
+
+fn font() -> Font {
+    Font::load("tests/assets/Cantarell-Regular.ttf").unwrap()
+}
+
+#[test]
+fn text_wraps_between_words_and_on_newlines() {
+    let font = font();
+    let single = layout_text(&font, "hello world", 16.0, 1.0, TextAlignment::Left, None);
+    assert_eq!(single.lines, 1);
+    assert!(single.glyphs.iter().all(|glyph| glyph.line == 0));
+
+    let wrapped = layout_text(
+        &font,
+        "hello world",
+        16.0,
+        1.0,
+        TextAlignment::Left,
+        Some(single.size.x - 1.0),
+    );
+    assert_eq!(wrapped.lines, 2);
+    let world = wrapped
+        .glyphs
+        .iter()
+        .find(|glyph| glyph.character == 'w')
+        .unwrap();
+    assert_eq!(world.line, 1);
+    assert_eq!(world.position.x, 0.0);
+    assert!(wrapped.size.x < single.size.x);
+
+    let spaced = layout_text(&font, "a\nb", 16.0, 2.0, TextAlignment::Left, None);
+    assert_eq!(spaced.lines, 2);
+    let gap = spaced.glyphs[1].position.y - spaced.glyphs[0].position.y;
+    assert!((gap - font.line_height(16.0) * 2.0).abs() < 1e-3);
+}
+
+#[test]
+fn lines_are_aligned_inside_the_max_width() {
+    let font = font();
+    let left = layout_text(&font, "hi", 20.0, 1.0, TextAlignment::Left, Some(200.0));
+    let center = layout_text(&font, "hi", 20.0, 1.0, TextAlignment::Center, Some(200.0));
+    let right = layout_text(&font, "hi", 20.0, 1.0, TextAlignment::Right, Some(200.0));
+
+    let width = left.size.x;
+    assert_eq!(left.glyphs[0].position.x, 0.0);
+    assert!((center.glyphs[0].position.x - (200.0 - width) / 2.0).abs() < 1e-3);
+    assert!((right.glyphs[0].position.x - (200.0 - width)).abs() < 1e-3);
+}
+
+#[test]
+fn glyphs_are_rasterized_once_into_the_cache() {
+    let font = font();
+    let mut fonts = Fonts::new();
+    let id = fonts.add(font.clone());
+    let layout = layout_text(&font, "A A", 32.0, 1.0, TextAlignment::Left, None);
+    let (a, space) = (layout.glyphs[0].glyph, layout.glyphs[1].glyph);
+
+    let mut cache = GlyphCache::new(64);
+    let first = cache.get_or_insert(id, &font, a, 32.0).unwrap().unwrap();
+    assert!(first.region.width() > 0.0 && first.region.height() > 0.0);
+    // Glyphs sit above the baseline, so their top is above the glyph's position
+    assert!(first.offset.y < 0.0);
+    assert_eq!(cache.get_or_insert(id, &font, a, 32.0), Ok(Some(first)));
+    assert_eq!(cache.get_or_insert(id, &font, space, 32.0), Ok(None));
+
+    let mut tiny = GlyphCache::new(8);
+    assert!(tiny.get_or_insert(id, &font, a, 32.0).is_err());
+}
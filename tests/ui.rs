@@ -0,0 +1,127 @@
+use glam::Vec2;
+use starry_ecs::World;
+use starstruck::events::Events;
+use starstruck::input::Input;
+use starstruck::ui::layout::layout_root;
+use starstruck::ui::{
+    ui_interaction_system, AlignItems, ButtonClicked, Edges, FlexDirection, Interaction,
+    JustifyContent, Style, UiAnchor, UiNode, UiRoot, Val,
+};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceId, ElementState, MouseButton, WindowEvent};
+
+fn boxed(name: &str, width: f32, height: f32) -> UiNode {
+    UiNode::container()
+        .with_name(name)
+        .with_style(Style::new().with_size(Val::Px(width), Val::Px(height)))
+}
+
+#[test]
+fn row_lays_out_children_with_grow_and_alignment() {
+    let mut root = UiRoot::new(
+        UiNode::container()
+            .with_style(
+                Style::new()
+                    .with_size(Val::Px(200.0), Val::Px(100.0))
+                    .with_direction(FlexDirection::Row)
+                    .with_padding(Edges::all(10.0))
+                    .with_gap(5.0)
+                    .with_align_items(AlignItems::Center),
+            )
+            .with_child(boxed("a", 50.0, 20.0))
+            .with_child(
+                UiNode::container().with_name("grow").with_style(
+                    Style::new()
+                        .with_size(Val::Auto, Val::Px(40.0))
+                        .with_grow(1.0),
+                ),
+            ),
+    );
+    layout_root(&mut root, Vec2::new(800.0, 600.0), 1.0, &|_| Vec2::ZERO);
+
+    let a = root.node.find("a").unwrap().layout();
+    assert_eq!(a.min, Vec2::new(10.0, 40.0));
+    assert_eq!(a.max, Vec2::new(60.0, 60.0));
+    let grow = root.node.find("grow").unwrap().layout();
+    assert_eq!(grow.min, Vec2::new(65.0, 30.0));
+    assert_eq!(grow.max, Vec2::new(190.0, 70.0));
+
+    let mut root = UiRoot::new(
+        UiNode::container()
+            .with_style(
+                Style::new()
+                    .with_size(Val::Px(100.0), Val::Percent(50.0))
+                    .with_direction(FlexDirection::Column)
+                    .with_justify_content(JustifyContent::SpaceBetween),
+            )
+            .with_children([boxed("top", 100.0, 10.0), boxed("bottom", 100.0, 10.0)]),
+    )
+    .with_anchor(UiAnchor::BottomRight)
+    .with_offset(Vec2::new(-10.0, 0.0));
+    // A scale factor of 2 doubles every length
+    layout_root(&mut root, Vec2::new(800.0, 600.0), 2.0, &|_| Vec2::ZERO);
+
+    assert_eq!(root.node.layout().min, Vec2::new(580.0, 300.0));
+    assert_eq!(root.node.layout().max, Vec2::new(780.0, 600.0));
+    let bottom = root.node.find("bottom").unwrap().layout();
+    assert_eq!(bottom.min, Vec2::new(580.0, 580.0));
+}
+
+#[test]
+fn buttons_are_hovered_pressed_and_clicked() {
+    let device_id = unsafe { DeviceId::dummy() };
+    let mut root = UiRoot::new(
+        UiNode::container()
+            .with_style(Style::new().with_padding(Edges::all(10.0)))
+            .with_child(
+                UiNode::button()
+                    .with_name("play")
+                    .with_child(boxed("label", 80.0, 30.0)),
+            ),
+    );
+    layout_root(&mut root, Vec2::new(800.0, 600.0), 1.0, &|_| Vec2::ZERO);
+    let world = World::new()
+        .add_resource(Input::new())
+        .add_resource(Events::<ButtonClicked>::new())
+        .add_component(root);
+
+    let send = |event: WindowEvent| {
+        world.get_resource_mut::<Input>().handle_event(&event);
+        ui_interaction_system(&world);
+        world.get_resource_mut::<Input>().clear();
+        world.get_resource_mut::<Events<ButtonClicked>>().update();
+    };
+    let interaction = || {
+        world.try_get_components::<UiRoot>().unwrap()[0]
+            .node
+            .find("play")
+            .unwrap()
+            .interaction()
+    };
+    let mouse = |state| WindowEvent::MouseInput {
+        device_id,
+        state,
+        button: MouseButton::Left,
+        modifiers: Default::default(),
+    };
+
+    #[allow(deprecated)]
+    send(WindowEvent::CursorMoved {
+        device_id,
+        position: PhysicalPosition::new(50.0, 20.0),
+        modifiers: Default::default(),
+    });
+    assert_eq!(interaction(), Interaction::Hovered);
+
+    #[allow(deprecated)]
+    send(mouse(ElementState::Pressed));
+    assert_eq!(interaction(), Interaction::Pressed);
+    assert!(world.get_resource::<Events<ButtonClicked>>().is_empty());
+
+    #[allow(deprecated)]
+    send(mouse(ElementState::Released));
+    assert_eq!(interaction(), Interaction::Hovered);
+    let events = world.get_resource::<Events<ButtonClicked>>();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events.iter().next().unwrap().name.as_deref(), Some("play"));
+}